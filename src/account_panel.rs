@@ -0,0 +1,169 @@
+use gui_core::{Element, widgets::*};
+use gui_core::widgets::container::{Background, Padding};
+use gui_core::widgets::text::text_signal;
+use gui_reactive::Signal;
+use std::sync::mpsc;
+use vello::peniko::{Color, Gradient};
+
+use crate::commands::Command;
+use crate::helpers::entitlements::{format_date, GenerationUsage};
+use crate::helpers::utilities::AuthState;
+
+/// Text shown in the account panel, filled from the auth state
+#[derive(Clone)]
+pub struct AccountSignals {
+    pub visible: Signal<bool>,
+    pub plan: Signal<String>,
+    pub description: Signal<String>,
+    pub status: Signal<String>,
+    /// When the plan renews or ends
+    pub renewal: Signal<String>,
+    pub usage: Signal<String>,
+    /// Refresh progress, errors or a notice about the plan
+    pub message: Signal<String>,
+}
+
+impl AccountSignals {
+    pub fn new() -> Self {
+        Self {
+            visible: Signal::new(false),
+            plan: Signal::new(String::new()),
+            description: Signal::new(String::new()),
+            status: Signal::new(String::new()),
+            renewal: Signal::new(String::new()),
+            usage: Signal::new(String::new()),
+            message: Signal::new(String::new()),
+        }
+    }
+
+    /// Show what's known about the subscription without asking the backend
+    pub fn load(&self, auth_state: &AuthState) {
+        let subscription = auth_state.known_subscription();
+        let entitlements = auth_state.entitlements();
+        let now = chrono::Utc::now();
+
+        let plan = subscription.as_ref().and_then(|subscription| subscription.plan.as_ref());
+        self.plan.set(format!(
+            "Plan: {}",
+            plan.and_then(|plan| plan.name.clone()).unwrap_or_else(|| "Unknown".to_string())
+        ));
+        self.description.set(plan.and_then(|plan| plan.description.clone()).unwrap_or_default());
+
+        let status = match &subscription {
+            Some(subscription) => status_label(&subscription.subscription_status),
+            None => "No subscription".to_string(),
+        };
+        self.status.set(if auth_state.offline {
+            format!("Status: {} (offline, as last seen)", status)
+        } else {
+            format!("Status: {}", status)
+        });
+
+        self.renewal.set(match &subscription {
+            Some(subscription) => match subscription.current_period_end {
+                Some(end) if subscription.cancel_at_period_end && end > now => format!("Ends on {}", format_date(end)),
+                Some(end) if subscription.cancel_at_period_end => format!("Ended on {}", format_date(end)),
+                Some(end) if end > now => format!("Renews on {}", format_date(end)),
+                Some(end) => format!("Period ended on {}", format_date(end)),
+                None => String::new(),
+            },
+            None => String::new(),
+        });
+
+        let used = GenerationUsage::load().used(&entitlements.period);
+        self.usage.set(match entitlements.generation_quota {
+            Some(quota) => format!("Cloud motion generations: {} of {} used this period", used.min(quota), quota),
+            None => format!("Cloud motion generations: {} this period", used),
+        });
+
+        self.message.set(entitlements.notice.unwrap_or_default());
+    }
+
+    pub fn close(&self) {
+        self.visible.set(false);
+    }
+}
+
+/// "PAST_DUE" as "Past due"
+fn status_label(status: &str) -> String {
+    let status = status.to_lowercase().replace('_', " ");
+    let mut chars = status.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Unknown".to_string(),
+    }
+}
+
+pub fn create_account_panel(
+    command_tx: mpsc::Sender<Command>,
+    signals: AccountSignals,
+    button_normal: Gradient,
+    button_hover: Gradient,
+    button_pressed: Gradient,
+) -> Element {
+    let panel_button = |label: &str, command: Command| {
+        Element::new_widget(Box::new(
+            button(label)
+                .with_font_size(12.0)
+                .with_width(110.0)
+                .with_height(25.0)
+                .with_backgrounds(
+                    Background::Gradient(button_normal.clone()),
+                    Background::Gradient(button_hover.clone()),
+                    Background::Gradient(button_pressed.clone())
+                )
+                .on_click({
+                    let tx = command_tx.clone();
+                    move || {
+                        let _ = tx.send(command.clone());
+                    }
+                })
+        ))
+    };
+
+    let line = |signal: &Signal<String>, font_size: f32, color: Color| {
+        Element::new_widget(Box::new(
+            text_signal(signal.clone())
+                .with_font_size(font_size)
+                .with_color(color)
+        ))
+    };
+
+    container()
+        .absolute()
+        .with_position(540.0, 60.0)
+        .with_size(300.0, 240.0)
+        .with_background_color(Color::rgba8(50, 50, 60, 240))
+        .with_border_radius(8.0)
+        .with_padding(Padding::all(15.0))
+        .with_shadow(4.0, 4.0, 8.0, Color::rgba8(0, 0, 0, 150))
+        .with_display_signal(signals.visible.clone())
+        .with_child(
+            column()
+                .with_size(270.0, 210.0)
+                .with_main_axis_alignment(MainAxisAlignment::Start)
+                .with_cross_axis_alignment(CrossAxisAlignment::Start)
+                .with_child(Element::new_widget(Box::new(
+                    text("Account")
+                        .with_font_size(14.0)
+                        .with_color(Color::rgba8(255, 255, 255, 255))
+                )))
+                .with_child(line(&signals.plan, 12.0, Color::rgba8(255, 255, 255, 255)))
+                .with_child(line(&signals.description, 11.0, Color::rgba8(180, 180, 180, 255)))
+                .with_child(line(&signals.status, 12.0, Color::rgba8(200, 200, 200, 255)))
+                .with_child(line(&signals.renewal, 12.0, Color::rgba8(200, 200, 200, 255)))
+                .with_child(line(&signals.usage, 12.0, Color::rgba8(200, 200, 200, 255)))
+                .with_child(line(&signals.message, 11.0, Color::rgba8(255, 200, 120, 255)))
+                .with_child(
+                    row()
+                        .with_size(270.0, 35.0)
+                        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+                        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+                        .with_child(panel_button("Refresh", Command::RefreshAccount))
+                        .with_child(panel_button("Close", Command::CloseAccountPanel))
+                        .into_container_element()
+                )
+                .into_container_element()
+        )
+        .into_container_element()
+}
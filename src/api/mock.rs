@@ -0,0 +1,201 @@
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::{
+    ApiAnimationData, ApiAnimationProperty, ApiKeyframe, ApiKeyframeValue, ApiPosition,
+    AuthResponse, JwtData, GENERATE_MOTION_PATH, LOGIN_PATH, REFRESH_TOKEN_PATH, SUBSCRIPTION_DETAILS_PATH,
+};
+
+pub const DEFAULT_MOCK_PORT: u16 = 3000;
+
+/// Length of the canned animation
+const MOCK_DURATION_MS: i32 = 3000;
+
+/// Serve canned responses for every backend endpoint until the process is stopped,
+/// so sign in and motion generation work without the real backend.
+/// Point the editor at it with `STUNTS_API_URL=http://127.0.0.1:<port>`.
+pub async fn run_mock_server(port: u16) -> Result<(), String> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .map_err(|e| format!("Couldn't listen on port {}: {}", port, e))?;
+
+    println!("Mock API listening on http://127.0.0.1:{}", port);
+
+    loop {
+        let (stream, _) = listener.accept().await.map_err(|e| e.to_string())?;
+
+        tokio::spawn(async move {
+            if let Err(e) = serve(stream).await {
+                println!("Mock API request failed: {}", e);
+            }
+        });
+    }
+}
+
+/// Answer a single HTTP/1.1 request and close the connection
+async fn serve(stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 {
+            break;
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    println!("Mock API: {} {}", method, path);
+
+    let (status, response) = respond(&method, &path, &body);
+    let response = response.to_string();
+
+    let mut stream = reader.into_inner();
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                response.len(),
+                response
+            )
+            .as_bytes(),
+        )
+        .await?;
+    stream.shutdown().await
+}
+
+fn respond(method: &str, path: &str, body: &Value) -> (&'static str, Value) {
+    let path = path.split('?').next().unwrap_or_default();
+
+    match (method, path) {
+        ("POST", LOGIN_PATH) | ("POST", REFRESH_TOKEN_PATH) => {
+            let auth_response = AuthResponse {
+                jwt_data: JwtData {
+                    token: "mock-token".to_string(),
+                    expiry: 24 * 60 * 60,
+                },
+            };
+            ("200 OK", json!(auth_response))
+        }
+        ("GET", SUBSCRIPTION_DETAILS_PATH) => {
+            let period_end = chrono::Utc::now() + chrono::Duration::days(30);
+            (
+                "200 OK",
+                json!({
+                    "subscriptionStatus": "ACTIVE",
+                    "currentPeriodEnd": period_end,
+                    "plan": {
+                        "id": "mock",
+                        "name": "Mock Plan",
+                        "description": "Canned responses from the local mock API"
+                    },
+                    "cancelAtPeriodEnd": false
+                }),
+            )
+        }
+        ("POST", GENERATE_MOTION_PATH) => ("200 OK", json!(canned_motion(body))),
+        _ => (
+            "404 Not Found",
+            json!({ "error": format!("No mock response for {} {}", method, path) }),
+        ),
+    }
+}
+
+/// A simple animation that follows the drawn arrow through its waypoints, or stays put without one
+fn canned_motion(request: &Value) -> ApiAnimationData {
+    let arrow = &request["arrow_positions"];
+    let coordinate = |value: &Value, key: &str| value[key].as_f64().unwrap_or(0.0).round() as i32;
+
+    let mut path: Vec<ApiPosition> = request["waypoints"]
+        .as_array()
+        .map(|waypoints| {
+            waypoints
+                .iter()
+                .map(|point| ApiPosition {
+                    x: coordinate(point, "x"),
+                    y: coordinate(point, "y"),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if path.len() < 2 {
+        path = vec![
+            ApiPosition {
+                x: coordinate(arrow, "startX"),
+                y: coordinate(arrow, "startY"),
+            },
+            ApiPosition {
+                x: coordinate(arrow, "endX"),
+                y: coordinate(arrow, "endY"),
+            },
+        ];
+    }
+
+    // Vary the easing when the editor asks for several variants
+    let easing = ["EaseInOut", "EaseOut", "EaseIn", "Linear"][request["variant"].as_u64().unwrap_or(0) as usize % 4];
+
+    // Values are spread evenly over the duration
+    let property = |name: &str, values: Vec<ApiKeyframeValue>| {
+        let steps = values.len().saturating_sub(1).max(1) as i32;
+        ApiAnimationProperty {
+            name: name.to_string(),
+            keyframes: values
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| ApiKeyframe {
+                    time: MOCK_DURATION_MS * i as i32 / steps,
+                    value,
+                    easing: easing.to_string(),
+                    ..ApiKeyframe::default()
+                })
+                .collect(),
+        }
+    };
+
+    ApiAnimationData {
+        id: uuid::Uuid::new_v4().to_string(),
+        duration: MOCK_DURATION_MS,
+        properties: vec![
+            property(
+                "Position",
+                path.into_iter()
+                    .map(|position| ApiKeyframeValue::Position { Position: position })
+                    .collect(),
+            ),
+            property(
+                "Rotation",
+                vec![ApiKeyframeValue::Rotation { Rotation: 0 }, ApiKeyframeValue::Rotation { Rotation: 0 }],
+            ),
+            property(
+                "Scale",
+                vec![ApiKeyframeValue::Scale { Scale: 100 }, ApiKeyframeValue::Scale { Scale: 100 }],
+            ),
+            property(
+                "Opacity",
+                vec![ApiKeyframeValue::Opacity { Opacity: 100 }, ApiKeyframeValue::Opacity { Opacity: 100 }],
+            ),
+        ],
+    }
+}
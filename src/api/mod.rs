@@ -0,0 +1,293 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use stunts_engine::saved_state::get_ground_truth_dir;
+
+use crate::helpers::utilities::{AuthToken, SubscriptionDetails, API_URL};
+
+pub mod mock;
+pub mod motion;
+pub mod session;
+
+pub use motion::{ApiAnimationData, ApiAnimationProperty, ApiKeyframe, ApiKeyframeValue, ApiPosition, ConversionMode};
+pub use session::{AuthSession, Unauthorized};
+
+const API_SETTINGS_FILENAME: &str = "api.json";
+
+/// Overrides the backend URL from the settings file, e.g. `STUNTS_API_URL=http://localhost:4000`
+pub const API_URL_ENV: &str = "STUNTS_API_URL";
+
+/// Set to 1 to reject motion responses containing values the editor doesn't recognize
+pub const API_STRICT_ENV: &str = "STUNTS_API_STRICT";
+
+pub const LOGIN_PATH: &str = "/api/auth/login";
+/// Trades a token that's still valid for a new one, answering like login.
+/// Backends without it can turn refreshing off with `"refresh_path": null` in `api.json`.
+pub const REFRESH_TOKEN_PATH: &str = "/api/auth/refresh";
+pub const SUBSCRIPTION_DETAILS_PATH: &str = "/api/subscription/details";
+pub const GENERATE_MOTION_PATH: &str = "/api/projects/generate-motion";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JwtData {
+    pub token: String,
+    pub expiry: i64, // Seconds until the token expires
+}
+
+impl JwtData {
+    /// The token to store, with its expiry counted from now
+    pub fn to_auth_token(&self) -> AuthToken {
+        AuthToken {
+            token: self.token.clone(),
+            expiry: Some(chrono::Utc::now() + chrono::Duration::seconds(self.expiry)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuthResponse {
+    #[serde(rename = "jwtData")]
+    pub jwt_data: JwtData,
+}
+
+/// Why a backend call failed, telling a rejected token apart from an unreachable backend
+#[derive(Debug, Clone)]
+pub enum ApiError {
+    /// 401: the token is missing, expired or revoked
+    Unauthorized,
+    /// Any other unsuccessful status
+    Status { status: u16, message: String },
+    /// The backend couldn't be reached
+    Network(String),
+    /// The response wasn't what the endpoint should return
+    Parse(String),
+}
+
+impl ApiError {
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(self, ApiError::Unauthorized)
+    }
+
+    async fn from_response(response: reqwest::Response) -> Self {
+        match response.status().as_u16() {
+            401 => ApiError::Unauthorized,
+            status => ApiError::Status {
+                status,
+                message: response.text().await.unwrap_or_default(),
+            },
+        }
+    }
+}
+
+impl Unauthorized for ApiError {
+    fn is_unauthorized(&self) -> bool {
+        ApiError::is_unauthorized(self)
+    }
+
+    fn session_expired() -> Self {
+        ApiError::Unauthorized
+    }
+
+    fn network(message: String) -> Self {
+        ApiError::Network(message)
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            ApiError::Parse(e.to_string())
+        } else if e.status().map(|status| status.as_u16()) == Some(401) {
+            ApiError::Unauthorized
+        } else {
+            ApiError::Network(e.to_string())
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Unauthorized => write!(f, "the token was rejected"),
+            ApiError::Status { status, message } => write!(f, "status {}: {}", status, message),
+            ApiError::Network(message) => write!(f, "backend unreachable: {}", message),
+            ApiError::Parse(message) => write!(f, "unexpected response: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// Backend settings stored next to the app preferences
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiSettings {
+    /// Base URL of the backend, without a trailing slash
+    pub base_url: String,
+    /// Reject motion responses with unknown easings, path types, key types or properties
+    /// instead of falling back to defaults
+    pub strict_conversion: bool,
+    /// Endpoint that renews the auth token, `None` when the backend has none
+    pub refresh_path: Option<String>,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            base_url: API_URL.to_string(),
+            strict_conversion: false,
+            refresh_path: Some(REFRESH_TOKEN_PATH.to_string()),
+        }
+    }
+}
+
+impl ApiSettings {
+    fn path() -> PathBuf {
+        get_ground_truth_dir()
+            .expect("Couldn't get CommonOS directory")
+            .join(API_SETTINGS_FILENAME)
+    }
+
+    /// Settings from `api.json`, with `STUNTS_API_URL` and `STUNTS_API_STRICT` taking precedence
+    pub fn load() -> ApiSettings {
+        let mut settings = match fs::read_to_string(Self::path()) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                println!("Failed to parse API settings, using defaults: {}", e);
+                ApiSettings::default()
+            }),
+            Err(_) => ApiSettings::default(),
+        };
+
+        if let Ok(base_url) = std::env::var(API_URL_ENV) {
+            if !base_url.trim().is_empty() {
+                settings.base_url = base_url;
+            }
+        }
+
+        if let Ok(strict) = std::env::var(API_STRICT_ENV) {
+            settings.strict_conversion = matches!(strict.trim(), "1" | "true" | "yes");
+        }
+
+        settings.base_url = settings.base_url.trim().trim_end_matches('/').to_string();
+        settings
+    }
+}
+
+/// Client for every backend endpoint the editor calls
+#[derive(Clone)]
+pub struct ApiClient {
+    base_url: String,
+    conversion_mode: ConversionMode,
+    refresh_path: Option<String>,
+    http: reqwest::Client,
+}
+
+impl ApiClient {
+    pub fn new(settings: ApiSettings) -> Self {
+        Self {
+            base_url: settings.base_url,
+            conversion_mode: if settings.strict_conversion {
+                ConversionMode::Strict
+            } else {
+                ConversionMode::Lenient
+            },
+            refresh_path: settings.refresh_path.filter(|path| !path.trim().is_empty()),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Client configured from the settings file and environment
+    pub fn from_settings() -> Self {
+        Self::new(ApiSettings::load())
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// How motion responses from this backend should be converted
+    pub fn conversion_mode(&self) -> ConversionMode {
+        self.conversion_mode
+    }
+
+    /// Whether the backend is configured with a token refresh endpoint
+    pub fn can_refresh(&self) -> bool {
+        self.refresh_path.is_some()
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    pub async fn login(&self, email: &str, password: &str) -> anyhow::Result<AuthResponse> {
+        let auth_data = serde_json::json!({
+            "email": email,
+            "password": password
+        });
+
+        let response = self
+            .http
+            .post(self.url(LOGIN_PATH))
+            .json(&auth_data)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            let auth_response: AuthResponse = response.json().await?;
+            Ok(auth_response)
+        } else {
+            Err(anyhow::anyhow!("Authentication failed: {}", response.status()))
+        }
+    }
+
+    /// Exchange a token for a fresh one before it expires
+    pub async fn refresh_token(&self, token: &str) -> Result<AuthResponse, ApiError> {
+        let path = self.refresh_path.as_deref().ok_or_else(|| ApiError::Status {
+            status: 404,
+            message: "token refresh is turned off in api.json".to_string(),
+        })?;
+
+        let response = self
+            .http
+            .post(self.url(path))
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            let auth_response: AuthResponse = response.json().await?;
+            Ok(auth_response)
+        } else {
+            Err(ApiError::from_response(response).await)
+        }
+    }
+
+    pub async fn subscription_details(
+        &self,
+        token: &str,
+    ) -> Result<SubscriptionDetails, ApiError> {
+        let response = self
+            .http
+            .get(self.url(SUBSCRIPTION_DETAILS_PATH))
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            let details = response.json::<SubscriptionDetails>().await?;
+            Ok(details)
+        } else {
+            Err(ApiError::from_response(response).await)
+        }
+    }
+
+    /// Ask the backend for an animation; the response body is `ApiAnimationData` JSON
+    pub async fn generate_motion(&self, request: &serde_json::Value, token: &str) -> reqwest::Result<reqwest::Response> {
+        self.http
+            .post(self.url(GENERATE_MOTION_PATH))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(request)
+            .send()
+            .await
+    }
+}
@@ -0,0 +1,502 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use stunts_engine::animations::ObjectType;
+
+// Intermediate structs to parse the API response format
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct ApiAnimationData {
+    pub id: String,
+    pub duration: i32, // milliseconds as i32
+    pub properties: Vec<ApiAnimationProperty>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct ApiAnimationProperty {
+    pub name: String,
+    pub keyframes: Vec<ApiKeyframe>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct ApiKeyframe {
+    pub time: i32, // milliseconds as i32
+    pub value: ApiKeyframeValue,
+    /// "Linear", "EaseIn", "EaseOut" or "EaseInOut"; case, hyphens and underscores are ignored
+    pub easing: String,
+    /// "Linear" or "Bezier"; empty means Linear
+    #[serde(rename = "pathType", skip_serializing_if = "String::is_empty")]
+    pub path_type: String,
+    /// Bezier handles, relative to the keyframe's position
+    #[serde(rename = "controlPoint1", skip_serializing_if = "Option::is_none")]
+    pub control_point1: Option<ApiPosition>,
+    #[serde(rename = "controlPoint2", skip_serializing_if = "Option::is_none")]
+    pub control_point2: Option<ApiPosition>,
+    /// "Frame" or "Range"; empty means Frame
+    #[serde(rename = "keyType", skip_serializing_if = "String::is_empty")]
+    pub key_type: String,
+    /// End of a Range keyframe, in milliseconds
+    #[serde(rename = "endTime", skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum ApiKeyframeValue {
+    Position { Position: ApiPosition },
+    Scale { Scale: i32 },
+    Opacity { Opacity: i32 },
+    Rotation { Rotation: i32 },
+    Zoom { Zoom: i32 },
+    PerspectiveX { PerspectiveX: i32 },
+    PerspectiveY { PerspectiveY: i32 },
+    Custom { Custom: Vec<i32> },
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct ApiPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// How to treat values the editor doesn't recognize, e.g. an unknown easing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConversionMode {
+    /// Drop unknown properties, fall back to a default for other unknown values and log a warning
+    Lenient,
+    /// Reject the response
+    Strict,
+}
+
+// Default implementations
+impl Default for ApiAnimationData {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            duration: 0,
+            properties: Vec::new(),
+        }
+    }
+}
+
+impl Default for ApiAnimationProperty {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            keyframes: Vec::new(),
+        }
+    }
+}
+
+impl Default for ApiKeyframe {
+    fn default() -> Self {
+        Self {
+            time: 0,
+            value: ApiKeyframeValue::Position { Position: ApiPosition::default() },
+            easing: "Linear".to_string(),
+            path_type: String::new(),
+            control_point1: None,
+            control_point2: None,
+            key_type: String::new(),
+            end_time: None,
+        }
+    }
+}
+
+impl Default for ApiPosition {
+    fn default() -> Self {
+        Self { x: 0, y: 0 }
+    }
+}
+
+impl ApiKeyframeValue {
+    /// The `property_path` of the properties this value belongs to
+    fn property_path(&self) -> &'static str {
+        match self {
+            ApiKeyframeValue::Position { .. } => "position",
+            ApiKeyframeValue::Scale { .. } => "scale",
+            ApiKeyframeValue::Opacity { .. } => "opacity",
+            ApiKeyframeValue::Rotation { .. } => "rotation",
+            ApiKeyframeValue::Zoom { .. } => "zoom",
+            ApiKeyframeValue::PerspectiveX { .. } => "perspective_x",
+            ApiKeyframeValue::PerspectiveY { .. } => "perspective_y",
+            ApiKeyframeValue::Custom { .. } => "custom",
+        }
+    }
+}
+
+/// `property_path` for the property names the editor uses, e.g. "Zoom / Popout" is "zoom"
+fn property_path_for(name: &str) -> Option<&'static str> {
+    match normalize(name).as_str() {
+        "position" => Some("position"),
+        "rotation" => Some("rotation"),
+        "scale" => Some("scale"),
+        "opacity" => Some("opacity"),
+        "zoom" | "zoom/popout" | "popout" => Some("zoom"),
+        "perspectivex" => Some("perspective_x"),
+        "perspectivey" => Some("perspective_y"),
+        "custom" => Some("custom"),
+        _ => None,
+    }
+}
+
+/// Lowercase without spaces, hyphens or underscores, so "ease-in-out" matches "EaseInOut"
+fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+// Conversion functions
+impl ApiAnimationData {
+    /// Convert leniently, logging anything that had to be dropped or fall back to a default
+    pub fn to_animation_data(self, polygon_id: String, object_type: ObjectType) -> stunts_engine::animations::AnimationData {
+        let mut problems = Vec::new();
+        let animation_data = self.convert_collecting(polygon_id, object_type, &mut problems);
+
+        for problem in problems {
+            println!("API animation warning: {}", problem);
+        }
+
+        animation_data
+    }
+
+    pub fn convert(
+        self,
+        polygon_id: String,
+        object_type: ObjectType,
+        mode: ConversionMode,
+    ) -> Result<stunts_engine::animations::AnimationData, String> {
+        match mode {
+            ConversionMode::Lenient => Ok(self.to_animation_data(polygon_id, object_type)),
+            ConversionMode::Strict => {
+                let mut problems = Vec::new();
+                let animation_data = self.convert_collecting(polygon_id, object_type, &mut problems);
+
+                if problems.is_empty() {
+                    Ok(animation_data)
+                } else {
+                    Err(problems.join("; "))
+                }
+            }
+        }
+    }
+
+    /// Convert, dropping unknown properties and keyframes whose value belongs to another
+    /// property, falling back to defaults and describing each problem in `problems`
+    fn convert_collecting(
+        self,
+        polygon_id: String,
+        object_type: ObjectType,
+        problems: &mut Vec<String>,
+    ) -> stunts_engine::animations::AnimationData {
+        use stunts_engine::animations::{AnimationData, AnimationProperty, UIKeyframe, KeyframeValue, EasingType, KeyType, RangeData};
+        use stunts_engine::editor::{ControlPoint, CurveData, PathType};
+
+        // Convert API properties to AnimationProperty
+        let properties = self.properties.into_iter().filter_map(|api_prop| {
+            // The editor has nothing to animate with a property it doesn't know
+            let property_path = match property_path_for(&api_prop.name) {
+                Some(property_path) => property_path.to_string(),
+                None => {
+                    problems.push(format!("unknown property \"{}\"", api_prop.name));
+                    return None;
+                }
+            };
+
+            let keyframes = api_prop.keyframes.into_iter().enumerate().filter_map(|(index, api_keyframe)| {
+                let context = format!("{} keyframe {}", api_prop.name, index + 1);
+
+                // A zoom value in an opacity property would be applied as opacity
+                if api_keyframe.value.property_path() != property_path {
+                    problems.push(format!(
+                        "{}: {} value in a {} property",
+                        context,
+                        api_keyframe.value.property_path(),
+                        property_path
+                    ));
+                    return None;
+                }
+
+                let value = match api_keyframe.value {
+                    ApiKeyframeValue::Position { Position: pos } => {
+                        KeyframeValue::Position([pos.x, pos.y])
+                    }
+                    ApiKeyframeValue::Scale { Scale: scale } => {
+                        KeyframeValue::Scale(scale)
+                    }
+                    ApiKeyframeValue::Opacity { Opacity: opacity } => {
+                        KeyframeValue::Opacity(opacity)
+                    }
+                    ApiKeyframeValue::Rotation { Rotation: rotation } => {
+                        KeyframeValue::Rotation(rotation)
+                    }
+                    ApiKeyframeValue::Zoom { Zoom: zoom } => {
+                        KeyframeValue::Zoom(zoom)
+                    }
+                    ApiKeyframeValue::PerspectiveX { PerspectiveX: perspective } => {
+                        KeyframeValue::PerspectiveX(perspective)
+                    }
+                    ApiKeyframeValue::PerspectiveY { PerspectiveY: perspective } => {
+                        KeyframeValue::PerspectiveY(perspective)
+                    }
+                    ApiKeyframeValue::Custom { Custom: values } => {
+                        KeyframeValue::Custom(values)
+                    }
+                };
+
+                let easing = match normalize(&api_keyframe.easing).as_str() {
+                    "linear" | "" => EasingType::Linear,
+                    "easein" => EasingType::EaseIn,
+                    "easeout" => EasingType::EaseOut,
+                    "easeinout" => EasingType::EaseInOut,
+                    _ => {
+                        problems.push(format!("{}: unknown easing \"{}\"", context, api_keyframe.easing));
+                        EasingType::Linear
+                    }
+                };
+
+                let control_point = |point: Option<ApiPosition>| point.map(|point| ControlPoint { x: point.x, y: point.y });
+
+                let path_type = match normalize(&api_keyframe.path_type).as_str() {
+                    "linear" | "" => PathType::Linear,
+                    "bezier" | "curve" | "curved" => PathType::Bezier(CurveData {
+                        control_point1: control_point(api_keyframe.control_point1),
+                        control_point2: control_point(api_keyframe.control_point2),
+                    }),
+                    _ => {
+                        problems.push(format!("{}: unknown path type \"{}\"", context, api_keyframe.path_type));
+                        PathType::Linear
+                    }
+                };
+
+                let key_type = match normalize(&api_keyframe.key_type).as_str() {
+                    "frame" | "" => KeyType::Frame,
+                    "range" => match api_keyframe.end_time {
+                        Some(end_time) if end_time >= api_keyframe.time => KeyType::Range(RangeData {
+                            end_time: Duration::from_millis(end_time as u64),
+                        }),
+                        _ => {
+                            problems.push(format!("{}: Range keyframe needs an endTime after its time", context));
+                            KeyType::Frame
+                        }
+                    },
+                    _ => {
+                        problems.push(format!("{}: unknown key type \"{}\"", context, api_keyframe.key_type));
+                        KeyType::Frame
+                    }
+                };
+
+                if api_keyframe.time < 0 || api_keyframe.time > self.duration {
+                    problems.push(format!(
+                        "{}: time {}ms is outside the animation's {}ms",
+                        context, api_keyframe.time, self.duration
+                    ));
+                }
+
+                Some(UIKeyframe {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    time: Duration::from_millis(api_keyframe.time.max(0) as u64),
+                    value,
+                    easing,
+                    path_type,
+                    key_type,
+                })
+            }).collect();
+
+            Some(AnimationProperty {
+                name: api_prop.name,
+                property_path,
+                children: Vec::new(),
+                keyframes,
+                depth: 0,
+            })
+        }).collect();
+
+        AnimationData {
+            id: self.id,
+            object_type,
+            polygon_id,
+            duration: Duration::from_millis(self.duration.max(0) as u64),
+            start_time_ms: 0,
+            properties,
+            position: [0, 0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use stunts_engine::animations::{AnimationData, EasingType, KeyType, KeyframeValue, UIKeyframe};
+    use stunts_engine::editor::PathType;
+
+    /// A one-property response holding `keyframe` at 0ms of a 3s animation
+    fn response(name: &str, keyframe: serde_json::Value) -> ApiAnimationData {
+        serde_json::from_value(json!({
+            "id": "motion",
+            "duration": 3000,
+            "properties": [{ "name": name, "keyframes": [keyframe] }],
+        }))
+        .expect("response should deserialize")
+    }
+
+    fn convert(response: ApiAnimationData, mode: ConversionMode) -> Result<AnimationData, String> {
+        response.convert("polygon".to_string(), ObjectType::Polygon, mode)
+    }
+
+    fn only_keyframe(animation: &AnimationData) -> &UIKeyframe {
+        &animation.properties[0].keyframes[0]
+    }
+
+    #[test]
+    fn every_keyframe_value_converts_in_strict_mode() {
+        let cases: [(&str, serde_json::Value, &str, fn(&KeyframeValue) -> bool); 8] = [
+            ("Position", json!({ "Position": { "x": 10, "y": -20 } }), "position", |value| matches!(value, KeyframeValue::Position([10, -20]))),
+            ("Scale", json!({ "Scale": 150 }), "scale", |value| matches!(value, KeyframeValue::Scale(150))),
+            ("Opacity", json!({ "Opacity": 40 }), "opacity", |value| matches!(value, KeyframeValue::Opacity(40))),
+            ("Rotation", json!({ "Rotation": -90 }), "rotation", |value| matches!(value, KeyframeValue::Rotation(-90))),
+            ("Zoom / Popout", json!({ "Zoom": 120 }), "zoom", |value| matches!(value, KeyframeValue::Zoom(120))),
+            ("Perspective X", json!({ "PerspectiveX": 15 }), "perspective_x", |value| matches!(value, KeyframeValue::PerspectiveX(15))),
+            ("perspective_y", json!({ "PerspectiveY": -15 }), "perspective_y", |value| matches!(value, KeyframeValue::PerspectiveY(-15))),
+            ("Custom", json!({ "Custom": [1, 2, 3] }), "custom", |value| matches!(value, KeyframeValue::Custom(values) if values[..] == [1, 2, 3])),
+        ];
+
+        for (name, value, property_path, is_expected) in cases {
+            let response = response(name, json!({ "time": 0, "value": value, "easing": "ease-in-out" }));
+            let animation = convert(response, ConversionMode::Strict).unwrap_or_else(|e| panic!("{}: {}", name, e));
+
+            assert_eq!(animation.properties[0].property_path, property_path, "{}", name);
+            let keyframe = only_keyframe(&animation);
+            assert!(is_expected(&keyframe.value), "{}: {:?}", name, keyframe.value);
+            assert!(matches!(keyframe.easing, EasingType::EaseInOut), "{}", name);
+        }
+    }
+
+    #[test]
+    fn property_names_map_to_property_paths() {
+        let cases = [
+            ("Position", Some("position")),
+            ("ROTATION", Some("rotation")),
+            ("Zoom / Popout", Some("zoom")),
+            ("popout", Some("zoom")),
+            ("Perspective-X", Some("perspective_x")),
+            ("perspective_y", Some("perspective_y")),
+            ("custom", Some("custom")),
+            ("Glow", None),
+            ("", None),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(property_path_for(name), expected, "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn bezier_keyframes_keep_their_control_points() {
+        let response = response(
+            "Position",
+            json!({
+                "time": 0,
+                "value": { "Position": { "x": 0, "y": 0 } },
+                "easing": "Linear",
+                "pathType": "Bezier",
+                "controlPoint1": { "x": 5, "y": 10 },
+                "controlPoint2": { "x": -5, "y": 20 },
+            }),
+        );
+
+        let animation = convert(response, ConversionMode::Strict).unwrap();
+        match &only_keyframe(&animation).path_type {
+            PathType::Bezier(curve) => {
+                let first = curve.control_point1.as_ref().map(|point| (point.x, point.y));
+                let second = curve.control_point2.as_ref().map(|point| (point.x, point.y));
+                assert_eq!(first, Some((5, 10)));
+                assert_eq!(second, Some((-5, 20)));
+            }
+            other => panic!("expected a Bezier path, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn range_keyframes_need_an_end_time() {
+        let range = |end_time: Option<i32>| {
+            let mut keyframe = json!({ "time": 500, "value": { "Opacity": 100 }, "keyType": "Range" });
+            if let Some(end_time) = end_time {
+                keyframe["endTime"] = end_time.into();
+            }
+            response("Opacity", keyframe)
+        };
+
+        let animation = convert(range(Some(2000)), ConversionMode::Strict).unwrap();
+        match &only_keyframe(&animation).key_type {
+            KeyType::Range(range) => assert_eq!(range.end_time, Duration::from_millis(2000)),
+            other => panic!("expected a Range keyframe, got {:?}", other),
+        }
+
+        for end_time in [None, Some(100)] {
+            assert!(convert(range(end_time), ConversionMode::Strict).is_err(), "{:?}", end_time);
+
+            let animation = convert(range(end_time), ConversionMode::Lenient).unwrap();
+            assert!(matches!(only_keyframe(&animation).key_type, KeyType::Frame), "{:?}", end_time);
+        }
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_values_and_lenient_mode_drops_them() {
+        // Each response has one value the editor doesn't recognize
+        let cases = [
+            ("unknown easing", response("Opacity", json!({ "time": 0, "value": { "Opacity": 0 }, "easing": "Wobble" }))),
+            ("unknown path type", response("Opacity", json!({ "time": 0, "value": { "Opacity": 0 }, "pathType": "Spiral" }))),
+            ("unknown key type", response("Opacity", json!({ "time": 0, "value": { "Opacity": 0 }, "keyType": "Hold" }))),
+            ("time out of range", response("Opacity", json!({ "time": 4000, "value": { "Opacity": 0 } }))),
+        ];
+
+        for (case, response) in cases {
+            let error = convert(response.clone(), ConversionMode::Strict).expect_err(case);
+            assert!(!error.is_empty(), "{}", case);
+
+            let animation = convert(response, ConversionMode::Lenient).expect(case);
+            let keyframe = only_keyframe(&animation);
+            assert!(matches!(keyframe.easing, EasingType::Linear), "{}", case);
+            assert!(matches!(keyframe.path_type, PathType::Linear), "{}", case);
+            assert!(matches!(keyframe.key_type, KeyType::Frame), "{}", case);
+        }
+
+        let mismatched = response("Opacity", json!({ "time": 0, "value": { "Zoom": 120 } }));
+        let error = convert(mismatched.clone(), ConversionMode::Strict).unwrap_err();
+        assert!(error.contains("zoom value"), "{}", error);
+
+        let animation = convert(mismatched, ConversionMode::Lenient).unwrap();
+        assert!(animation.properties[0].keyframes.is_empty());
+
+        let mut response = response("Opacity", json!({ "time": 0, "value": { "Opacity": 0 } }));
+        response.properties.push(ApiAnimationProperty {
+            name: "Glow".to_string(),
+            keyframes: vec![ApiKeyframe::default()],
+        });
+
+        let error = convert(response.clone(), ConversionMode::Strict).unwrap_err();
+        assert!(error.contains("unknown property \"Glow\""), "{}", error);
+
+        let animation = convert(response, ConversionMode::Lenient).unwrap();
+        let paths: Vec<_> = animation.properties.iter().map(|property| property.property_path.as_str()).collect();
+        assert_eq!(paths, vec!["opacity"]);
+    }
+
+    #[test]
+    fn unrecognized_keyframe_values_fail_to_deserialize() {
+        let response = json!({
+            "id": "motion",
+            "duration": 3000,
+            "properties": [{ "name": "Glow", "keyframes": [{ "time": 0, "value": { "Glow": 3 } }] }],
+        });
+
+        assert!(serde_json::from_value::<ApiAnimationData>(response).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use gui_reactive::Signal;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{ApiClient, ApiError};
+use crate::helpers::credentials::credential_store;
+use crate::helpers::utilities::{AuthState, AuthToken};
+use crate::{clear_auth_token_everywhere, store_auth_token};
+
+/// Tokens are refreshed once they have less than this left
+const REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// How often the background task checks the token's expiry
+const REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Errors that can say the backend rejected the token
+pub trait Unauthorized {
+    fn is_unauthorized(&self) -> bool;
+
+    /// The error to report when there's no usable token left
+    fn session_expired() -> Self;
+
+    /// The error to report when the token couldn't be renewed because the backend was unreachable
+    fn network(message: String) -> Self;
+}
+
+/// Why a token couldn't be renewed
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefreshError {
+    /// Signed out, or the backend rejected the token
+    Expired,
+    /// The backend has no refresh endpoint
+    Unsupported,
+    /// The backend couldn't be reached; the token may still be good
+    Network(String),
+}
+
+impl RefreshError {
+    /// The caller's error for this failure; only a network failure isn't a lost session
+    fn into_error<E: Unauthorized>(self) -> E {
+        match self {
+            RefreshError::Network(message) => E::network(message),
+            RefreshError::Expired | RefreshError::Unsupported => E::session_expired(),
+        }
+    }
+}
+
+/// Keeps the signed-in token fresh and signs the user out when it can't be renewed.
+/// Cloning shares the same session.
+#[derive(Clone)]
+pub struct AuthSession {
+    auth_state: Signal<AuthState>,
+    show_auth_form: Signal<bool>,
+    /// Held while refreshing so concurrent requests don't each trade the token in
+    refreshing: Arc<tokio::sync::Mutex<()>>,
+    /// Set once the backend answers that it has no refresh endpoint, so it isn't asked again
+    refresh_missing: Arc<AtomicBool>,
+    client: ApiClient,
+}
+
+impl AuthSession {
+    pub fn new(auth_state: Signal<AuthState>, show_auth_form: Signal<bool>) -> Self {
+        Self::with_client(auth_state, show_auth_form, ApiClient::from_settings())
+    }
+
+    /// A session that refreshes tokens through `client`
+    pub fn with_client(auth_state: Signal<AuthState>, show_auth_form: Signal<bool>, client: ApiClient) -> Self {
+        Self {
+            auth_state,
+            show_auth_form,
+            refreshing: Arc::new(tokio::sync::Mutex::new(())),
+            refresh_missing: Arc::new(AtomicBool::new(false)),
+            client,
+        }
+    }
+
+    /// The signed-in token, whether or not it's about to expire
+    pub fn token(&self) -> Option<AuthToken> {
+        let auth_state = self.auth_state.get();
+        if auth_state.is_authenticated {
+            auth_state.token
+        } else {
+            None
+        }
+    }
+
+    /// A token that won't expire within the refresh margin, refreshing it if needed
+    pub async fn fresh_token(&self) -> Result<String, RefreshError> {
+        let token = self.token().ok_or(RefreshError::Expired)?;
+        if !needs_refresh(&token) {
+            return Ok(token.token);
+        }
+
+        match self.refresh(&token.token).await {
+            Ok(token) => Ok(token),
+            // Refreshing can fail for lack of network; the old token still works until it expires
+            Err(e) => {
+                let token = self.token().ok_or(RefreshError::Expired)?;
+                if token.expiry.map_or(true, |expiry| expiry > chrono::Utc::now()) {
+                    return Ok(token.token);
+                }
+
+                // Without a refresh endpoint an expired token can't come back
+                if !self.can_refresh() {
+                    println!("The auth token expired and can't be renewed, signing out");
+                    self.expire();
+                    return Err(RefreshError::Expired);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Whether tokens can be renewed at all; without it they're used until they expire
+    pub fn can_refresh(&self) -> bool {
+        !self.refresh_missing.load(Ordering::Relaxed) && self.client.can_refresh()
+    }
+
+    /// Trade `stale` for a new token. Signs out when the backend rejects it.
+    pub async fn refresh(&self, stale: &str) -> Result<String, RefreshError> {
+        let _refreshing = self.refreshing.lock().await;
+
+        // Another request may have refreshed while this one waited
+        let current = self.token().ok_or(RefreshError::Expired)?;
+        if current.token != stale && !needs_refresh(&current) {
+            return Ok(current.token);
+        }
+
+        if !self.can_refresh() {
+            return Err(RefreshError::Unsupported);
+        }
+
+        println!("Refreshing the auth token");
+        match self.client.refresh_token(&current.token).await {
+            Ok(auth_response) => {
+                let token = auth_response.jwt_data.to_auth_token();
+                if let Err(e) = store_auth_token(&*credential_store(), &token) {
+                    println!("Failed to store the refreshed auth token: {}", e);
+                }
+
+                let mut auth_state = self.auth_state.get();
+                auth_state.token = Some(token.clone());
+                self.auth_state.set(auth_state);
+
+                Ok(token.token)
+            }
+            Err(e) if e.is_unauthorized() => {
+                println!("Couldn't refresh the auth token, signing out: {}", e);
+                self.expire();
+                Err(RefreshError::Expired)
+            }
+            // The backend has no such endpoint; keep the token until it expires
+            Err(ApiError::Status { status: 404 | 405, .. }) => {
+                println!("The backend can't refresh auth tokens, set \"refresh_path\" in api.json to its refresh endpoint or to null");
+                self.refresh_missing.store(true, Ordering::Relaxed);
+                Err(RefreshError::Unsupported)
+            }
+            // A network hiccup doesn't mean the token is bad; try again on the next check
+            Err(e) => {
+                println!("Couldn't refresh the auth token: {}", e);
+                Err(RefreshError::Network(e.to_string()))
+            }
+        }
+    }
+
+    /// Forget the token and ask the user to sign in again
+    pub fn expire(&self) {
+        clear_auth_token_everywhere();
+
+        let mut auth_state = self.auth_state.get();
+        auth_state.token = None;
+        auth_state.is_authenticated = false;
+        self.auth_state.set(auth_state);
+        self.show_auth_form.set(true);
+    }
+
+    /// Run `call` with a fresh token, refreshing and trying once more if it comes back unauthorized
+    pub async fn authorized<T, E, F, Fut>(&self, mut call: F) -> Result<T, E>
+    where
+        E: Unauthorized,
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let token = self.fresh_token().await.map_err(RefreshError::into_error)?;
+
+        match call(token.clone()).await {
+            // Without refreshing there's nothing to retry with
+            Err(e) if e.is_unauthorized() && !self.can_refresh() => {
+                println!("Request was unauthorized, signing out");
+                self.expire();
+                Err(e)
+            }
+            Err(e) if e.is_unauthorized() => {
+                println!("Request was unauthorized, refreshing the token and trying again");
+                let token = self.refresh(&token).await.map_err(RefreshError::into_error)?;
+
+                let result = call(token).await;
+                if matches!(&result, Err(e) if e.is_unauthorized()) {
+                    self.expire();
+                }
+                result
+            }
+            result => result,
+        }
+    }
+
+    /// Refresh the token in the background shortly before it expires, for as long as the app runs
+    pub fn start_refreshing(&self) {
+        let session = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(REFRESH_CHECK_INTERVAL).await;
+
+                // Also signs out once the token expires if it can't be renewed
+                if let Some(token) = session.token() {
+                    if needs_refresh(&token) {
+                        let _ = session.fresh_token().await;
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn needs_refresh(token: &AuthToken) -> bool {
+    match token.expiry {
+        Some(expiry) => {
+            let margin = chrono::Duration::from_std(REFRESH_MARGIN).unwrap_or_default();
+            expiry - chrono::Utc::now() < margin
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ApiSettings;
+    use crate::motion::GenerationError;
+
+    /// A session whose token is already expired, refreshing against a port nothing listens on
+    fn session_with_unreachable_backend() -> AuthSession {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("couldn't find a free port")
+            .port();
+        let client = ApiClient::new(ApiSettings {
+            base_url: format!("http://127.0.0.1:{}", port),
+            ..ApiSettings::default()
+        });
+
+        let auth_state = Signal::new(AuthState {
+            token: Some(AuthToken {
+                token: "stale".to_string(),
+                expiry: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+            }),
+            is_authenticated: true,
+            subscription: None,
+            offline: false,
+        });
+
+        AuthSession::with_client(auth_state, Signal::new(false), client)
+    }
+
+    #[tokio::test]
+    async fn network_failure_while_refreshing_is_not_an_expired_session() {
+        let session = session_with_unreachable_backend();
+
+        assert!(matches!(session.refresh("stale").await, Err(RefreshError::Network(_))));
+
+        let result = session
+            .authorized(|_token| async { Ok::<_, GenerationError>(()) })
+            .await;
+        assert!(matches!(result, Err(GenerationError::Network(_))), "{:?}", result);
+
+        // Still signed in, so the next check can try again
+        assert!(session.token().is_some());
+    }
+
+    #[tokio::test]
+    async fn network_failure_after_unauthorized_is_reported_as_network() {
+        let session = session_with_unreachable_backend();
+        let mut auth_state = session.auth_state.get();
+        auth_state.token = Some(AuthToken {
+            token: "rejected".to_string(),
+            expiry: None,
+        });
+        session.auth_state.set(auth_state);
+
+        let result = session
+            .authorized(|_token| async { Err::<(), _>(GenerationError::Auth { status: 401 }) })
+            .await;
+        assert!(matches!(result, Err(GenerationError::Network(_))), "{:?}", result);
+    }
+}
@@ -0,0 +1,582 @@
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use rand::RngCore;
+use stunts_engine::saved_state::get_ground_truth_dir;
+
+use crate::commands::{Command, CommandResult};
+use crate::export::ExportProgress;
+use crate::helpers::credentials::{to_hex, write_private};
+
+mod rpc;
+
+use rpc::{Notification, Request, Response, RpcError};
+
+/// How long a client waits for the editor to handle a command
+const REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// File next to the app preferences holding the session token TCP clients authenticate with
+pub const TOKEN_FILENAME: &str = "automation_token";
+
+/// Lines waiting to be written to a client; a client that falls this far behind is dropped
+const OUTBOX_CAPACITY: usize = 256;
+
+/// Where the automation endpoint listens. TCP is limited to loopback addresses.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AutomationAddress {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl AutomationAddress {
+    /// Accepts `<port>`, `localhost:<port>`, `127.0.0.1:<port>`, or on Unix a socket path
+    pub fn parse(value: &str) -> Result<AutomationAddress, String> {
+        let value = value.trim();
+
+        if let Ok(port) = value.parse::<u16>() {
+            return Ok(AutomationAddress::Tcp(SocketAddr::from(([127, 0, 0, 1], port))));
+        }
+
+        let socket_addr = match value.strip_prefix("localhost:") {
+            Some(port) => format!("127.0.0.1:{}", port).parse::<SocketAddr>(),
+            None => value.parse::<SocketAddr>(),
+        };
+
+        if let Ok(socket_addr) = socket_addr {
+            if !socket_addr.ip().is_loopback() {
+                return Err(format!(
+                    "Automation only listens on localhost, got {}",
+                    socket_addr
+                ));
+            }
+            return Ok(AutomationAddress::Tcp(socket_addr));
+        }
+
+        #[cfg(unix)]
+        {
+            Ok(AutomationAddress::Unix(PathBuf::from(value)))
+        }
+
+        #[cfg(not(unix))]
+        {
+            Err(format!(
+                "Invalid automation address '{}', expected a port or 127.0.0.1:<port>",
+                value
+            ))
+        }
+    }
+}
+
+impl fmt::Display for AutomationAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AutomationAddress::Tcp(socket_addr) => write!(f, "{}", socket_addr),
+            #[cfg(unix)]
+            AutomationAddress::Unix(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// A command received from a client, handled by the render loop like UI commands
+pub struct AutomationRequest {
+    pub command: Command,
+    pub reply: mpsc::Sender<CommandResult>,
+}
+
+type Clients = Arc<Mutex<HashMap<u64, Client>>>;
+
+/// A connected client. Its lines are written by a thread of its own, so a client that stops
+/// reading can't hold up the editor.
+struct Client {
+    outbox: mpsc::SyncSender<String>,
+    subscribed: Arc<AtomicBool>,
+}
+
+impl Client {
+    /// Start the writer thread for `writer`
+    fn new(mut writer: Box<dyn Write + Send>) -> Client {
+        let (outbox, lines) = mpsc::sync_channel::<String>(OUTBOX_CAPACITY);
+
+        std::thread::spawn(move || {
+            for line in lines {
+                let written = writeln!(writer, "{}", line).and_then(|_| writer.flush());
+                if let Err(e) = written {
+                    println!("Failed to write to automation client: {}", e);
+                    break;
+                }
+            }
+        });
+
+        Client {
+            outbox,
+            subscribed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// Local JSON-RPC 2.0 endpoint, one request or notification per line.
+/// Every `Command` is exposed as a method named after its variant in snake_case,
+/// plus `subscribe` / `unsubscribe` for events such as `export_progress`.
+/// Any local process (or a web page) can reach a loopback port, so TCP clients must first call
+/// `authenticate` with the token from `TOKEN_FILENAME`; the Unix socket is owner-only instead.
+pub struct AutomationServer {
+    clients: Clients,
+}
+
+impl AutomationServer {
+    pub fn start(
+        address: &AutomationAddress,
+        request_tx: mpsc::Sender<AutomationRequest>,
+    ) -> Result<AutomationServer, String> {
+        let clients: Clients = Arc::new(Mutex::new(HashMap::new()));
+        let next_client_id = Arc::new(AtomicU64::new(0));
+
+        let accept = {
+            let clients = clients.clone();
+            move |reader: Box<dyn Read + Send>, writer: Box<dyn Write + Send>, token: Option<Arc<String>>| {
+                let client_id = next_client_id.fetch_add(1, Ordering::SeqCst);
+                serve_client(client_id, reader, writer, token, request_tx.clone(), clients.clone());
+            }
+        };
+
+        match address {
+            AutomationAddress::Tcp(socket_addr) => {
+                let token = Arc::new(write_session_token()?);
+
+                let listener = TcpListener::bind(socket_addr)
+                    .map_err(|e| format!("Couldn't listen on {}: {}", socket_addr, e))?;
+
+                std::thread::spawn(move || {
+                    for stream in listener.incoming() {
+                        match stream.and_then(|stream| Ok((stream.try_clone()?, stream))) {
+                            Ok((reader, writer)) => accept(Box::new(reader), Box::new(writer), Some(token.clone())),
+                            Err(e) => println!("Automation connection failed: {}", e),
+                        }
+                    }
+                });
+            }
+            #[cfg(unix)]
+            AutomationAddress::Unix(path) => {
+                let listener = bind_private_socket(path)?;
+
+                std::thread::spawn(move || {
+                    for stream in listener.incoming() {
+                        match stream.and_then(|stream| Ok((stream.try_clone()?, stream))) {
+                            Ok((reader, writer)) => accept(Box::new(reader), Box::new(writer), None),
+                            Err(e) => println!("Automation connection failed: {}", e),
+                        }
+                    }
+                });
+            }
+        }
+
+        Ok(AutomationServer { clients })
+    }
+
+    /// Queue an event for every subscribed client without waiting for them to read it.
+    /// Clients that disconnected or fell too far behind stop getting events.
+    pub fn notify(&self, method: &str, params: Value) {
+        let line = match serde_json::to_string(&Notification::new(method, params)) {
+            Ok(line) => line,
+            Err(e) => {
+                println!("Failed to serialize automation event: {}", e);
+                return;
+            }
+        };
+
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain(|client_id, client| {
+            if !client.subscribed.load(Ordering::SeqCst) {
+                return true;
+            }
+
+            match client.outbox.try_send(line.clone()) {
+                Ok(()) => true,
+                Err(mpsc::TrySendError::Full(_)) => {
+                    println!("Automation client {} isn't reading events, dropping it", client_id);
+                    false
+                }
+                Err(mpsc::TrySendError::Disconnected(_)) => false,
+            }
+        });
+    }
+
+    pub fn notify_export(&self, job_id: &str, progress: &ExportProgress) {
+        let params = match progress {
+            ExportProgress::Progress {
+                percent,
+                sequence_name,
+            } => json!({
+                "job_id": job_id,
+                "status": "progress",
+                "percent": percent,
+                "sequence_name": sequence_name,
+            }),
+            ExportProgress::Complete(output_path) => json!({
+                "job_id": job_id,
+                "status": "complete",
+                "output_path": output_path,
+            }),
+            ExportProgress::Error(message) => json!({
+                "job_id": job_id,
+                "status": "error",
+                "message": message,
+            }),
+            ExportProgress::Cancelled => json!({
+                "job_id": job_id,
+                "status": "cancelled",
+            }),
+        };
+
+        self.notify("export_progress", params);
+    }
+}
+
+/// Generate this session's token and write it where only the current user can read it
+fn write_session_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = to_hex(&bytes);
+
+    let path = get_ground_truth_dir()
+        .expect("Couldn't get CommonOS directory")
+        .join(TOKEN_FILENAME);
+    write_private(&path, token.as_bytes())
+        .map_err(|e| format!("Couldn't write the automation token to {}: {}", path.display(), e))?;
+
+    println!("Automation clients authenticate with the token in {}", path.display());
+    Ok(token)
+}
+
+/// Listen on a Unix socket only the current user can connect to
+#[cfg(unix)]
+fn bind_private_socket(path: &std::path::Path) -> Result<std::os::unix::net::UnixListener, String> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+    use std::os::unix::net::UnixListener;
+
+    // A socket left behind by a previous session blocks the bind; anything else at the path is kept
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)
+            .map_err(|e| format!("Couldn't remove old socket {}: {}", path.display(), e))?,
+        Ok(_) => return Err(format!("{} already exists and isn't a socket", path.display())),
+        Err(_) => {}
+    }
+
+    // Bind inside a directory only the user can enter, so nobody can connect before the
+    // socket's own permissions are tightened, then move it into place
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("{} isn't a socket path", path.display()))?;
+    let private_dir = path.with_file_name(format!(".{}.{}", file_name.to_string_lossy(), std::process::id()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)
+        .map_err(|e| format!("Couldn't create {}: {}", private_dir.display(), e))?;
+
+    let staged_path = private_dir.join("socket");
+    let bound = UnixListener::bind(&staged_path)
+        .map_err(|e| format!("Couldn't listen on {}: {}", path.display(), e))
+        .and_then(|listener| {
+            std::fs::set_permissions(&staged_path, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| format!("Couldn't restrict {}: {}", path.display(), e))?;
+            std::fs::rename(&staged_path, path)
+                .map_err(|e| format!("Couldn't move the socket to {}: {}", path.display(), e))?;
+            Ok(listener)
+        });
+
+    let _ = std::fs::remove_file(&staged_path);
+    let _ = std::fs::remove_dir(&private_dir);
+    bound
+}
+
+/// Read requests from one client until it disconnects.
+/// With a `token` the client has to `authenticate` before anything else.
+fn serve_client(
+    client_id: u64,
+    reader: Box<dyn Read + Send>,
+    writer: Box<dyn Write + Send>,
+    token: Option<Arc<String>>,
+    request_tx: mpsc::Sender<AutomationRequest>,
+    clients: Clients,
+) {
+    let client = Client::new(writer);
+    let outbox = client.outbox.clone();
+    let subscribed = client.subscribed.clone();
+    let mut authenticated = token.is_none();
+
+    clients.lock().unwrap().insert(client_id, client);
+
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if let Some(response) = handle_line(&line, token.as_deref(), &mut authenticated, &request_tx, &subscribed) {
+                let response = match serde_json::to_string(&response) {
+                    Ok(response) => response,
+                    Err(e) => {
+                        println!("Failed to serialize automation response: {}", e);
+                        continue;
+                    }
+                };
+
+                // Only this client's thread waits if it isn't reading its responses
+                if outbox.send(response).is_err() {
+                    break;
+                }
+            }
+        }
+
+        clients.lock().unwrap().remove(&client_id);
+    });
+}
+
+/// Handle one line of input, returning the response unless the request was a notification
+fn handle_line(
+    line: &str,
+    token: Option<&String>,
+    authenticated: &mut bool,
+    request_tx: &mpsc::Sender<AutomationRequest>,
+    subscribed: &AtomicBool,
+) -> Option<Response> {
+    let request: Request = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(Response::new(
+                Value::Null,
+                Err(RpcError::new(rpc::PARSE_ERROR, e.to_string())),
+            ));
+        }
+    };
+
+    let outcome = if request.jsonrpc != "2.0" {
+        Err(RpcError::new(rpc::INVALID_REQUEST, "Only JSON-RPC 2.0 is supported"))
+    } else if request.method == "authenticate" {
+        let given = request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("token"))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+
+        *authenticated = token.map_or(true, |token| tokens_match(token, given));
+        if *authenticated {
+            Ok(Value::Bool(true))
+        } else {
+            Err(RpcError::new(rpc::UNAUTHENTICATED, "Wrong automation token"))
+        }
+    } else if !*authenticated {
+        Err(RpcError::new(
+            rpc::UNAUTHENTICATED,
+            format!("Call authenticate with the token from {} first", TOKEN_FILENAME),
+        ))
+    } else {
+        match request.method.as_str() {
+            "subscribe" => {
+                subscribed.store(true, Ordering::SeqCst);
+                Ok(Value::Bool(true))
+            }
+            "unsubscribe" => {
+                subscribed.store(false, Ordering::SeqCst);
+                Ok(Value::Bool(true))
+            }
+            method => run_command(method, request.params, request_tx),
+        }
+    };
+
+    request.id.map(|id| Response::new(id, outcome))
+}
+
+/// Pass the command to the editor and wait for its handler to finish
+fn run_command(
+    method: &str,
+    params: Option<Value>,
+    request_tx: &mpsc::Sender<AutomationRequest>,
+) -> Result<Value, RpcError> {
+    let mut message = Map::new();
+    message.insert("method".to_string(), Value::String(method.to_string()));
+    if let Some(params) = params {
+        message.insert("params".to_string(), params);
+    }
+
+    let command: Command = serde_json::from_value(Value::Object(message)).map_err(|e| {
+        let message = e.to_string();
+        if message.starts_with("unknown variant") {
+            RpcError::new(rpc::METHOD_NOT_FOUND, message)
+        } else {
+            RpcError::new(rpc::INVALID_PARAMS, message)
+        }
+    })?;
+
+    let (reply_tx, reply_rx) = mpsc::channel::<CommandResult>();
+    request_tx
+        .send(AutomationRequest {
+            command,
+            reply: reply_tx,
+        })
+        .map_err(|_| RpcError::new(rpc::COMMAND_FAILED, "The editor has shut down"))?;
+
+    match reply_rx.recv_timeout(REPLY_TIMEOUT) {
+        Ok(Ok(())) => Ok(Value::Null),
+        Ok(Err(e)) => Err(RpcError::new(rpc::COMMAND_FAILED, e)),
+        Err(_) => Err(RpcError::new(
+            rpc::COMMAND_TIMED_OUT,
+            "The editor didn't handle the command in time",
+        )),
+    }
+}
+
+/// Compare without stopping at the first difference, so timing doesn't give the token away
+fn tokens_match(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0u8, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    /// Run one line through `handle_line` and return the response as JSON
+    fn respond(line: &str, token: Option<&str>, authenticated: &mut bool, request_tx: &mpsc::Sender<AutomationRequest>) -> Value {
+        let token = token.map(str::to_string);
+        let response = handle_line(line, token.as_ref(), authenticated, request_tx, &AtomicBool::new(false))
+            .expect("request had an id");
+        serde_json::to_value(response).unwrap()
+    }
+
+    fn error_code(response: &Value) -> Option<i64> {
+        response["error"]["code"].as_i64()
+    }
+
+    #[test]
+    fn rejects_unparseable_lines() {
+        let (request_tx, _request_rx) = mpsc::channel();
+
+        let response = respond("{not json", None, &mut true, &request_tx);
+
+        assert_eq!(error_code(&response), Some(rpc::PARSE_ERROR));
+        assert_eq!(response["id"], Value::Null);
+    }
+
+    #[test]
+    fn rejects_unknown_methods() {
+        let (request_tx, _request_rx) = mpsc::channel();
+
+        let response = respond(r#"{"jsonrpc":"2.0","id":1,"method":"format_disk"}"#, None, &mut true, &request_tx);
+
+        assert_eq!(error_code(&response), Some(rpc::METHOD_NOT_FOUND));
+    }
+
+    #[test]
+    fn requires_the_token_before_commands() {
+        let (request_tx, request_rx) = mpsc::channel();
+        let mut authenticated = false;
+        let sign_out = r#"{"jsonrpc":"2.0","id":1,"method":"sign_out"}"#;
+
+        let response = respond(sign_out, Some(TOKEN), &mut authenticated, &request_tx);
+        assert_eq!(error_code(&response), Some(rpc::UNAUTHENTICATED));
+
+        let wrong = r#"{"jsonrpc":"2.0","id":2,"method":"authenticate","params":{"token":"0123456789abcdee"}}"#;
+        let response = respond(wrong, Some(TOKEN), &mut authenticated, &request_tx);
+        assert_eq!(error_code(&response), Some(rpc::UNAUTHENTICATED));
+        assert!(!authenticated);
+
+        let response = respond(sign_out, Some(TOKEN), &mut authenticated, &request_tx);
+        assert_eq!(error_code(&response), Some(rpc::UNAUTHENTICATED));
+
+        // Nothing reached the editor
+        assert!(request_rx.try_recv().is_err());
+
+        let right = format!(r#"{{"jsonrpc":"2.0","id":3,"method":"authenticate","params":{{"token":"{}"}}}}"#, TOKEN);
+        let response = respond(&right, Some(TOKEN), &mut authenticated, &request_tx);
+        assert_eq!(response["result"], Value::Bool(true));
+        assert!(authenticated);
+    }
+
+    #[test]
+    fn dispatches_commands_to_the_editor() {
+        let (request_tx, request_rx) = mpsc::channel::<AutomationRequest>();
+
+        let editor = std::thread::spawn(move || {
+            let AutomationRequest { command, reply } = request_rx.recv().unwrap();
+            let file_path = match command {
+                Command::AddImage { file_path } => file_path,
+                other => panic!("unexpected command {:?}", other.family()),
+            };
+            reply.send(Ok(())).unwrap();
+            file_path
+        });
+
+        let line = r#"{"jsonrpc":"2.0","id":7,"method":"add_image","params":{"file_path":"logo.png"}}"#;
+        let response = respond(line, None, &mut true, &request_tx);
+
+        assert_eq!(editor.join().unwrap(), "logo.png");
+        assert_eq!(response["id"], Value::from(7));
+        assert_eq!(response["result"], Value::Null);
+        assert!(response.get("error").is_none());
+    }
+
+    #[test]
+    fn reports_failed_commands() {
+        let (request_tx, request_rx) = mpsc::channel::<AutomationRequest>();
+
+        std::thread::spawn(move || {
+            let request = request_rx.recv().unwrap();
+            request.reply.send(Err("Open a project first".to_string())).unwrap();
+        });
+
+        let response = respond(r#"{"jsonrpc":"2.0","id":1,"method":"add_text"}"#, None, &mut true, &request_tx);
+
+        assert_eq!(error_code(&response), Some(rpc::COMMAND_FAILED));
+        assert_eq!(response["error"]["message"], "Open a project first");
+    }
+
+    /// A client that never reads, so writing to it blocks forever
+    struct StalledWriter;
+
+    impl Write for StalledWriter {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            loop {
+                std::thread::park();
+            }
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn stalled_clients_do_not_block_events() {
+        let client = Client::new(Box::new(StalledWriter));
+        client.subscribed.store(true, Ordering::SeqCst);
+        let server = AutomationServer {
+            clients: Arc::new(Mutex::new(HashMap::from([(0, client)]))),
+        };
+
+        for _ in 0..OUTBOX_CAPACITY + 2 {
+            server.notify("export_progress", json!({ "percent": 50.0 }));
+        }
+
+        assert!(server.clients.lock().unwrap().is_empty());
+    }
+}
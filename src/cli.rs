@@ -0,0 +1,316 @@
+use std::path::PathBuf;
+use tokio::sync::mpsc as tokio_mpsc;
+
+use crate::api::mock::DEFAULT_MOCK_PORT;
+use crate::automation::AutomationAddress;
+use crate::export::{
+    plan_export, spawn_export, CancelToken, ExportProgress, ExportScope, ExportSettings,
+    OutputFormat, ResolutionPreset,
+};
+use crate::helpers::entitlements::{cached_subscription, EntitlementSettings, Entitlements};
+use crate::helpers::projects::get_project_name;
+
+pub const USAGE: &str = "Usage:
+  stunts-native [--automation <port> | <host:port> | <socket-path>]
+                                     Launch the editor, optionally accepting JSON-RPC
+                                     commands from scripts on localhost or a Unix socket
+  stunts-native export --project <id> [--out <path>] [--size <width>x<height>] [--fps <rate>]
+                       [--format mp4|png|gif|webp] [--sequence <sequence-id> | --range <in>-<out>]
+  stunts-native mock-api [--port <port>]
+                                     Serve canned backend responses on localhost (default port 3000)
+
+Export options default to the project's saved export settings.
+--format png writes a folder of frames; --out names the folder.
+--range takes seconds on the arranged timeline, e.g. --range 2.5-7
+Exports follow the plan of the account last signed in to the editor, if any.
+The backend URL comes from api.json in the app data folder, or STUNTS_API_URL.";
+
+/// Commands that run without opening the editor window
+#[derive(Debug, Clone)]
+pub enum CliCommand {
+    Export(ExportArgs),
+    Editor(EditorArgs),
+    MockApi { port: u16 },
+    Help,
+}
+
+/// Options for launching the editor window
+#[derive(Debug, Clone, Default)]
+pub struct EditorArgs {
+    pub automation: Option<AutomationAddress>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportArgs {
+    pub project_id: String,
+    pub output_path: Option<PathBuf>,
+    pub resolution: Option<ResolutionPreset>,
+    pub fps: Option<u32>,
+    pub format: Option<OutputFormat>,
+    pub scope: ExportScope,
+}
+
+/// Parse the process arguments (without the binary name).
+/// Returns `Ok(None)` when no arguments were given and the editor should launch as usual.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<CliCommand>, String> {
+    let mut args = args.into_iter();
+
+    let subcommand = match args.next() {
+        Some(subcommand) => subcommand,
+        None => return Ok(None),
+    };
+
+    match subcommand.as_str() {
+        "export" => parse_export_args(args).map(|export_args| Some(CliCommand::Export(export_args))),
+        "mock-api" => parse_mock_api_args(args).map(|port| Some(CliCommand::MockApi { port })),
+        "help" | "--help" | "-h" => Ok(Some(CliCommand::Help)),
+        flag if flag.starts_with("--") => {
+            parse_editor_args(std::iter::once(subcommand.clone()).chain(args))
+                .map(|editor_args| Some(CliCommand::Editor(editor_args)))
+        }
+        other => Err(format!("Unknown command: {}", other)),
+    }
+}
+
+fn parse_mock_api_args(mut args: impl Iterator<Item = String>) -> Result<u16, String> {
+    let mut port = DEFAULT_MOCK_PORT;
+
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("Missing value for {}", flag));
+
+        match flag.as_str() {
+            "--port" => {
+                let value = value()?;
+                port = value
+                    .trim()
+                    .parse::<u16>()
+                    .map_err(|_| format!("Invalid port '{}'", value))?;
+            }
+            other => return Err(format!("Unknown mock-api option: {}", other)),
+        }
+    }
+
+    Ok(port)
+}
+
+fn parse_editor_args(mut args: impl Iterator<Item = String>) -> Result<EditorArgs, String> {
+    let mut editor_args = EditorArgs::default();
+
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("Missing value for {}", flag));
+
+        match flag.as_str() {
+            "--automation" => editor_args.automation = Some(AutomationAddress::parse(&value()?)?),
+            other => return Err(format!("Unknown option: {}", other)),
+        }
+    }
+
+    Ok(editor_args)
+}
+
+fn parse_export_args(mut args: impl Iterator<Item = String>) -> Result<ExportArgs, String> {
+    let mut project_id = None;
+    let mut output_path = None;
+    let mut resolution = None;
+    let mut fps = None;
+    let mut format = None;
+    let mut scope = ExportScope::All;
+
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("Missing value for {}", flag));
+
+        match flag.as_str() {
+            "--project" => project_id = Some(value()?),
+            "--out" => output_path = Some(PathBuf::from(value()?)),
+            "--size" => resolution = Some(parse_size(&value()?)?),
+            "--fps" => fps = Some(parse_fps(&value()?)?),
+            "--format" => format = Some(parse_format(&value()?)?),
+            "--sequence" | "--range" if scope != ExportScope::All => {
+                return Err("Use only one of --sequence or --range".to_string());
+            }
+            "--sequence" => scope = ExportScope::Sequence { sequence_id: value()? },
+            "--range" => scope = ExportScope::parse_range(&value()?)?,
+            other => return Err(format!("Unknown export option: {}", other)),
+        }
+    }
+
+    Ok(ExportArgs {
+        project_id: project_id.ok_or("Missing --project <id>")?,
+        output_path,
+        resolution,
+        fps,
+        format,
+        scope,
+    })
+}
+
+/// Parse sizes written as `1920x1080`, matching them to a preset where possible
+pub fn parse_size(value: &str) -> Result<ResolutionPreset, String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or_else(|| format!("Invalid size '{}', expected <width>x<height>", value))?;
+
+    let width = width
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid width in '{}'", value))?;
+    let height = height
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid height in '{}'", value))?;
+
+    if width == 0 || height == 0 {
+        return Err(format!("Size must be non-zero, got '{}'", value));
+    }
+
+    let preset = ResolutionPreset::PRESETS
+        .into_iter()
+        .find(|preset| {
+            let size = preset.size();
+            size.width == width && size.height == height
+        })
+        .unwrap_or(ResolutionPreset::Custom { width, height });
+
+    Ok(preset)
+}
+
+pub fn parse_fps(value: &str) -> Result<u32, String> {
+    match value.trim().parse::<u32>() {
+        Ok(fps) if fps > 0 && fps <= 240 => Ok(fps),
+        _ => Err(format!("Invalid frame rate '{}', expected 1-240", value)),
+    }
+}
+
+pub fn parse_format(value: &str) -> Result<OutputFormat, String> {
+    OutputFormat::from_key(&value.trim().to_lowercase())
+        .ok_or_else(|| format!("Invalid format '{}', expected mp4, png, gif or webp", value))
+}
+
+/// Load a saved project and render it to disk without a window.
+/// Returns the process exit code.
+pub async fn run_headless_export(args: ExportArgs) -> i32 {
+    println!("Loading project {}", args.project_id);
+
+    let saved_state = match stunts_engine::saved_state::load_project_state(args.project_id.clone()) {
+        Ok(saved_state) => saved_state,
+        Err(e) => {
+            eprintln!("Failed to load project {}: {}", args.project_id, e);
+            return 1;
+        }
+    };
+
+    let mut plan = match plan_export(saved_state.sequences, &args.scope) {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("Cannot export project {}: {}", args.project_id, e);
+            return 1;
+        }
+    };
+
+    let mut settings = ExportSettings::load(&args.project_id);
+    if let Some(resolution) = args.resolution {
+        settings.resolution = resolution;
+    }
+    if let Some(fps) = args.fps {
+        settings.fps = Some(fps);
+    }
+    if let Some(format) = args.format {
+        settings.format = format;
+    }
+
+    // Without a window there's no sign-in, so the plan last seen in the app applies; a machine
+    // that never ran the app, such as a build server, isn't limited
+    let entitlements = match cached_subscription() {
+        Some(subscription) => {
+            Entitlements::for_subscription(Some(&subscription), EntitlementSettings::current(), chrono::Utc::now())
+        }
+        None => Entitlements::unrestricted(chrono::Utc::now()),
+    };
+    let size = settings.window_size();
+    if let Err(e) = entitlements.check_export_resolution(size.width, size.height) {
+        eprintln!("Cannot export project {}: {}", args.project_id, e);
+        return 1;
+    }
+    plan.watermark = entitlements.watermark.clone();
+
+    let output_path = match args.output_path {
+        Some(output_path) => output_path,
+        None => {
+            let project_name = get_project_name(&args.project_id).unwrap_or(args.project_id.clone());
+            match settings.output_path(&project_name, plan.sequence_name.as_deref()) {
+                Ok(output_path) => output_path,
+                Err(e) => {
+                    eprintln!("Cannot export project {}: {}", args.project_id, e);
+                    return 1;
+                }
+            }
+        }
+    };
+
+    if let Some(parent) = output_path.parent() {
+        if !parent.as_os_str().is_empty() {
+            if let Err(e) = std::fs::create_dir_all(parent) {
+                eprintln!("Failed to create output directory {}: {}", parent.display(), e);
+                return 1;
+            }
+        }
+    }
+
+    let (progress_tx, mut progress_rx) = tokio_mpsc::unbounded_channel::<ExportProgress>();
+    let cancel_token = CancelToken::new();
+
+    let export_thread = spawn_export(
+        plan,
+        &output_path,
+        &settings,
+        args.project_id.clone(),
+        progress_tx,
+        cancel_token.clone(),
+    );
+
+    // Ctrl+C cancels the render and lets the export thread clean up its partial file
+    tokio::spawn({
+        let cancel_token = cancel_token.clone();
+        async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                println!("Cancelling export...");
+                cancel_token.cancel();
+            }
+        }
+    });
+
+    let mut exit_code = 1;
+    let mut current_sequence = None;
+
+    // The channel closes once the export thread drops its sender
+    while let Some(progress) = progress_rx.recv().await {
+        match progress {
+            ExportProgress::Progress { percent, sequence_name } => {
+                if sequence_name.is_some() && sequence_name != current_sequence {
+                    println!("Rendering sequence: {}", sequence_name.as_deref().unwrap_or_default());
+                    current_sequence = sequence_name;
+                }
+                println!("Exporting: {:.1}%", percent);
+            }
+            ExportProgress::Complete(output_path) => {
+                println!("Export complete: {}", output_path);
+                exit_code = 0;
+            }
+            ExportProgress::Error(err) => {
+                eprintln!("Export failed: {}", err);
+                exit_code = 1;
+            }
+            ExportProgress::Cancelled => {
+                eprintln!("Export cancelled");
+                exit_code = 130;
+            }
+        }
+    }
+
+    if export_thread.join().is_err() {
+        eprintln!("Export thread panicked");
+        exit_code = 1;
+    }
+
+    exit_code
+}
@@ -0,0 +1,270 @@
+use super::{unsupported, AppSignals, Command, CommandContext, CommandFamily, CommandHandler, CommandResult};
+use crate::helpers;
+use crate::helpers::utilities::AuthState;
+use crate::api::ApiClient;
+use crate::helpers::credentials::credential_store;
+use crate::helpers::entitlements::{cache_subscription, clear_cached_subscription};
+use crate::{clear_auth_token_everywhere, clear_stored_auth_token, get_stored_auth_token, load_local_projects, store_auth_token};
+
+pub struct AuthHandler;
+
+impl CommandHandler for AuthHandler {
+    fn family(&self) -> CommandFamily {
+        CommandFamily::Auth
+    }
+
+    fn handle(&mut self, command: Command, ctx: &mut CommandContext<'_>) -> CommandResult {
+        let CommandContext { editor, signals, .. } = ctx;
+        let AppSignals {
+            auth_state,
+            auth_loading,
+            show_auth_form,
+            is_offline,
+            plan_notice,
+            account_signals,
+            auth_session,
+            local_projects_signal,
+            selected_project_signal,
+            show_project_list,
+            show_project_creation,
+            show_editor,
+            ..
+        } = &**signals;
+
+        match command {
+            Command::SubmitSignIn { email, password } => {
+                println!("Processing sign in command");
+                auth_loading.set(true);
+                
+                let auth_state = auth_state.clone();
+                let local_projects_signal = local_projects_signal.clone();
+                let show_auth_form = show_auth_form.clone();
+                let show_project_list = show_project_list.clone();
+                let auth_loading = auth_loading.clone();
+                let is_offline = is_offline.clone();
+                let plan_notice = plan_notice.clone();
+                
+                tokio::spawn(async move {
+                    match ApiClient::from_settings().login(&email, &password).await {
+                        Ok(auth_response) => {
+                            // Create AuthToken with expiry
+                            let auth_token = auth_response.jwt_data.to_auth_token();
+                            
+                            // Store token securely in the credential store
+                            if let Err(e) = store_auth_token(&*credential_store(), &auth_token) {
+                                println!("Failed to store auth token: {}", e);
+                            }
+                            
+                            // Fetch subscription details
+                            match helpers::utilities::fetch_subscription_details(&auth_response.jwt_data.token).await {
+                                Ok(subscription) => {
+                                    let new_auth_state = AuthState {
+                                        token: Some(auth_token),
+                                        is_authenticated: true,
+                                        subscription: Some(subscription),
+                                        offline: false,
+                                    };
+                                    
+                                    // Load local projects
+                                    match load_local_projects() {
+                                        Ok(projects) => {
+                                            plan_notice.set(new_auth_state.entitlements().notice.unwrap_or_default());
+                                            auth_state.set(new_auth_state);
+                                            local_projects_signal.set(projects);
+                                            auth_loading.set(false);
+                                            is_offline.set(false);
+                                            show_auth_form.set(false);
+                                            show_project_list.set(true);
+                                            
+                                            println!("Authentication successful, loaded local projects");
+                                        }
+                                        Err(e) => {
+                                            println!("Failed to load local projects: {}", e);
+                                            auth_loading.set(false);
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    println!("Failed to fetch subscription details: {}", e);
+                                    auth_loading.set(false);
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            println!("Authentication failed: {}", e);
+                            auth_loading.set(false);
+                        }
+                    }
+                });
+            }
+            Command::SignOut => {
+                println!("Processing sign out command");
+                
+                // Clear stored token so the next launch doesn't sign back in
+                clear_auth_token_everywhere();
+                // The next account to work offline here shouldn't inherit this one's plan
+                clear_cached_subscription();
+                plan_notice.set(String::new());
+                account_signals.close();
+                
+                // Reset authentication state
+                let new_auth_state = AuthState {
+                    token: None,
+                    is_authenticated: false,
+                    subscription: None,
+                    offline: false,
+                };
+                auth_state.set(new_auth_state);
+                is_offline.set(false);
+                local_projects_signal.set(Vec::new());
+                selected_project_signal.set(None);
+                
+                // Update UI visibility
+                show_project_list.set(false);
+                show_project_creation.set(false);
+                show_auth_form.set(true);
+                show_editor.set(false);
+                
+                // Hide canvas
+                editor.canvas_hidden = true;
+                
+                println!("User signed out successfully");
+            }
+            Command::WorkOffline => {
+                println!("Processing work offline command");
+
+                // Keep a stored token around so Go Online can resume the session
+                let new_auth_state = AuthState {
+                    token: get_stored_auth_token(&*credential_store()),
+                    is_authenticated: false,
+                    subscription: None,
+                    offline: true,
+                };
+                plan_notice.set(new_auth_state.entitlements().notice.unwrap_or_default());
+                auth_state.set(new_auth_state);
+                is_offline.set(true);
+
+                local_projects_signal.set(load_local_projects().map_err(|e| format!("Failed to load local projects: {}", e))?);
+                show_auth_form.set(false);
+                if selected_project_signal.get().is_none() {
+                    show_project_list.set(true);
+                }
+
+                println!("Working offline, cloud features are off");
+            }
+            Command::GoOnline => {
+                println!("Processing go online command");
+
+                let Some(token) = auth_state.get().token else {
+                    // Nothing to resume; the sign-in form leaves offline mode once it succeeds
+                    show_auth_form.set(true);
+                    return Ok(());
+                };
+
+                auth_loading.set(true);
+
+                let auth_state = auth_state.clone();
+                let show_auth_form = show_auth_form.clone();
+                let auth_loading = auth_loading.clone();
+                let is_offline = is_offline.clone();
+                let plan_notice = plan_notice.clone();
+
+                tokio::spawn(async move {
+                    match helpers::utilities::fetch_subscription_details(&token.token).await {
+                        Ok(subscription) => {
+                            let online_state = AuthState {
+                                token: Some(token),
+                                is_authenticated: true,
+                                subscription: Some(subscription),
+                                offline: false,
+                            };
+                            plan_notice.set(online_state.entitlements().notice.unwrap_or_default());
+                            auth_state.set(online_state);
+                            is_offline.set(false);
+                            println!("Back online");
+                        }
+                        Err(e) if e.is_unauthorized() => {
+                            // Only a rejected token is thrown away; stay offline until signing in again
+                            println!("Stored token was rejected: {}", e);
+                            if let Err(e) = clear_stored_auth_token(&*credential_store()) {
+                                println!("Failed to remove the stored auth token: {}", e);
+                            }
+
+                            let mut offline_state = auth_state.get();
+                            offline_state.token = None;
+                            auth_state.set(offline_state);
+                            show_auth_form.set(true);
+                        }
+                        Err(e) => println!("Still offline: {}", e),
+                    }
+
+                    auth_loading.set(false);
+                });
+            }
+            Command::ShowAccountPanel => {
+                println!("Processing show account panel command");
+
+                account_signals.load(&auth_state.get());
+                account_signals.visible.set(true);
+            }
+            Command::RefreshAccount => {
+                println!("Processing refresh account command");
+
+                if !auth_state.get().cloud_available() {
+                    account_signals.message.set("Go online to refresh your subscription".to_string());
+                    return Ok(());
+                }
+
+                account_signals.message.set("Refreshing...".to_string());
+
+                let auth_state = auth_state.clone();
+                let auth_session = auth_session.clone();
+                let plan_notice = plan_notice.clone();
+                let account_signals = account_signals.clone();
+
+                tokio::spawn(async move {
+                    let used_token = std::sync::Mutex::new(String::new());
+
+                    // A 401 refreshes the token and tries once more
+                    let result = auth_session
+                        .authorized(|token| {
+                            *used_token.lock().unwrap() = token.clone();
+                            async move { ApiClient::from_settings().subscription_details(&token).await }
+                        })
+                        .await;
+
+                    // Signing out while the request was in flight clears the plan and its
+                    // cache, so an answer for the old session mustn't bring them back
+                    let current_token = auth_state.get().token.map(|token| token.token);
+                    if current_token.as_deref() != Some(used_token.lock().unwrap().as_str()) {
+                        println!("Session changed during the subscription refresh, ignoring the result");
+                        account_signals.message.set(String::new());
+                        return;
+                    }
+
+                    match result {
+                        Ok(subscription) => {
+                            cache_subscription(&subscription);
+
+                            let mut refreshed_state = auth_state.get();
+                            refreshed_state.subscription = Some(subscription);
+
+                            plan_notice.set(refreshed_state.entitlements().notice.unwrap_or_default());
+                            account_signals.load(&refreshed_state);
+                            auth_state.set(refreshed_state);
+                            println!("Subscription details refreshed");
+                        }
+                        Err(e) => {
+                            println!("Failed to refresh subscription details: {}", e);
+                            account_signals.message.set(format!("Couldn't refresh the subscription: {}", e));
+                        }
+                    }
+                });
+            }
+            Command::CloseAccountPanel => account_signals.close(),
+            other => return unsupported(&other, self.family()),
+        }
+
+        Ok(())
+    }
+}
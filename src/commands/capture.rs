@@ -0,0 +1,109 @@
+use stunts_engine::capture::{get_sources, WindowInfo};
+
+use super::{
+    capture_source_option, unsupported, AppSignals, Command, CommandContext, CommandFamily,
+    CommandHandler, CommandResult,
+};
+
+pub struct CaptureHandler;
+
+impl CommandHandler for CaptureHandler {
+    fn family(&self) -> CommandFamily {
+        CommandFamily::Capture
+    }
+
+    fn handle(&mut self, command: Command, ctx: &mut CommandContext<'_>) -> CommandResult {
+        let CommandContext { editor, editor_project_id, signals, .. } = ctx;
+        let AppSignals {
+            capture_sources_visible,
+            available_capture_sources,
+            is_recording,
+            capture_button_text,
+            ..
+        } = &**signals;
+
+        match command {
+            Command::ShowCaptureSources => {
+                println!("Processing show capture sources command");
+                // Get available capture sources (Windows enumeration)
+                // Note: This runs in render thread where editor is accessible
+                match get_sources() {
+                    Ok(sources) => {
+                        let filtered_sources: Vec<WindowInfo> = sources
+                            .into_iter()
+                            .filter(|s| s.title.len() > 1 && s.rect.width > 100 && s.rect.height > 100)
+                            .collect();
+
+                        // Store sources for UI (can't directly update UI from here)
+                        // The UI will need to be rebuilt with source buttons
+                        println!("Found {} valid capture sources", filtered_sources.len());
+                        for source in &filtered_sources {
+                            println!("Source: {} (HWND: {}) - {}x{}",
+                                source.title, source.hwnd, source.rect.width, source.rect.height);
+                        }
+
+                        // Show the dropdown
+                        capture_sources_visible.set(true);
+
+                            
+
+                        // Update the signal with available sources
+                        available_capture_sources.set(filtered_sources.iter().map(capture_source_option).collect());
+                    }
+                    Err(e) => {
+                        println!("Failed to get capture sources: {}", e);
+                    }
+                }
+            }
+            Command::StartScreenCapture { hwnd, width, height } => {
+                println!("Processing start screen capture command for HWND: {}", hwnd);
+
+                editor.st_capture
+                    .start_mouse_tracking()
+                    .map_err(|e| format!("Couldn't start mouse tracking: {}", e))?;
+
+                match editor.st_capture.start_video_capture(hwnd, width as u32, height as u32, editor_project_id.to_string()) {
+                    Ok(_) => {
+                        println!("Screen capture started successfully");
+                        is_recording.set(true);
+                        capture_button_text.set("Stop Capture".to_string());
+                        capture_sources_visible.set(false);
+                    }
+                    Err(e) => {
+                        println!("Failed to start screen capture: {}", e);
+                    }
+                }
+            }
+            Command::StopScreenCapture => {
+                println!("Processing stop screen capture command");
+
+                let mouse_positions_path = editor.st_capture
+                            .stop_mouse_tracking(
+                                editor_project_id.to_string(),
+                            )
+                            .map_err(|e| format!("Couldn't stop mouse tracking: {}", e))?;
+
+                match editor.st_capture.stop_video_capture(editor_project_id.to_string()) {
+                    Ok((video_path, mouse_data_path)) => {
+                        println!("Screen capture stopped successfully");
+                        println!("Video saved to: {:?}", video_path);
+                        println!("Mouse data saved to: {:?}", mouse_data_path);
+
+                        is_recording.set(false);
+                        capture_button_text.set("Screen Capture".to_string());
+
+                        // Video will be automatically added via the completion callback
+                    }
+                    Err(e) => {
+                        println!("Failed to stop screen capture: {}", e);
+                        is_recording.set(false);
+                        capture_button_text.set("Screen Capture".to_string());
+                    }
+                }
+            }
+            other => return unsupported(&other, self.family()),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use stunts_engine::saved_state::save_saved_state_raw;
+
+use super::{unsupported, AppSignals, Command, CommandContext, CommandFamily, CommandHandler, CommandResult};
+use crate::export::{self, ExportJob};
+
+pub struct ExportHandler;
+
+impl CommandHandler for ExportHandler {
+    fn family(&self) -> CommandFamily {
+        CommandFamily::Export
+    }
+
+    fn handle(&mut self, command: Command, ctx: &mut CommandContext<'_>) -> CommandResult {
+        let CommandContext {
+            editor,
+            selected_project,
+            signals,
+            export_queue,
+            preferences,
+            ..
+        } = ctx;
+        let AppSignals {
+            export_status,
+            export_button_text,
+            is_exporting,
+            export_progress_signals,
+            auth_state,
+            ..
+        } = &**signals;
+
+        match command {
+            Command::SaveExportSettings { settings } => {
+                println!("Saving export settings: {:?}", settings);
+
+                match settings.save(&selected_project.project_id) {
+                    Ok(_) => export_status.set("Export settings saved".to_string()),
+                    Err(e) => {
+                        println!("Failed to save export settings: {}", e);
+                        export_status.set("Failed to save export settings".to_string());
+                    }
+                }
+            }
+            Command::SetRevealAfterExport { enabled } => {
+                println!("Setting reveal after export: {}", enabled);
+
+                preferences.reveal_after_export = enabled;
+                if let Err(e) = preferences.save() {
+                    println!("Failed to save preferences: {}", e);
+                }
+            }
+            Command::Export { settings, scope } => {
+                println!("Processing export command");
+                
+                // Validate against the open project so mistakes show up now rather than when the job runs
+                let saved_state = if let Some(ref saved_state) = editor.saved_state {
+                    saved_state.clone()
+                } else {
+                    export_status.set("No sequences to export".to_string());
+                    return Err("No saved state found".to_string());
+                };
+                
+                if let Err(e) = export::plan_export(saved_state.sequences.clone(), &scope) {
+                    export_status.set(e.clone());
+                    return Err(e);
+                }
+
+                let entitlements = auth_state.get().entitlements();
+                let size = settings.window_size();
+                if let Err(e) = entitlements.check_export_resolution(size.width, size.height) {
+                    export_status.set(e.clone());
+                    return Err(e);
+                }
+                
+                // Queued jobs render from disk, so make sure it matches what's on screen
+                save_saved_state_raw(saved_state);
+
+                // Remember the settings used so the next export starts from them
+                if let Err(e) = settings.save(&selected_project.project_id) {
+                    println!("Failed to save export settings: {}", e);
+                }
+
+                let job = ExportJob::new(
+                    selected_project.project_id.clone(),
+                    selected_project.project_name.clone(),
+                    scope,
+                    settings,
+                )
+                .with_watermark(entitlements.watermark.clone());
+
+                if export_queue.is_empty() {
+                    export_status.set(if job.watermark.is_some() {
+                        format!("Starting export (watermarked on the {} plan)...", entitlements.plan_name)
+                    } else {
+                        "Starting export...".to_string()
+                    });
+                    export_button_text.set("Exporting...".to_string());
+                } else {
+                    export_status.set(format!("Queued {} ({} waiting)", job.label(), export_queue.len()));
+                    export_progress_signals.set_queued(export_queue.len());
+                }
+
+                is_exporting.set(true);
+                export_queue.enqueue(job);
+            }
+            Command::CancelExport => {
+                println!("Processing cancel export command");
+
+                if let Some(job_id) = export_queue.running_job_id() {
+                    export_queue.cancel(&job_id);
+                    export_status.set("Cancelling export...".to_string());
+                }
+            }
+            Command::ClearExportQueue => {
+                println!("Processing clear export queue command");
+
+                if !export_queue.is_empty() {
+                    export_queue.cancel_all();
+                    export_status.set("Cancelling all exports...".to_string());
+                }
+            }
+            other => return unsupported(&other, self.family()),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,577 @@
+use gui_core::widgets::dropdown::DropdownOption;
+use gui_reactive::Signal;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use stunts_engine::capture::WindowInfo;
+use stunts_engine::editor::{Editor, WindowSize};
+use stunts_engine::saved_state::ProjectData;
+use uuid::Uuid;
+
+use crate::account_panel::AccountSignals;
+use crate::api::AuthSession;
+use crate::editor_state::EditorState;
+use crate::export::{ExportQueue, ExportScope, ExportSettings};
+use crate::export_progress_panel::ExportProgressSignals;
+use crate::export_settings_panel::ExportSettingsSignals;
+use crate::helpers::preferences::Preferences;
+use crate::helpers::utilities::AuthState;
+use crate::motion::{MergeMode, MotionPrompt, MotionUpdate};
+use crate::motion_candidates_panel::MotionCandidateSignals;
+use crate::motion_generation_panel::MotionGenerationSignals;
+
+mod auth;
+mod capture;
+mod export;
+mod objects;
+mod pending;
+mod projects;
+mod sequences;
+
+pub use auth::AuthHandler;
+pub use capture::CaptureHandler;
+pub use export::ExportHandler;
+pub use objects::ObjectHandler;
+pub(crate) use objects::receive_motion_candidate;
+pub use pending::{CommandReply, PendingCommands};
+pub use projects::ProjectHandler;
+pub use sequences::SequenceHandler;
+
+/// Actions sent from the UI thread to the render thread.
+/// The automation endpoint deserializes them from JSON-RPC, e.g. `{"method": "add_image", "params": {"file_path": "..."}}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum Command {
+    AddSquarePolygon,
+    AddText,
+    AddImage { file_path: String },
+    AddVideo { file_path: String },
+    AddMotion,
+    SubmitMotionForm {
+        description: String,
+        position: String,
+        scale: String,
+        rotation: String,
+        opacity: String,
+        delay: String,
+        duration: String,
+        /// How the result combines with the object's existing motion
+        #[serde(default)]
+        merge_mode: MergeMode,
+        /// Number of variants to generate; more than one keeps them as candidates to pick from
+        #[serde(default)]
+        variants: usize,
+    },
+    SetCloudMotionGeneration { enabled: bool },
+    /// Open the saved variants of the object the last motion arrow was drawn on
+    BrowseMotionCandidates,
+    /// Preview the variant `step` places after the current one, wrapping around
+    ShowMotionCandidate { step: i32 },
+    ApplyMotionCandidate,
+    DiscardMotionCandidates,
+    /// Generate new motion for every object with a motion path in the current sequence
+    RegenerateAllMotion,
+    /// A motion arrow was drawn; starts a new path or continues the one being chained
+    MotionArrowPlaced,
+    /// Draw another arrow that continues the current motion path from where it ends
+    AddMotionWaypoint,
+    /// Fill the motion form with the prompt last submitted for an object, if any
+    LoadMotionPrompt { object_id: String },
+    /// Submit the prompt loaded into the motion form again, with its saved arrow
+    RerunMotionPrompt,
+    /// Stop every motion generation request in flight, including Regenerate All
+    CancelMotionGeneration,
+    CloseMotionGenerationPanel,
+    UpdateTextProperty {
+        // text_id: String,
+        property_key: String,
+        property_value: String,
+    },
+    TogglePlay,
+    ShowCaptureSources,
+    StartScreenCapture { hwnd: usize, width: usize, height: usize },
+    StopScreenCapture,
+    Export {
+        settings: ExportSettings,
+        scope: ExportScope,
+    },
+    CancelExport,
+    ClearExportQueue,
+    SaveExportSettings { settings: ExportSettings },
+    SetRevealAfterExport { enabled: bool },
+    // Authentication commands
+    SubmitSignIn { email: String, password: String },
+    SignOut,
+    /// Open local projects without signing in; cloud features stay off
+    WorkOffline,
+    /// Check the stored token with the backend and leave offline mode if it's accepted
+    GoOnline,
+    /// Open the account panel with the subscription as last fetched
+    ShowAccountPanel,
+    /// Fetch the subscription from the backend again and update the account panel
+    RefreshAccount,
+    CloseAccountPanel,
+    // CheckAuthentication,
+    // Project management commands
+    LoadProjects,
+    SelectProject { project_id: String },
+    CreateProject { name: String },
+    CreateSequence { name: String, project_id: String },
+    SelectSequence { sequence_id: String },
+    LoadSequences,
+    ApplyTheme { theme: [f64; 5] },
+    RedisplayCanvas,
+}
+
+/// Groups of commands, each served by one handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandFamily {
+    Objects,
+    Sequences,
+    Projects,
+    Auth,
+    Export,
+    Capture,
+}
+
+impl Command {
+    pub fn family(&self) -> CommandFamily {
+        match self {
+            Command::AddSquarePolygon
+            | Command::AddText
+            | Command::AddImage { .. }
+            | Command::AddVideo { .. }
+            | Command::AddMotion
+            | Command::SubmitMotionForm { .. }
+            | Command::SetCloudMotionGeneration { .. }
+            | Command::BrowseMotionCandidates
+            | Command::ShowMotionCandidate { .. }
+            | Command::ApplyMotionCandidate
+            | Command::DiscardMotionCandidates
+            | Command::RegenerateAllMotion
+            | Command::MotionArrowPlaced
+            | Command::AddMotionWaypoint
+            | Command::LoadMotionPrompt { .. }
+            | Command::RerunMotionPrompt
+            | Command::CancelMotionGeneration
+            | Command::CloseMotionGenerationPanel
+            | Command::UpdateTextProperty { .. } => CommandFamily::Objects,
+            Command::CreateSequence { .. }
+            | Command::SelectSequence { .. }
+            | Command::LoadSequences
+            | Command::TogglePlay
+            | Command::ApplyTheme { .. }
+            | Command::RedisplayCanvas => CommandFamily::Sequences,
+            Command::LoadProjects
+            | Command::SelectProject { .. }
+            | Command::CreateProject { .. } => CommandFamily::Projects,
+            Command::SubmitSignIn { .. }
+            | Command::SignOut
+            | Command::WorkOffline
+            | Command::GoOnline
+            | Command::ShowAccountPanel
+            | Command::RefreshAccount
+            | Command::CloseAccountPanel => CommandFamily::Auth,
+            Command::Export { .. }
+            | Command::CancelExport
+            | Command::ClearExportQueue
+            | Command::SaveExportSettings { .. }
+            | Command::SetRevealAfterExport { .. } => CommandFamily::Export,
+            Command::ShowCaptureSources
+            | Command::StartScreenCapture { .. }
+            | Command::StopScreenCapture => CommandFamily::Capture,
+        }
+    }
+}
+
+/// Label of the Regenerate All button while it isn't running
+pub const REGENERATE_ALL_LABEL: &str = "Regenerate All";
+
+/// Errors are reported by the dispatcher's caller; handlers update the UI themselves
+pub type CommandResult = Result<(), String>;
+
+/// UI state the handlers read and update
+#[derive(Clone)]
+pub struct AppSignals {
+    pub display_motion_form: Signal<bool>,
+    /// Progress and errors of motion being generated
+    pub motion_generation_signals: MotionGenerationSignals,
+    /// Inline errors under the motion form's Duration and Delay fields
+    pub motion_duration_error: Signal<String>,
+    pub motion_delay_error: Signal<String>,
+    pub motion_candidate_signals: MotionCandidateSignals,
+    /// Label of the Regenerate All button, which doubles as its progress
+    pub regenerate_all_label: Signal<String>,
+    pub is_regenerating: Signal<bool>,
+    pub motion_form_signals: MotionFormSignals,
+    pub capture_sources_visible: Signal<bool>,
+    pub available_capture_sources: Signal<Vec<DropdownOption>>,
+    pub is_recording: Signal<bool>,
+    pub capture_button_text: Signal<String>,
+    pub export_status: Signal<String>,
+    pub export_button_text: Signal<String>,
+    pub is_exporting: Signal<bool>,
+    pub export_progress_signals: ExportProgressSignals,
+    pub export_settings_signals: ExportSettingsSignals,
+    pub auth_state: Signal<AuthState>,
+    pub auth_loading: Signal<bool>,
+    pub show_auth_form: Signal<bool>,
+    /// Mirrors `AuthState::offline` for the UI
+    pub is_offline: Signal<bool>,
+    /// Keeps the token fresh for backend calls and signs out when it can't
+    pub auth_session: AuthSession,
+    /// What the plan limits or why an action was refused, empty when there's nothing to say
+    pub plan_notice: Signal<String>,
+    pub account_signals: AccountSignals,
+    pub local_projects_signal: Signal<Vec<ProjectData>>,
+    pub selected_project_signal: Signal<Option<ProjectData>>,
+    pub show_project_list: Signal<bool>,
+    pub show_project_creation: Signal<bool>,
+    pub show_editor: Signal<bool>,
+    pub project_name_text: Signal<String>,
+    pub current_sequence_id: Signal<String>,
+    pub available_sequences: Signal<Vec<DropdownOption>>,
+}
+
+/// Text fields of the motion form
+#[derive(Clone)]
+pub struct MotionFormSignals {
+    pub description: Signal<String>,
+    pub position: Signal<String>,
+    pub scale: Signal<String>,
+    pub opacity: Signal<String>,
+    pub rotation: Signal<String>,
+    pub delay: Signal<String>,
+    pub duration: Signal<String>,
+    /// Object whose saved prompt was last loaded or submitted, the one Re-run Motion regenerates
+    pub object_id: Signal<String>,
+    /// "Path: 3 points" while arrows are being chained, empty otherwise
+    pub waypoints: Signal<String>,
+}
+
+impl MotionFormSignals {
+    pub fn fill(&self, prompt: &MotionPrompt) {
+        self.description.set(prompt.description.clone());
+        self.position.set(prompt.position.clone());
+        self.scale.set(prompt.scale.clone());
+        self.opacity.set(prompt.opacity.clone());
+        self.rotation.set(prompt.rotation.clone());
+        self.delay.set(prompt.delay.clone());
+        self.duration.set(prompt.duration.clone());
+    }
+}
+
+/// Device and queue for commands that upload to the GPU (text, images, video)
+pub struct GpuContext<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+}
+
+/// Everything a handler may touch while processing one command
+pub struct CommandContext<'a> {
+    pub editor: &'a mut Editor,
+    pub editor_state: &'a mut EditorState,
+    /// `None` outside the render loop, e.g. in tests; GPU-backed commands fail without it
+    pub gpu: Option<GpuContext<'a>>,
+    pub window_size: WindowSize,
+    /// Id the editor was created with, used to name capture output
+    pub editor_project_id: Uuid,
+    pub selected_project: ProjectData,
+    pub signals: &'a AppSignals,
+    pub command_tx: mpsc::Sender<Command>,
+    pub api_response_tx: mpsc::Sender<MotionUpdate>,
+    pub export_queue: &'a ExportQueue,
+    pub preferences: &'a mut Preferences,
+}
+
+pub trait CommandHandler {
+    fn family(&self) -> CommandFamily;
+
+    fn handle(&mut self, command: Command, ctx: &mut CommandContext<'_>) -> CommandResult;
+}
+
+/// Routes each command to the handler registered for its family
+pub struct CommandDispatcher {
+    handlers: HashMap<CommandFamily, Box<dyn CommandHandler>>,
+}
+
+impl CommandDispatcher {
+    pub fn new() -> Self {
+        Self {
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// The handlers used by the editor
+    pub fn with_default_handlers() -> Self {
+        Self::new()
+            .with_handler(ObjectHandler::default())
+            .with_handler(SequenceHandler)
+            .with_handler(ProjectHandler)
+            .with_handler(AuthHandler)
+            .with_handler(ExportHandler)
+            .with_handler(CaptureHandler)
+    }
+
+    pub fn with_handler(mut self, handler: impl CommandHandler + 'static) -> Self {
+        self.register(Box::new(handler));
+        self
+    }
+
+    /// Register a handler, replacing any previous handler for the same family
+    pub fn register(&mut self, handler: Box<dyn CommandHandler>) {
+        self.handlers.insert(handler.family(), handler);
+    }
+
+    pub fn dispatch(&mut self, command: Command, ctx: &mut CommandContext<'_>) -> CommandResult {
+        let family = command.family();
+
+        match self.handlers.get_mut(&family) {
+            Some(handler) => handler.handle(command, ctx),
+            None => Err(format!("No handler registered for {:?} commands", family)),
+        }
+    }
+}
+
+fn require_gpu<'g, 'a>(gpu: &'g Option<GpuContext<'a>>) -> Result<&'g GpuContext<'a>, String> {
+    gpu.as_ref()
+        .ok_or_else(|| "This command needs the GPU, which isn't available here".to_string())
+}
+
+/// Error for commands that edit a project when none is open, e.g. sent by automation before `select_project`
+const NO_PROJECT_OPEN: &str = "Open a project first";
+
+/// Check a project is open and has the given sequence, before a command adds anything to it
+fn require_sequence(editor: &Editor, sequence_id: &str) -> CommandResult {
+    let saved_state = editor.saved_state.as_ref().ok_or(NO_PROJECT_OPEN)?;
+    if saved_state.sequences.iter().any(|s| s.id == sequence_id) {
+        Ok(())
+    } else {
+        Err("Select a sequence first".to_string())
+    }
+}
+
+/// Error for a command routed to a handler of another family. Only the command's family is
+/// named, commands can carry credentials.
+fn unsupported(command: &Command, family: CommandFamily) -> CommandResult {
+    Err(format!("{:?} command sent to the {:?} handler", command.family(), family))
+}
+
+fn sequence_options(sequences: &[stunts_engine::animations::Sequence]) -> Vec<DropdownOption> {
+    sequences
+        .iter()
+        .map(|seq| DropdownOption {
+            label: seq.name.clone(),
+            value: seq.id.clone(),
+        })
+        .collect()
+}
+
+fn capture_source_option(source: &WindowInfo) -> DropdownOption {
+    DropdownOption {
+        label: format!("{} ({}x{})", source.title, source.rect.width, source.rect.height),
+        // value: source.hwnd.to_string(),
+        value: format!("{}-{}x{}", source.hwnd.to_string(), source.rect.width, source.rect.height),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
+    use stunts_engine::editor::Viewport;
+    use undo::Record;
+
+    /// Records the family of each command it handles and fails Sign Out
+    struct FakeHandler {
+        family: CommandFamily,
+        name: &'static str,
+        handled: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl CommandHandler for FakeHandler {
+        fn family(&self) -> CommandFamily {
+            self.family
+        }
+
+        fn handle(&mut self, command: Command, _ctx: &mut CommandContext<'_>) -> CommandResult {
+            self.handled.borrow_mut().push(format!("{}: {:?}", self.name, command.family()));
+
+            match command {
+                Command::SignOut => Err("sign out failed".to_string()),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    fn fake_handler(family: CommandFamily, name: &'static str, handled: &Rc<RefCell<Vec<String>>>) -> FakeHandler {
+        FakeHandler {
+            family,
+            name,
+            handled: handled.clone(),
+        }
+    }
+
+    fn fake_signals() -> AppSignals {
+        let auth_state = Signal::new(AuthState {
+            token: None,
+            is_authenticated: false,
+            subscription: None,
+            offline: true,
+        });
+        let show_auth_form = Signal::new(false);
+
+        AppSignals {
+            display_motion_form: Signal::new(false),
+            motion_generation_signals: MotionGenerationSignals::new(),
+            motion_duration_error: Signal::new(String::new()),
+            motion_delay_error: Signal::new(String::new()),
+            motion_candidate_signals: MotionCandidateSignals::new(),
+            regenerate_all_label: Signal::new(String::new()),
+            is_regenerating: Signal::new(false),
+            motion_form_signals: MotionFormSignals {
+                description: Signal::new(String::new()),
+                position: Signal::new(String::new()),
+                scale: Signal::new(String::new()),
+                opacity: Signal::new(String::new()),
+                rotation: Signal::new(String::new()),
+                delay: Signal::new(String::new()),
+                duration: Signal::new(String::new()),
+                object_id: Signal::new(String::new()),
+                waypoints: Signal::new(String::new()),
+            },
+            capture_sources_visible: Signal::new(false),
+            available_capture_sources: Signal::new(Vec::new()),
+            is_recording: Signal::new(false),
+            capture_button_text: Signal::new(String::new()),
+            export_status: Signal::new(String::new()),
+            export_button_text: Signal::new(String::new()),
+            is_exporting: Signal::new(false),
+            export_progress_signals: ExportProgressSignals::new(),
+            export_settings_signals: ExportSettingsSignals::new(),
+            auth_session: AuthSession::new(auth_state.clone(), show_auth_form.clone()),
+            auth_state,
+            auth_loading: Signal::new(false),
+            show_auth_form,
+            is_offline: Signal::new(true),
+            plan_notice: Signal::new(String::new()),
+            account_signals: AccountSignals::new(),
+            local_projects_signal: Signal::new(Vec::new()),
+            selected_project_signal: Signal::new(None),
+            show_project_list: Signal::new(false),
+            show_project_creation: Signal::new(false),
+            show_editor: Signal::new(true),
+            project_name_text: Signal::new(String::new()),
+            current_sequence_id: Signal::new(String::new()),
+            available_sequences: Signal::new(Vec::new()),
+        }
+    }
+
+    /// Dispatch `commands` with a context that has no GPU and no project open
+    fn dispatch_all(dispatcher: &mut CommandDispatcher, commands: Vec<Command>) -> Vec<CommandResult> {
+        let window_size = WindowSize { width: 1200, height: 800 };
+        let viewport = Arc::new(Mutex::new(Viewport::new(window_size.width as f32, window_size.height as f32)));
+        let project_id = Uuid::new_v4();
+        let mut editor = Editor::new(viewport.clone(), project_id.to_string());
+        let mut editor_state = EditorState::new(
+            Arc::new(Mutex::new(Editor::new(viewport, project_id.to_string()))),
+            Arc::new(Mutex::new(Record::new())),
+        );
+        let signals = fake_signals();
+        let export_queue = ExportQueue::idle();
+        let mut preferences = Preferences::default();
+
+        let mut ctx = CommandContext {
+            editor: &mut editor,
+            editor_state: &mut editor_state,
+            gpu: None,
+            window_size,
+            editor_project_id: project_id,
+            selected_project: ProjectData {
+                project_id: project_id.to_string(),
+                project_name: "Test project".to_string(),
+            },
+            signals: &signals,
+            command_tx: mpsc::channel().0,
+            api_response_tx: mpsc::channel().0,
+            export_queue: &export_queue,
+            preferences: &mut preferences,
+        };
+
+        commands
+            .into_iter()
+            .map(|command| dispatcher.dispatch(command, &mut ctx))
+            .collect()
+    }
+
+    #[test]
+    fn routes_commands_to_their_family() {
+        let handled = Rc::new(RefCell::new(Vec::new()));
+        let mut dispatcher = CommandDispatcher::new()
+            .with_handler(fake_handler(CommandFamily::Objects, "objects", &handled))
+            .with_handler(fake_handler(CommandFamily::Auth, "auth", &handled));
+
+        let results = dispatch_all(&mut dispatcher, vec![Command::AddText, Command::WorkOffline, Command::AddSquarePolygon]);
+
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(*handled.borrow(), vec!["objects: Objects", "auth: Auth", "objects: Objects"]);
+    }
+
+    #[test]
+    fn reports_missing_handlers() {
+        let handled = Rc::new(RefCell::new(Vec::new()));
+        let mut dispatcher = CommandDispatcher::new().with_handler(fake_handler(CommandFamily::Objects, "objects", &handled));
+
+        let results = dispatch_all(&mut dispatcher, vec![Command::SignOut]);
+
+        assert_eq!(results, vec![Err("No handler registered for Auth commands".to_string())]);
+        assert!(handled.borrow().is_empty());
+    }
+
+    #[test]
+    fn passes_handler_errors_through() {
+        let handled = Rc::new(RefCell::new(Vec::new()));
+        let mut dispatcher = CommandDispatcher::new().with_handler(fake_handler(CommandFamily::Auth, "auth", &handled));
+
+        let results = dispatch_all(&mut dispatcher, vec![Command::SignOut, Command::WorkOffline]);
+
+        assert_eq!(results, vec![Err("sign out failed".to_string()), Ok(())]);
+    }
+
+    #[test]
+    fn later_registration_replaces_the_handler() {
+        let handled = Rc::new(RefCell::new(Vec::new()));
+        let mut dispatcher = CommandDispatcher::new()
+            .with_handler(fake_handler(CommandFamily::Objects, "first", &handled))
+            .with_handler(fake_handler(CommandFamily::Objects, "second", &handled));
+
+        dispatch_all(&mut dispatcher, vec![Command::AddText]);
+
+        assert_eq!(*handled.borrow(), vec!["second: Objects"]);
+    }
+
+    #[test]
+    fn real_handlers_fail_without_a_project_instead_of_panicking() {
+        let mut dispatcher = CommandDispatcher::with_default_handlers();
+
+        let results = dispatch_all(&mut dispatcher, vec![Command::AddSquarePolygon, Command::AddText]);
+
+        assert!(results.iter().all(Result::is_err));
+    }
+
+    #[test]
+    fn unsupported_does_not_leak_credentials() {
+        let command = Command::SubmitSignIn {
+            email: "user@example.com".to_string(),
+            password: "hunter2".to_string(),
+        };
+
+        let error = unsupported(&command, CommandFamily::Objects).unwrap_err();
+
+        assert_eq!(error, "Auth command sent to the Objects handler");
+        assert!(!error.contains("hunter2"));
+        assert!(!error.contains("user@example.com"));
+    }
+}
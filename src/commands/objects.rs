@@ -0,0 +1,1314 @@
+use gui_reactive::Signal;
+use std::fs;
+use std::path::Path;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+use stunts_engine::animations::{AnimationData, KeyframeValue, ObjectType, Sequence};
+use stunts_engine::capture::{MousePosition, SourceData};
+use stunts_engine::editor::{Editor, ObjectProperty, Point, WindowSize};
+use stunts_engine::polygon::{PolygonConfig, SavedPoint, SavedPolygonConfig, SavedStroke, Stroke};
+use stunts_engine::saved_state::get_random_coords;
+use stunts_engine::st_image::{SavedStImageConfig, StImageConfig};
+use stunts_engine::st_video::{SavedStVideoConfig, StVideoConfig};
+use stunts_engine::text_due::{SavedTextRendererConfig, TextRendererConfig};
+use tokio::sync::Semaphore;
+use uuid::Uuid;
+
+use super::{
+    require_gpu, require_sequence, unsupported, AppSignals, Command, CommandContext, CommandFamily, CommandHandler,
+    CommandResult, NO_PROJECT_OPEN, REGENERATE_ALL_LABEL,
+};
+use crate::api::{ApiAnimationData, ApiAnimationProperty, ApiClient, ApiKeyframe, ApiKeyframeValue, ApiPosition, AuthSession};
+use crate::editor_state::EditorState;
+use crate::helpers::entitlements::{record_generations, Entitlements, GenerationUsage};
+use crate::motion_candidates_panel::MotionCandidateSignals;
+use crate::motion_generation_panel::MotionGenerationSignals;
+use crate::motion::{
+    apply_generated_motion, candidate_label, end_motion_preview, generate_motion_variant, parse_delay,
+    parse_duration, preview_generated_motion, GeneratedMotion, GenerationError, GenerationJob, MergeMode, MotionArrow, MotionCandidate, MotionCandidates, MotionPrompts, MotionUpdate,
+    MotionPrompt, MotionTiming, SavedMotionPrompt, MAX_VARIANTS,
+};
+
+#[derive(Default)]
+pub struct ObjectHandler {
+    /// Arrows drawn one after another for the same object, before the motion form is submitted
+    arrow_chain: Option<ArrowChain>,
+    /// Whether the next arrow drawn continues `arrow_chain` instead of starting over
+    extend_chain: bool,
+    /// Shared by every generation request started since the last cancel
+    generation: GenerationJob,
+}
+
+/// A motion path made of chained arrows, from the first arrow's start to the last arrow's end
+struct ArrowChain {
+    object_id: String,
+    object_type: ObjectType,
+    /// Width and height sent along with the request, or null
+    object_dimensions: serde_json::Value,
+    waypoints: Vec<Point>,
+}
+
+impl ArrowChain {
+    /// The chain made of just the last arrow drawn
+    fn from_last_arrow(editor: &Editor) -> Self {
+        let object_dimensions = editor.last_motion_arrow_object_dimensions.clone();
+
+        Self {
+            object_id: editor.last_motion_arrow_object_id.to_string(),
+            object_type: editor.last_motion_arrow_object_type.clone(),
+            object_dimensions: serde_json::json!(object_dimensions.map(|(w, h)| serde_json::json!({"width": w, "height": h}))),
+            waypoints: editor
+                .last_motion_arrow_end_positions
+                .clone()
+                .map(|(start, end)| vec![start, end])
+                .unwrap_or_default(),
+        }
+    }
+
+    /// "Path: 3 points" for the motion form
+    fn label(&self) -> String {
+        format!("Path: {} points", self.waypoints.len())
+    }
+}
+
+impl CommandHandler for ObjectHandler {
+    fn family(&self) -> CommandFamily {
+        CommandFamily::Objects
+    }
+
+    fn handle(&mut self, command: Command, ctx: &mut CommandContext<'_>) -> CommandResult {
+        let CommandContext {
+            editor,
+            editor_state,
+            gpu,
+            window_size,
+            signals,
+            api_response_tx,
+            command_tx,
+            preferences,
+            selected_project,
+            ..
+        } = ctx;
+        let window_size = *window_size;
+        let AppSignals {
+            display_motion_form,
+            motion_generation_signals,
+            motion_duration_error,
+            motion_delay_error,
+            current_sequence_id,
+            is_regenerating,
+            regenerate_all_label,
+            motion_form_signals,
+            auth_session,
+            auth_state,
+            ..
+        } = &**signals;
+        // Offline, motion is generated locally whatever the preference says
+        let auth_state = auth_state.get();
+        let cloud_motion_generation = preferences.cloud_motion_generation && auth_state.cloud_available();
+
+        match command {
+            Command::AddMotion => {
+                println!("Processing add motion command from channel");
+                editor.motion_mode = true;
+                println!("Motion mode enabled - user can now place arrows by clicking and dragging");
+            }
+            Command::AddSquarePolygon => {
+                println!("Processing add square polygon command from channel");
+                require_sequence(editor, &current_sequence_id.get())?;
+                let random_coords = get_random_coords(window_size);
+                let new_id = Uuid::new_v4();
+
+                let polygon_config = PolygonConfig {
+                    id: new_id,
+                    name: "Square".to_string(),
+                    points: vec![
+                        Point { x: 0.0, y: 0.0 },
+                        Point { x: 1.0, y: 0.0 },
+                        Point { x: 1.0, y: 1.0 },
+                        Point { x: 0.0, y: 1.0 },
+                    ],
+                    dimensions: (100.0, 100.0),
+                    position: Point {
+                        x: random_coords.0 as f32,
+                        y: random_coords.1 as f32,
+                    },
+                    border_radius: 0.0,
+                    fill: [1.0, 0.0, 0.0, 1.0], // Red color
+                    stroke: Stroke {
+                        fill: [0.0, 0.0, 0.0, 1.0], // Black border
+                        thickness: 2.0,
+                    },
+                    layer: 2,
+                };
+
+                // println!("add polyong {:?}", current_sequence_id.get().clone());
+                
+                editor.add_polygon(
+                    polygon_config.clone(),
+                    polygon_config.name.clone(),
+                    polygon_config.id,
+                    current_sequence_id.get().clone(),
+                );
+
+                editor_state.add_saved_polygon(
+                    editor.saved_state.as_mut().ok_or(NO_PROJECT_OPEN)?,
+                    current_sequence_id.get().clone(),
+                    SavedPolygonConfig {
+                        id: polygon_config.id.to_string().clone(),
+                        name: polygon_config.name.clone(),
+                        dimensions: (
+                            polygon_config.dimensions.0 as i32,
+                            polygon_config.dimensions.1 as i32,
+                        ),
+                        fill: [
+                            polygon_config.fill[0] as i32,
+                            polygon_config.fill[1] as i32,
+                            polygon_config.fill[2] as i32,
+                            polygon_config.fill[3] as i32,
+                        ],
+                        border_radius: polygon_config.border_radius as i32, // multiply by 100?
+                        position: SavedPoint {
+                            x: polygon_config.position.x as i32,
+                            y: polygon_config.position.y as i32,
+                        },
+                        stroke: SavedStroke {
+                            thickness: polygon_config.stroke.thickness as i32,
+                            fill: [
+                                polygon_config.stroke.fill[0] as i32,
+                                polygon_config.stroke.fill[1] as i32,
+                                polygon_config.stroke.fill[2] as i32,
+                                polygon_config.stroke.fill[3] as i32,
+                            ],
+                        },
+                        layer: polygon_config.layer.clone(),
+                    },
+                );
+
+                let saved_state = editor
+                    .saved_state
+                    .as_ref()
+                    .ok_or(NO_PROJECT_OPEN)?;
+                let updated_sequence = saved_state
+                    .sequences
+                    .iter()
+                    .find(|s| s.id == current_sequence_id.get().clone())
+                    .ok_or("The current sequence no longer exists")?;
+                
+                let sequence_cloned = updated_sequence.clone();
+                
+                // drop(editor_state);
+
+                // let mut editor = editor_cloned.lock().unwrap();
+
+                editor.current_sequence_data = Some(sequence_cloned.clone());
+                editor.update_motion_paths(&sequence_cloned);
+                
+                // drop(editor);
+
+                println!("Square polygon added to editor successfully: {}", polygon_config.id);
+            }
+            Command::AddText => {
+                println!("Processing add text command from channel");
+                require_sequence(editor, &current_sequence_id.get())?;
+                let gpu = require_gpu(gpu)?;
+                let random_coords = get_random_coords(window_size);
+                let new_id = Uuid::new_v4();
+
+                let text_config = TextRendererConfig {
+                    id: new_id,
+                    name: "Text".to_string(),
+                    text: "Sample Text".to_string(),
+                    font_family: "Aleo".to_string(),
+                    dimensions: (200.0, 50.0),
+                    position: Point {
+                        x: random_coords.0 as f32,
+                        y: random_coords.1 as f32,
+                    },
+                    layer: 2,
+                    color: [0, 0, 0, 255],
+                    font_size: 24,
+                    background_fill: [255, 255, 255, 255],
+                };
+
+                let window_size = WindowSize {
+                    width: window_size.width,
+                    height: window_size.height,
+                };
+
+                editor.add_text_item(
+                    &window_size,
+                    gpu.device,
+                    gpu.queue,
+                    text_config.clone(),
+                    text_config.text.clone(),
+                    new_id,
+                    current_sequence_id.get().clone(),
+                );
+
+                editor_state.add_saved_text_item(
+                    editor.saved_state.as_mut().ok_or(NO_PROJECT_OPEN)?,
+                    current_sequence_id.get().clone(),
+                    SavedTextRendererConfig {
+                        id: text_config.id.to_string(),
+                        name: text_config.name.clone(),
+                        text: text_config.text.clone(),
+                        font_family: text_config.font_family.clone(),
+                        dimensions: (text_config.dimensions.0 as i32, text_config.dimensions.1 as i32),
+                        position: SavedPoint {
+                            x: text_config.position.x as i32,
+                            y: text_config.position.y as i32,
+                        },
+                        layer: text_config.layer,
+                        color: text_config.color,
+                        font_size: text_config.font_size,
+                        background_fill: Some(text_config.background_fill),
+                    },
+                );
+
+                let saved_state = editor
+                    .saved_state
+                    .as_ref()
+                    .ok_or(NO_PROJECT_OPEN)?;
+                let updated_sequence = saved_state
+                    .sequences
+                    .iter()
+                    .find(|s| s.id == current_sequence_id.get().clone())
+                    .ok_or("The current sequence no longer exists")?;
+                
+                let sequence_cloned = updated_sequence.clone();
+                
+                editor.current_sequence_data = Some(sequence_cloned.clone());
+                editor.update_motion_paths(&sequence_cloned);
+
+                println!("Text item added to editor successfully: {}", text_config.id);
+            }
+            Command::AddImage { file_path } => {
+                println!("Processing add image command from channel with file: {}", file_path);
+                require_sequence(editor, &current_sequence_id.get())?;
+                let gpu = require_gpu(gpu)?;
+                let random_coords = get_random_coords(window_size);
+                let new_id = Uuid::new_v4();
+                
+                // Extract filename for a better name
+                let filename = std::path::Path::new(&file_path)
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("Image");
+
+                let image_config = StImageConfig {
+                    id: new_id.to_string(),
+                    name: filename.to_string(),
+                    path: file_path.clone(),
+                    dimensions: (150, 150),
+                    position: Point {
+                        x: random_coords.0 as f32,
+                        y: random_coords.1 as f32,
+                    },
+                    layer: 2,
+                };
+
+                let window_size = WindowSize {
+                    width: window_size.width,
+                    height: window_size.height,
+                };
+
+                editor.add_image_item(
+                    &window_size,
+                    gpu.device,
+                    gpu.queue,
+                    image_config.clone(),
+                    &Path::new(&file_path.clone()),
+                    new_id,
+                    current_sequence_id.get().clone(),
+                );
+
+                editor_state.add_saved_image_item(
+                    editor.saved_state.as_mut().ok_or(NO_PROJECT_OPEN)?,
+                    current_sequence_id.get().clone(),
+                    SavedStImageConfig {
+                        id: image_config.id.clone(),
+                        name: image_config.name.clone(),
+                        path: file_path,
+                        dimensions: image_config.dimensions,
+                        position: SavedPoint {
+                            x: image_config.position.x as i32,
+                            y: image_config.position.y as i32,
+                        },
+                        layer: image_config.layer,
+                    },
+                );
+
+                let saved_state = editor
+                    .saved_state
+                    .as_ref()
+                    .ok_or(NO_PROJECT_OPEN)?;
+                let updated_sequence = saved_state
+                    .sequences
+                    .iter()
+                    .find(|s| s.id == current_sequence_id.get().clone())
+                    .ok_or("The current sequence no longer exists")?;
+                
+                let sequence_cloned = updated_sequence.clone();
+                
+                editor.current_sequence_data = Some(sequence_cloned.clone());
+                editor.update_motion_paths(&sequence_cloned);
+
+                println!("Image item added to editor successfully: {}", image_config.id);
+            }
+            Command::AddVideo { file_path } => {
+                println!("Processing add video command from channel with file: {}", file_path);
+                require_sequence(editor, &current_sequence_id.get())?;
+                let gpu = require_gpu(gpu)?;
+                let random_coords = get_random_coords(window_size);
+                let new_id = Uuid::new_v4();
+                
+                let path = std::path::Path::new(&file_path);
+                // Extract filename for a better name
+                let filename = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("Video");
+
+                let parent = path.parent().unwrap_or(Path::new(""));
+
+                let mouse_positions_path = parent.join("mousePositions.json");
+                let source_data_path = parent.join("sourceData.json");
+
+                let video_config = StVideoConfig {
+                    id: new_id.to_string(),
+                    name: filename.to_string(),
+                    path: file_path.clone(),
+                    dimensions: (300, 200),
+                    position: Point {
+                        x: random_coords.0 as f32,
+                        y: random_coords.1 as f32,
+                    },
+                    layer: 2,
+                    mouse_path: Some(mouse_positions_path.clone().display().to_string()),
+                };
+
+                let window_size = WindowSize {
+                    width: window_size.width,
+                    height: window_size.height,
+                };
+
+                let mut saved_mouse_path = None;
+                let mut stored_mouse_positions = None;
+                // if let Some(mouse_path) = &mouse_positions_path {
+                    if let Ok(positions) = fs::read_to_string(mouse_positions_path.clone()) {
+                        if let Ok(mouse_positions) = serde_json::from_str::<Vec<MousePosition>>(&positions) {
+                            saved_mouse_path = Some(mouse_positions_path.display().to_string());
+                            stored_mouse_positions = Some(mouse_positions);
+                        }
+                    }
+                // }
+
+                let mut stored_source_data = None;
+                // if let Some(source_path) = &source_data_path {
+                    if let Ok(source_data) = fs::read_to_string(source_data_path) {
+                        if let Ok(data) = serde_json::from_str::<SourceData>(&source_data) {
+                            stored_source_data = Some(data);
+                        }
+                    }
+                // }
+
+                editor.add_video_item(
+                    &window_size,
+                    gpu.device,
+                    gpu.queue,
+                    video_config.clone(),
+                    &Path::new(&file_path.clone()),
+                    new_id,
+                    current_sequence_id.get().clone(),
+                    stored_mouse_positions, // stored_mouse_positions
+                    stored_source_data, // stored_source_data
+                );
+
+                let source_duration_ms = editor
+                    .video_items
+                    .last()
+                    .ok_or("The video couldn't be added")?
+                    .source_duration_ms
+                    .clone();
+
+                editor_state.add_saved_video_item(
+                    editor.saved_state.as_mut().ok_or(NO_PROJECT_OPEN)?,
+                    current_sequence_id.get().clone(),
+                    SavedStVideoConfig {
+                        id: video_config.id.clone(),
+                        name: video_config.name.clone(),
+                        path: file_path,
+                        dimensions: video_config.dimensions,
+                        position: SavedPoint {
+                            x: video_config.position.x as i32,
+                            y: video_config.position.y as i32,
+                        },
+                        layer: video_config.layer,
+                        mouse_path: Some(mouse_positions_path.clone().display().to_string()),
+                    },
+                    source_duration_ms
+                );
+
+                let saved_state = editor
+                    .saved_state
+                    .as_ref()
+                    .ok_or(NO_PROJECT_OPEN)?;
+                let updated_sequence = saved_state
+                    .sequences
+                    .iter()
+                    .find(|s| s.id == current_sequence_id.get().clone())
+                    .ok_or("The current sequence no longer exists")?;
+                
+                let sequence_cloned = updated_sequence.clone();
+                
+                editor.current_sequence_data = Some(sequence_cloned.clone());
+                editor.update_motion_paths(&sequence_cloned);
+
+                println!("Video item added to editor successfully: {}", video_config.id);
+            }
+            Command::SubmitMotionForm { description, position, scale, opacity, rotation, delay, duration, merge_mode, variants } => {
+                println!("Processing motion form submission from channel");
+
+                let sequence_id = current_sequence_data_id(editor)?;
+
+                // Reset canvas hidden state
+                // let mut editor_lock = editor_for_render.lock().unwrap();
+                // editor.canvas_hidden = false;
+                // drop(editor_lock);
+
+                let prompt = MotionPrompt { description, position, scale, opacity, rotation, delay, duration };
+
+                // Checked again here since commands can also come from automation
+                let duration = parse_duration(&prompt.duration);
+                let delay = parse_delay(&prompt.delay);
+                motion_duration_error.set(duration.as_ref().err().cloned().unwrap_or_default());
+                motion_delay_error.set(delay.as_ref().err().cloned().unwrap_or_default());
+                let timing = MotionTiming {
+                    duration: duration?,
+                    delay: delay?,
+                };
+
+                // A chain of arrows wins over the last arrow drawn
+                self.extend_chain = false;
+                motion_form_signals.waypoints.set(String::new());
+                let chain = self
+                    .arrow_chain
+                    .take()
+                    .unwrap_or_else(|| ArrowChain::from_last_arrow(editor));
+
+                // Prepare API data
+                let mut api_data = motion_api_data(&prompt, timing, &chain.waypoints);
+                api_data["object_dimensions"] = chain.object_dimensions.clone();
+
+                let ArrowChain { object_id: polygon_id, object_type, waypoints, object_dimensions } = chain;
+
+                println!("api_data {:?} {:?} {:?}", api_data, polygon_id, object_dimensions);
+
+                let request = MotionRequest {
+                    sequence_id,
+                    prompt,
+                    timing,
+                    merge_mode,
+                    variants: variants.clamp(1, MAX_VARIANTS),
+                    api_data,
+                    waypoints,
+                    origin: current_position(editor, &polygon_id, &object_type),
+                    polygon_id,
+                    object_type,
+                };
+
+                remember_motion_prompt(&selected_project.project_id, &request);
+                motion_form_signals.object_id.set(request.polygon_id.clone());
+
+                start_motion_generation(
+                    request,
+                    cloud_motion_generation,
+                    &auth_state.entitlements(),
+                    self.generation.clone(),
+                    auth_session,
+                    api_response_tx,
+                    display_motion_form,
+                    motion_generation_signals,
+                );
+            }
+            Command::MotionArrowPlaced => {
+                let arrow = ArrowChain::from_last_arrow(editor);
+
+                match self.arrow_chain.as_mut() {
+                    // The new arrow continues from wherever the previous one ended
+                    Some(chain) if self.extend_chain && chain.object_id == arrow.object_id => {
+                        chain.waypoints.extend(arrow.waypoints.into_iter().skip(1));
+                    }
+                    _ => {
+                        let object_id = arrow.object_id.clone();
+                        self.arrow_chain = Some(arrow);
+                        let _ = command_tx.send(Command::LoadMotionPrompt { object_id });
+                    }
+                }
+                self.extend_chain = false;
+
+                let label = self.arrow_chain.as_ref().map(ArrowChain::label).unwrap_or_default();
+                println!("Motion arrow placed, {}", label);
+                motion_form_signals.waypoints.set(label);
+                display_motion_form.set(true);
+            }
+            Command::AddMotionWaypoint => {
+                if self.arrow_chain.is_none() {
+                    return Err("Draw a motion arrow before adding waypoints".to_string());
+                }
+
+                // The form comes back once the next arrow is drawn
+                self.extend_chain = true;
+                editor.motion_mode = true;
+                display_motion_form.set(false);
+                println!("Motion mode enabled - the next arrow continues the current path");
+            }
+            Command::CancelMotionGeneration => {
+                println!("Cancelling motion generation");
+
+                // Requests already started keep the old job and see it cancelled
+                self.generation.cancel();
+                self.generation = GenerationJob::default();
+            }
+            Command::CloseMotionGenerationPanel => motion_generation_signals.close(),
+            Command::LoadMotionPrompt { object_id } => {
+                // Without a saved prompt the form keeps whatever was typed last
+                match MotionPrompts::load(&selected_project.project_id).get(&object_id) {
+                    Some(saved) => {
+                        println!("Loading the saved motion prompt of {}", object_id);
+
+                        motion_form_signals.fill(&saved.prompt);
+                        motion_form_signals.object_id.set(object_id);
+                        motion_duration_error.set(String::new());
+                        motion_delay_error.set(String::new());
+                    }
+                    None => motion_form_signals.object_id.set(String::new()),
+                }
+            }
+            Command::RerunMotionPrompt => {
+                let object_id = motion_form_signals.object_id.get();
+                let saved = MotionPrompts::load(&selected_project.project_id)
+                    .get(&object_id)
+                    .cloned()
+                    .ok_or("Select an object whose motion was generated from the motion form first")?;
+
+                println!("Re-running the saved motion prompt of {}", object_id);
+
+                let timing = MotionTiming {
+                    duration: parse_duration(&saved.prompt.duration)?,
+                    delay: parse_delay(&saved.prompt.delay)?,
+                };
+                let waypoints = saved.arrow.map(|arrow| arrow.to_points()).unwrap_or_default();
+
+                let request = MotionRequest {
+                    sequence_id: current_sequence_data_id(editor)?,
+                    api_data: motion_api_data(&saved.prompt, timing, &waypoints),
+                    prompt: saved.prompt,
+                    timing,
+                    merge_mode: saved.merge_mode,
+                    variants: 1,
+                    waypoints,
+                    origin: current_position(editor, &object_id, &saved.object_type),
+                    polygon_id: object_id,
+                    object_type: saved.object_type,
+                };
+
+                remember_motion_prompt(&selected_project.project_id, &request);
+
+                start_motion_generation(
+                    request,
+                    cloud_motion_generation,
+                    &auth_state.entitlements(),
+                    self.generation.clone(),
+                    auth_session,
+                    api_response_tx,
+                    display_motion_form,
+                    motion_generation_signals,
+                );
+            }
+            Command::BrowseMotionCandidates => {
+                let object_id = editor.last_motion_arrow_object_id.to_string();
+                let candidates = MotionCandidates::load(&selected_project.project_id);
+                let count = candidates.for_object(&object_id).len();
+
+                if count == 0 {
+                    return Err("This object has no saved motion variants".to_string());
+                }
+
+                signals.motion_candidate_signals.object_id.set(object_id);
+                signals.motion_candidate_signals.visible.set(true);
+                show_motion_candidate(editor, editor_state, &signals.motion_candidate_signals, &candidates, count - 1)?;
+            }
+            Command::ShowMotionCandidate { step } => {
+                let candidates = MotionCandidates::load(&selected_project.project_id);
+                let panel = &signals.motion_candidate_signals;
+                let object_id = panel.object_id.get();
+                let count = candidates.for_object(&object_id).len();
+
+                if count == 0 {
+                    return Err("This object has no saved motion variants".to_string());
+                }
+
+                // A previewed variant dropped as the oldest was before the first one kept
+                let index = match candidates.position(&object_id, &panel.candidate_id.get()) {
+                    Some(index) => (index as i64 + step as i64).rem_euclid(count as i64) as usize,
+                    None if step > 0 => 0,
+                    None => count - 1,
+                };
+                show_motion_candidate(editor, editor_state, &signals.motion_candidate_signals, &candidates, index)?;
+            }
+            Command::ApplyMotionCandidate => {
+                let object_id = signals.motion_candidate_signals.object_id.get();
+                let candidate_id = signals.motion_candidate_signals.candidate_id.get();
+
+                let mut candidates = MotionCandidates::load(&selected_project.project_id);
+                let candidate = candidates
+                    .get(&object_id, &candidate_id)
+                    .ok_or("This variant was dropped to make room for newer ones")?
+                    .to_generated_motion();
+
+                apply_generated_motion(editor, editor_state, candidate)?;
+
+                candidates.mark_applied(&object_id, &candidate_id);
+                if let Err(e) = candidates.save(&selected_project.project_id) {
+                    println!("Failed to save motion candidates: {}", e);
+                }
+
+                signals.motion_candidate_signals.visible.set(false);
+            }
+            Command::DiscardMotionCandidates => {
+                signals.motion_candidate_signals.visible.set(false);
+                end_motion_preview(editor);
+            }
+            Command::RegenerateAllMotion => {
+                if is_regenerating.get() {
+                    return Err("Regenerate All is already running".to_string());
+                }
+
+                let sequence = editor
+                    .current_sequence_data
+                    .clone()
+                    .ok_or("Select a sequence before regenerating its motion")?;
+
+                // Objects keep their timing and use their saved prompt and arrow when there is one
+                let prompts = MotionPrompts::load(&selected_project.project_id);
+                let requests: Vec<MotionRequest> = sequence
+                    .polygon_motion_paths
+                    .iter()
+                    .filter_map(|path| {
+                        let saved = prompts.get(&path.polygon_id);
+                        let prompt = saved.map(|saved| saved.prompt.clone()).unwrap_or_default();
+                        let waypoints = match saved.and_then(|saved| saved.arrow.as_ref()) {
+                            Some(arrow) => arrow.to_points(),
+                            None => arrow_from_motion_path(path)?,
+                        };
+                        let timing = MotionTiming {
+                            duration: Some(path.duration),
+                            delay: Some(Duration::from_millis(path.start_time_ms.max(0) as u64)),
+                        };
+
+                        Some(MotionRequest {
+                            sequence_id: sequence.id.clone(),
+                            api_data: motion_api_data(&prompt, timing, &waypoints),
+                            prompt,
+                            timing,
+                            merge_mode: MergeMode::ReplaceAll,
+                            variants: 1,
+                            waypoints,
+                            origin: object_position(&sequence, &path.polygon_id, &path.object_type),
+                            polygon_id: path.polygon_id.clone(),
+                            object_type: path.object_type.clone(),
+                        })
+                    })
+                    .collect();
+
+                if requests.is_empty() {
+                    return Err("No objects in this sequence have a motion arrow to regenerate".to_string());
+                }
+
+                let total = requests.len();
+                println!("Regenerating motion for {} objects", total);
+
+                is_regenerating.set(true);
+                regenerate_all_label.set(format!("Regenerating 0/{}", total));
+
+                let entitlements = auth_state.entitlements();
+
+                // Past the plan's quota the whole batch is generated locally rather than partly in the cloud
+                let over_quota = cloud_motion_generation
+                    .then(|| {
+                        let used = GenerationUsage::load().used(&entitlements.period);
+                        entitlements.check_generations(used, total as u32).err()
+                    })
+                    .flatten();
+
+                if !cloud_motion_generation || over_quota.is_some() {
+                    if let Some(e) = &over_quota {
+                        println!("{}, regenerating motion locally", e);
+                        motion_generation_signals.fail("Regenerated with the built-in generator instead", e);
+                    }
+
+                    let motions = requests
+                        .iter()
+                        .map(|request| finish_motion(request, local_motion(request, 0)))
+                        .collect();
+
+                    if let Err(e) = api_response_tx.send(MotionUpdate::Batch { sequence_id: sequence.id.clone(), motions }) {
+                        is_regenerating.set(false);
+                        regenerate_all_label.set(REGENERATE_ALL_LABEL.to_string());
+                        return Err(format!("Failed to send regenerated motion through channel: {}", e));
+                    }
+
+                    return Ok(());
+                }
+
+                motion_generation_signals.start(&format!("Regenerating motion for {} objects...", total));
+
+                let response_sender = api_response_tx.clone();
+                let is_regenerating = is_regenerating.clone();
+                let regenerate_all_label = regenerate_all_label.clone();
+                let generation_signals = motion_generation_signals.clone();
+                let job = self.generation.clone();
+                let auth_session = auth_session.clone();
+                let period = entitlements.period.clone();
+                let sequence_id = sequence.id.clone();
+
+                tokio::spawn(async move {
+                    let client = ApiClient::from_settings();
+                    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_GENERATIONS));
+
+                    let mut tasks = tokio::task::JoinSet::new();
+                    for (index, request) in requests.into_iter().enumerate() {
+                        let client = client.clone();
+                        let permits = permits.clone();
+                        let job = job.clone();
+                        let auth_session = auth_session.clone();
+
+                        tasks.spawn(async move {
+                            let _permit = permits.acquire_owned().await;
+                            let result = job.run(|| request_cloud_motion(&client, &auth_session, &request, 0)).await;
+                            (index, request, result)
+                        });
+                    }
+
+                    let mut motions = Vec::with_capacity(total);
+                    let mut failures = Vec::new();
+                    while let Some(joined) = tasks.join_next().await {
+                        match joined {
+                            Ok((index, request, Ok(animation_data))) => {
+                                motions.push((index, finish_motion(&request, animation_data)));
+                            }
+                            Ok((_, request, Err(e))) => failures.push(format!("{}: {}", request.polygon_id, e)),
+                            Err(e) => failures.push(format!("generation task failed: {}", e)),
+                        }
+
+                        let progress = format!("Regenerating {}/{}", motions.len() + failures.len(), total);
+                        generation_signals.status.set(progress.clone());
+                        regenerate_all_label.set(progress);
+                    }
+
+                    // The backend did the work even if the batch isn't applied
+                    if !motions.is_empty() {
+                        record_generations(&period, motions.len() as u32);
+                    }
+
+                    if job.is_cancelled() {
+                        println!("Regenerate All cancelled, nothing was changed");
+                        is_regenerating.set(false);
+                        regenerate_all_label.set(REGENERATE_ALL_LABEL.to_string());
+                        generation_signals.close();
+                        return;
+                    }
+
+                    // Leave the sequence untouched unless every object has new motion
+                    if !failures.is_empty() {
+                        println!("Regenerate All failed for {} of {} objects, nothing was changed:", failures.len(), total);
+                        for failure in &failures {
+                            println!("  {}", failure);
+                        }
+
+                        is_regenerating.set(false);
+                        regenerate_all_label.set(format!("{} ({} failed)", REGENERATE_ALL_LABEL, failures.len()));
+                        generation_signals.fail(
+                            &format!("{} of {} objects failed, nothing was changed", failures.len(), total),
+                            &failures[0],
+                        );
+                        return;
+                    }
+
+                    generation_signals.finish();
+
+                    motions.sort_by_key(|(index, _)| *index);
+                    let motions = motions.into_iter().map(|(_, motion)| motion).collect();
+
+                    if let Err(e) = response_sender.send(MotionUpdate::Batch { sequence_id, motions }) {
+                        println!("Failed to send regenerated motion through channel: {}", e);
+                        is_regenerating.set(false);
+                        regenerate_all_label.set(REGENERATE_ALL_LABEL.to_string());
+                    }
+                });
+            }
+            Command::SetCloudMotionGeneration { enabled } => {
+                println!("Setting cloud motion generation: {}", enabled);
+
+                preferences.cloud_motion_generation = enabled;
+                if let Err(e) = preferences.save() {
+                    println!("Failed to save preferences: {}", e);
+                }
+            }
+            Command::UpdateTextProperty { property_key, property_value } => {
+                println!("Processing text property update: {} = {}", property_key, property_value);
+                
+                // Convert property to ObjectProperty enum
+                let object_property = match property_key.as_str() {
+                    "font_family" => ObjectProperty::FontFamily(property_value),
+                    "font_size" => {
+                        if let Ok(size) = property_value.parse::<f32>() {
+                            ObjectProperty::FontSize(size)
+                        } else {
+                            return Err(format!("Invalid font size: {}", property_value));
+                        }
+                    },
+                    "text_content" => ObjectProperty::Text(property_value),
+                    _ => {
+                        return Err(format!("Unsupported property: {}", property_key));
+                    }
+                };
+
+                // if let Some(last_text) = editor.text_items.last() {
+                    let text_id = editor.selected_polygon_id; //  this will be currently selected text object id
+                    let window_size = WindowSize {
+                        width: window_size.width,
+                        height: window_size.height,
+                    };
+                    
+                    if let Err(e) = editor.update_text_property(
+                        text_id,
+                        object_property,
+                    ) {
+                        println!("Failed to update text property: {}", e);
+                    } else {
+                        println!("Text property updated successfully");
+                    }
+                // } else {
+                //     println!("No text items to update");
+                // }
+            }
+            other => return unsupported(&other, self.family()),
+        }
+
+        Ok(())
+    }
+}
+
+/// Requests Regenerate All keeps in flight at once
+const MAX_CONCURRENT_GENERATIONS: usize = 3;
+
+/// Everything needed to generate motion for one object, cloned into background tasks
+#[derive(Clone)]
+struct MotionRequest {
+    /// Sequence the motion is for, so it isn't merged into another one opened meanwhile
+    sequence_id: String,
+    prompt: MotionPrompt,
+    timing: MotionTiming,
+    merge_mode: MergeMode,
+    /// How many variants were requested; more than one keeps them as candidates to pick from
+    variants: usize,
+    /// Body of the generate-motion request
+    api_data: serde_json::Value,
+    /// Start of the arrow, where chained arrows meet and the end; empty without an arrow
+    waypoints: Vec<Point>,
+    /// Where the object currently sits, which local motion stays at without an arrow
+    origin: Point,
+    polygon_id: String,
+    object_type: ObjectType,
+}
+
+/// Generate motion for `request` in the background, from the backend when `cloud` is set
+/// and with the local generator otherwise; results go to the render loop
+fn start_motion_generation(
+    request: MotionRequest,
+    cloud: bool,
+    entitlements: &Entitlements,
+    job: GenerationJob,
+    auth_session: &AuthSession,
+    api_response_tx: &mpsc::Sender<MotionUpdate>,
+    display_motion_form: &Signal<bool>,
+    generation_signals: &MotionGenerationSignals,
+) {
+    let over_quota = cloud
+        .then(|| {
+            let used = GenerationUsage::load().used(&entitlements.period);
+            entitlements.check_generations(used, request.variants as u32).err()
+        })
+        .flatten();
+
+    if !cloud || over_quota.is_some() {
+        match &over_quota {
+            Some(e) => println!("{}, generating motion locally", e),
+            None => println!("Cloud motion generation is off, generating motion locally"),
+        }
+
+        display_motion_form.set(false);
+        for variant in 0..request.variants {
+            send_local_motion(&request, variant, api_response_tx);
+        }
+
+        if let Some(e) = over_quota {
+            generation_signals.fail("Used the built-in generator instead", &e);
+        }
+        return;
+    }
+
+    generation_signals.start(&match request.variants {
+        1 => "Generating motion...".to_string(),
+        variants => format!("Generating {} variants...", variants),
+    });
+
+    // Clone the response sender for the async block
+    let response_sender = api_response_tx.clone();
+
+    let display_motion_form = display_motion_form.clone();
+    let generation_signals = generation_signals.clone();
+    let auth_session = auth_session.clone();
+    let period = entitlements.period.clone();
+
+    // Spawn the async task - no editor locking here!
+    tokio::spawn(async move {
+        let client = ApiClient::from_settings();
+
+        // Variants are requested side by side and kept as they arrive
+        let mut tasks = tokio::task::JoinSet::new();
+        for variant in 0..request.variants {
+            let client = client.clone();
+            let request = request.clone();
+            let job = job.clone();
+            let auth_session = auth_session.clone();
+
+            tasks.spawn(async move {
+                let result = job.run(|| request_cloud_motion(&client, &auth_session, &request, variant)).await;
+                (variant, result)
+            });
+        }
+
+        let mut errors = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            display_motion_form.set(false);
+
+            match joined {
+                Ok((_, Ok(animation_data))) => {
+                    record_generations(&period, 1);
+
+                    // Send the response back through the channel
+                    send_motion(&request, animation_data, &response_sender);
+                }
+                Ok((_, Err(GenerationError::Cancelled))) => println!("Motion generation cancelled"),
+                Ok((_, Err(e))) if e.needs_sign_in() => {
+                    println!("{}", e);
+                    errors.push(e);
+                }
+                Ok((variant, Err(e))) => {
+                    println!("{}", e);
+                    println!("Falling back to local motion generation");
+                    send_local_motion(&request, variant, &response_sender);
+                    errors.push(e);
+                }
+                Err(e) => println!("Motion generation task failed: {}", e),
+            }
+        }
+
+        // The spinner always goes away; errors stay up until they're read
+        match errors.iter().find(|e| e.needs_sign_in()).or(errors.first()) {
+            Some(e) if e.needs_sign_in() => generation_signals.fail("Sign in again to generate motion", &e.to_string()),
+            Some(e) => generation_signals.fail("Used the built-in generator instead", &e.to_string()),
+            None if job.is_cancelled() => generation_signals.close(),
+            None => generation_signals.finish(),
+        }
+    });
+}
+
+/// Keep the prompt and arrow so the object's motion can be run again without retyping
+fn remember_motion_prompt(project_id: &str, request: &MotionRequest) {
+    let mut prompts = MotionPrompts::load(project_id);
+    prompts.remember(
+        request.polygon_id.clone(),
+        SavedMotionPrompt {
+            prompt: request.prompt.clone(),
+            arrow: MotionArrow::from_points(&request.waypoints),
+            object_type: request.object_type.clone(),
+            merge_mode: request.merge_mode,
+            updated: chrono::Utc::now(),
+        },
+    );
+
+    if let Err(e) = prompts.save(project_id) {
+        println!("Failed to save motion prompt: {}", e);
+    }
+}
+
+/// Body of the generate-motion request, without the object's dimensions
+fn motion_api_data(prompt: &MotionPrompt, timing: MotionTiming, waypoints: &[Point]) -> serde_json::Value {
+    // arrow_positions keeps describing the whole path for backends that don't read waypoints
+    let arrow_positions = match (waypoints.first(), waypoints.last()) {
+        (Some(p1), Some(p2)) if waypoints.len() >= 2 => Some((p1, p2)),
+        _ => None,
+    };
+
+    serde_json::json!({
+        "description": prompt.description,
+        "position": prompt.position,
+        "scale": prompt.scale,
+        "opacity": prompt.opacity,
+        "rotation": prompt.rotation,
+        "delay": prompt.delay,
+        "duration": prompt.duration,
+        "duration_ms": timing.duration.map(|duration| duration.as_millis() as u64),
+        "delay_ms": timing.delay.map(|delay| delay.as_millis() as u64),
+        "arrow_positions": arrow_positions.map(|(p1, p2)| serde_json::json!({"startX": p1.x, "startY": p1.y, "endX": p2.x, "endY": p2.y})),
+        "waypoints": waypoints.iter().map(|point| serde_json::json!({"x": point.x, "y": point.y})).collect::<Vec<_>>(),
+    })
+}
+
+/// The arrow an existing motion path follows, from its first to its last position
+fn arrow_from_motion_path(path: &AnimationData) -> Option<Vec<Point>> {
+    let position = path
+        .properties
+        .iter()
+        .find(|property| property.property_path == "position")?;
+
+    let mut keyframes: Vec<_> = position.keyframes.iter().collect();
+    keyframes.sort_by_key(|keyframe| keyframe.time);
+
+    let point = |value: &KeyframeValue| match value {
+        KeyframeValue::Position([x, y]) => Some(Point { x: *x as f32, y: *y as f32 }),
+        _ => None,
+    };
+
+    Some(vec![point(&keyframes.first()?.value)?, point(&keyframes.last()?.value)?])
+}
+
+/// Position of the object in the sequence open in the editor
+fn current_position(editor: &Editor, object_id: &str, object_type: &ObjectType) -> Point {
+    editor
+        .current_sequence_data
+        .as_ref()
+        .map(|sequence| object_position(sequence, object_id, object_type))
+        .unwrap_or(Point { x: 0.0, y: 0.0 })
+}
+
+/// Position of the object as saved in `sequence`, or the canvas origin if it isn't there
+fn object_position(sequence: &Sequence, object_id: &str, object_type: &ObjectType) -> Point {
+    let position = match object_type {
+        ObjectType::Polygon => sequence.active_polygons.iter().find(|p| p.id == object_id).map(|p| &p.position),
+        ObjectType::TextItem => sequence.active_text_items.iter().find(|t| t.id == object_id).map(|t| &t.position),
+        ObjectType::ImageItem => sequence.active_image_items.iter().find(|i| i.id == object_id).map(|i| &i.position),
+        ObjectType::VideoItem => sequence.active_video_items.iter().find(|v| v.id == object_id).map(|v| &v.position),
+    };
+
+    position
+        .map(|position| Point { x: position.x as f32, y: position.y as f32 })
+        .unwrap_or(Point { x: 0.0, y: 0.0 })
+}
+
+/// Ask the backend for one variant, describing what went wrong if it can't be used
+async fn request_cloud_motion(
+    client: &ApiClient,
+    auth_session: &AuthSession,
+    request: &MotionRequest,
+    variant: usize,
+) -> Result<AnimationData, GenerationError> {
+    let mut api_data = request.api_data.clone();
+    if request.variants > 1 {
+        api_data["variant"] = variant.into();
+        api_data["variants"] = request.variants.into();
+    }
+
+    // A 401 refreshes the token and tries once more
+    let api_data = &api_data;
+    let response = auth_session
+        .authorized(|token| async move {
+            let response = client.generate_motion(api_data, &token).await?;
+
+            let status = response.status();
+            if status.is_success() {
+                Ok(response)
+            } else {
+                let body = response.text().await.unwrap_or_default();
+                Err(GenerationError::from_status(status, body))
+            }
+        })
+        .await?;
+
+    // Get the raw response text first for logging
+    let raw_response = response.text().await?;
+    println!("Raw API response received:");
+    println!("{}", raw_response);
+
+    // First try to parse as API format, then convert
+    let api_animation = serde_json::from_str::<ApiAnimationData>(&raw_response).map_err(|parse_error| {
+        println!("Raw response that failed to parse: {}", raw_response);
+        log_expected_formats(&raw_response);
+        GenerationError::parse(format!("not valid ApiAnimationData: {}", parse_error), &raw_response)
+    })?;
+    println!("Successfully parsed API response format: {:?}", api_animation);
+
+    // Convert to the expected format
+    let mut animation_data = api_animation
+        .convert(request.polygon_id.clone(), request.object_type.clone(), client.conversion_mode())
+        .map_err(|e| GenerationError::parse(format!("rejected in strict mode: {}", e), &raw_response))?;
+    request.timing.apply(&mut animation_data);
+    println!("Converted to AnimationData format: {:?}", animation_data);
+
+    Ok(animation_data)
+}
+
+/// Generate the motion locally, used when cloud generation is off or fails
+fn send_local_motion(request: &MotionRequest, variant: usize, response_sender: &mpsc::Sender<MotionUpdate>) {
+    send_motion(request, local_motion(request, variant), response_sender);
+}
+
+fn local_motion(request: &MotionRequest, variant: usize) -> AnimationData {
+    let mut animation_data = generate_motion_variant(&request.prompt, &request.waypoints, request.origin, variant)
+        .to_animation_data(request.polygon_id.clone(), request.object_type.clone());
+    request.timing.apply(&mut animation_data);
+    println!("Generated motion locally: {:?}", animation_data);
+
+    animation_data
+}
+
+/// Hand a generated animation to the render loop, which merges it into the sequence
+/// or keeps it as a candidate when several variants were requested
+fn send_motion(request: &MotionRequest, animation_data: AnimationData, response_sender: &mpsc::Sender<MotionUpdate>) {
+    let update = MotionUpdate::Generated {
+        sequence_id: request.sequence_id.clone(),
+        motion: finish_motion(request, animation_data),
+    };
+    if let Err(e) = response_sender.send(update) {
+        println!("Failed to send generated motion through channel: {}", e);
+    }
+}
+
+/// Id of the sequence open in the editor, which generated motion is merged into
+fn current_sequence_data_id(editor: &Editor) -> Result<String, String> {
+    editor
+        .current_sequence_data
+        .as_ref()
+        .map(|sequence| sequence.id.clone())
+        .ok_or_else(|| "Select a sequence first".to_string())
+}
+
+/// When replacing only described properties, the ones left blank in the form are dropped
+/// so the object's current motion for them is kept
+fn finish_motion(request: &MotionRequest, mut animation_data: AnimationData) -> GeneratedMotion {
+    if request.merge_mode == MergeMode::ReplacePresent {
+        let described = request.prompt.described_properties();
+
+        if !described.is_empty() {
+            animation_data
+                .properties
+                .retain(|property| described.contains(&property.property_path.as_str()));
+        }
+    }
+
+    if request.variants > 1 {
+        GeneratedMotion::candidate(animation_data, request.merge_mode)
+    } else {
+        GeneratedMotion::new(animation_data, request.merge_mode)
+    }
+}
+
+/// Keep a generated variant in the project's candidate history. The first variant for an
+/// object opens the variants panel and is previewed; later ones only update the count.
+pub(crate) fn receive_motion_candidate(
+    editor: &mut Editor,
+    editor_state: &mut EditorState,
+    signals: &AppSignals,
+    project_id: &str,
+    motion: GeneratedMotion,
+) {
+    let object_id = motion.animation.polygon_id.clone();
+
+    let mut candidates = MotionCandidates::load(project_id);
+    let candidate_id = candidates.add(MotionCandidate::new(motion));
+    if let Err(e) = candidates.save(project_id) {
+        println!("Failed to save motion candidates: {}", e);
+    }
+
+    let panel = &signals.motion_candidate_signals;
+    let browsing_object = panel.visible.get() && panel.object_id.get() == object_id;
+    if !browsing_object {
+        panel.object_id.set(object_id.clone());
+        panel.visible.set(true);
+
+        let index = candidates.position(&object_id, &candidate_id).unwrap_or_default();
+        if let Err(e) = show_motion_candidate(editor, editor_state, panel, &candidates, index) {
+            println!("Failed to preview motion candidate: {}", e);
+        }
+    } else {
+        // The previewed variant keeps its place even if adding this one dropped the oldest
+        panel
+            .label
+            .set(candidate_label(candidates.for_object(&object_id), &panel.candidate_id.get()));
+    }
+}
+
+/// Preview the browsed object's candidate at `index` and update the variants panel
+fn show_motion_candidate(
+    editor: &mut Editor,
+    editor_state: &mut EditorState,
+    panel: &MotionCandidateSignals,
+    candidates: &MotionCandidates,
+    index: usize,
+) -> CommandResult {
+    let object_candidates = candidates.for_object(&panel.object_id.get());
+    let candidate = object_candidates
+        .get(index)
+        .ok_or_else(|| format!("There is no variant {}", index + 1))?;
+
+    panel.candidate_id.set(candidate.id.clone());
+    panel.label.set(candidate_label(object_candidates, &candidate.id));
+
+    preview_generated_motion(editor, editor_state, candidate.to_generated_motion())
+}
+
+/// Log the formats the motion endpoint is expected to return, to help debug parse failures
+fn log_expected_formats(raw_response: &str) {
+    // Try direct parsing as fallback and show expected formats
+    println!("\nTrying direct AnimationData parsing for comparison...");
+    match serde_json::from_str::<AnimationData>(raw_response) {
+        Ok(_) => println!("Direct AnimationData parsing succeeded (unexpected!)"),
+        Err(direct_error) => {
+            println!("Direct AnimationData parsing also failed: {}", direct_error);
+
+            // Log expected structures for comparison
+            println!("\n=== Expected API Format Example ===");
+            let example_api = ApiAnimationData {
+                id: "example_id".to_string(),
+                duration: 3000,
+                properties: vec![
+                    ApiAnimationProperty {
+                        name: "Position".to_string(),
+                        keyframes: vec![
+                            ApiKeyframe {
+                                time: 0,
+                                value: ApiKeyframeValue::Position { Position: ApiPosition { x: 100, y: 100 } },
+                                easing: "Linear".to_string(),
+                                ..ApiKeyframe::default()
+                            }
+                        ],
+                    }
+                ],
+            };
+            if let Ok(example_json) = serde_json::to_string_pretty(&example_api) {
+                println!("{}", example_json);
+            }
+
+            println!("\n=== Expected AnimationData Format Example ===");
+            let example_animation_data = AnimationData::default();
+            if let Ok(example_json) = serde_json::to_string_pretty(&example_animation_data) {
+                println!("{}", example_json);
+            }
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use super::{Command, CommandResult};
+
+/// Waits longer than this are logged as they happen
+const SLOW_WAIT: Duration = Duration::from_millis(100);
+
+/// A summary of the waits is logged every this many commands
+const STATS_LOG_INTERVAL: u64 = 200;
+
+/// Receives the handler's result, for callers outside the UI such as the automation endpoint
+pub type CommandReply = mpsc::Sender<CommandResult>;
+
+struct PendingCommand {
+    command: Command,
+    reply: Option<CommandReply>,
+    received_at: Instant,
+    /// Frames on which the editor was locked and the command had to wait
+    deferred_frames: u32,
+}
+
+/// How long commands waited between leaving the UI channel and being handled
+#[derive(Debug, Clone, Default)]
+struct CommandWaitStats {
+    processed: u64,
+    /// Commands that missed at least one frame because the editor was locked
+    deferred: u64,
+    total_wait: Duration,
+    max_wait: Duration,
+}
+
+impl CommandWaitStats {
+    fn average_wait(&self) -> Duration {
+        if self.processed == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(self.total_wait.as_secs_f64() / self.processed as f64)
+        }
+    }
+
+    fn log_summary(&self) {
+        println!(
+            "{} commands handled, {} waited for the editor lock; average wait {} ms, longest {} ms",
+            self.processed,
+            self.deferred,
+            self.average_wait().as_millis(),
+            self.max_wait.as_millis()
+        );
+    }
+}
+
+/// Commands received from the UI but not yet handled. They are kept in order and
+/// retried on the next frame when the editor or its state can't be locked.
+#[derive(Default)]
+pub struct PendingCommands {
+    queue: VecDeque<PendingCommand>,
+    stats: CommandWaitStats,
+}
+
+impl PendingCommands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, command: Command) {
+        self.push_pending(command, None);
+    }
+
+    /// Queue a command whose result is sent to `reply` once it has been handled
+    pub fn push_with_reply(&mut self, command: Command, reply: CommandReply) {
+        self.push_pending(command, Some(reply));
+    }
+
+    fn push_pending(&mut self, command: Command, reply: Option<CommandReply>) {
+        self.queue.push_back(PendingCommand {
+            command,
+            reply,
+            received_at: Instant::now(),
+            deferred_frames: 0,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Record that this frame couldn't process the queue
+    pub fn defer(&mut self) {
+        self.queue
+            .iter_mut()
+            .for_each(|pending| pending.deferred_frames += 1);
+    }
+
+    /// Take the oldest command for processing, recording how long it waited
+    pub fn pop(&mut self) -> Option<(Command, Option<CommandReply>)> {
+        let pending = self.queue.pop_front()?;
+        let waited = pending.received_at.elapsed();
+
+        self.stats.processed += 1;
+        self.stats.total_wait += waited;
+        self.stats.max_wait = self.stats.max_wait.max(waited);
+
+        if pending.deferred_frames > 0 {
+            self.stats.deferred += 1;
+        }
+
+        if waited >= SLOW_WAIT {
+            // Only the family is logged, commands can carry credentials
+            println!(
+                "{:?} command waited {} ms ({} frames) for the editor lock",
+                pending.command.family(),
+                waited.as_millis(),
+                pending.deferred_frames
+            );
+        }
+
+        if self.stats.processed % STATS_LOG_INTERVAL == 0 {
+            self.stats.log_summary();
+        }
+
+        Some((pending.command, pending.reply))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(project_id: &str) -> Command {
+        Command::SelectProject {
+            project_id: project_id.to_string(),
+        }
+    }
+
+    fn selected_id(command: &Command) -> &str {
+        match command {
+            Command::SelectProject { project_id } => project_id,
+            other => panic!("expected SelectProject, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn commands_come_out_in_the_order_they_arrived() {
+        let mut pending = PendingCommands::new();
+        for project_id in ["a", "b", "c"] {
+            pending.push(select(project_id));
+        }
+        pending.defer();
+
+        let order: Vec<String> = std::iter::from_fn(|| pending.pop())
+            .map(|(command, _)| selected_id(&command).to_string())
+            .collect();
+        assert_eq!(order, vec!["a", "b", "c"]);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn replies_stay_with_their_command() {
+        let mut pending = PendingCommands::new();
+        let (a_tx, a_rx) = mpsc::channel();
+        let (c_tx, c_rx) = mpsc::channel();
+        pending.push_with_reply(select("a"), a_tx);
+        pending.push(select("b"));
+        pending.push_with_reply(select("c"), c_tx);
+
+        while let Some((command, reply)) = pending.pop() {
+            let project_id = selected_id(&command).to_string();
+            match reply {
+                Some(reply) => reply.send(Err(project_id)).unwrap(),
+                None => assert_eq!(project_id, "b"),
+            }
+        }
+
+        assert_eq!(a_rx.try_recv().unwrap(), Err("a".to_string()));
+        assert_eq!(c_rx.try_recv().unwrap(), Err("c".to_string()));
+    }
+
+    #[test]
+    fn deferred_frames_are_counted() {
+        let mut pending = PendingCommands::new();
+        pending.push(select("a"));
+        pending.defer();
+        pending.defer();
+        pending.push(select("b"));
+
+        let frames: Vec<u32> = pending.queue.iter().map(|p| p.deferred_frames).collect();
+        assert_eq!(frames, vec![2, 0]);
+
+        pending.pop();
+        pending.pop();
+        assert_eq!(pending.stats.processed, 2);
+        assert_eq!(pending.stats.deferred, 1);
+    }
+
+    #[test]
+    fn average_wait_divides_by_every_command() {
+        let stats = CommandWaitStats {
+            processed: 4,
+            total_wait: Duration::from_secs(3),
+            ..CommandWaitStats::default()
+        };
+        assert_eq!(stats.average_wait(), Duration::from_millis(750));
+        assert_eq!(CommandWaitStats::default().average_wait(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,273 @@
+use stunts_engine::animations::{BackgroundFill, Sequence};
+use stunts_engine::editor::{rgb_to_wgpu, wgpu_to_human, Editor};
+use uuid::Uuid;
+
+use super::{
+    sequence_options, unsupported, AppSignals, Command, CommandContext, CommandFamily,
+    CommandHandler, CommandResult,
+};
+use crate::export::ExportSettings;
+use crate::helpers;
+use crate::{create_local_project, load_local_projects};
+
+pub struct ProjectHandler;
+
+impl CommandHandler for ProjectHandler {
+    fn family(&self) -> CommandFamily {
+        CommandFamily::Projects
+    }
+
+    fn handle(&mut self, command: Command, ctx: &mut CommandContext<'_>) -> CommandResult {
+        let CommandContext { editor, signals, .. } = ctx;
+        let AppSignals {
+            auth_state,
+            plan_notice,
+            local_projects_signal,
+            selected_project_signal,
+            show_project_list,
+            show_project_creation,
+            show_editor,
+            project_name_text,
+            current_sequence_id,
+            available_sequences,
+            export_settings_signals,
+            ..
+        } = &**signals;
+
+        match command {
+            Command::LoadProjects => {
+                println!("Processing load projects command");
+                
+                match load_local_projects() {
+                    Ok(projects) => {
+                        local_projects_signal.set(projects);
+                        println!("Local projects reloaded successfully");
+                    }
+                    Err(e) => {
+                        println!("Failed to reload local projects: {}", e);
+                    }
+                }
+            }
+            Command::SelectProject { project_id } => {
+                println!("Processing select project command: {}", project_id);
+                
+                let current_projects = local_projects_signal.get();
+                if let Some(project) = current_projects.iter().find(|p| p.project_id == project_id) {
+                    selected_project_signal.set(Some(project.clone()));
+                    
+                    // Load the project state
+                    match stunts_engine::saved_state::load_project_state(project_id.clone()) {
+                        Ok(saved_state) => {
+                            editor.saved_state = Some(saved_state.clone());
+                            editor.project_selected = Some(uuid::Uuid::parse_str(&project_id).unwrap());
+
+                            export_settings_signals.load(&ExportSettings::load(&project_id));
+                            editor.current_view = "scene".to_string();
+
+                            saved_state.sequences.iter().enumerate().for_each(|(i, s)| {
+                                editor.restore_sequence_objects(
+                                    &s,
+                                    true,
+                                );
+                            });
+                            
+                            // Set the current sequence data
+                            if let Some(sequence) = saved_state.sequences.first() {
+                                current_sequence_id.set(sequence.id.clone());
+                                editor.current_sequence_data = Some(sequence.clone());
+
+                                show_sequence(editor, sequence);
+                            }
+                            
+                            // Hide project selection UI and show main canvas
+                            show_project_list.set(false);
+                            show_project_creation.set(false);
+                            show_editor.set(true);
+                            editor.canvas_hidden = false;
+                            
+                            // Load sequences into dropdown
+                            available_sequences.set(sequence_options(&saved_state.sequences));
+                            
+                            println!("Project selected and loaded: {}", project.project_name);
+                        }
+                        Err(e) => {
+                            println!("Failed to load project state: {}", e);
+                        }
+                    }
+                }
+            }
+            Command::CreateProject { name } => {
+                println!("Processing create project command: {}", name);
+                
+                // Free plans are limited in how many projects they keep
+                if let Err(e) = auth_state.get().can_create_projects(local_projects_signal.get().len()) {
+                    plan_notice.set(e.clone());
+                    return Err(e);
+                }
+
+                match create_local_project(&name) {
+                    Ok(new_project) => {
+                        // Update local projects list
+                        let mut current_projects = local_projects_signal.get();
+                        current_projects.push(new_project.clone());
+                        local_projects_signal.set(current_projects);
+                        
+                        // Set as selected project
+                        selected_project_signal.set(Some(new_project.clone()));
+                        
+                        // Load the newly created project state
+                        match stunts_engine::saved_state::load_project_state(new_project.project_id.clone()) {
+                            Ok(mut saved_state) => {
+                                // Create first sequence automatically
+                                let sequence_id = Uuid::new_v4();
+                                let first_sequence = Sequence {
+                                    id: sequence_id.to_string(),
+                                    name: "Sequence 1".to_string(),
+                                    background_fill: Some(BackgroundFill::Color([
+                                        wgpu_to_human(0.8) as i32,
+                                        wgpu_to_human(0.8) as i32,
+                                        wgpu_to_human(0.8) as i32,
+                                        255,
+                                    ])),
+                                    duration_ms: 20000,
+                                    active_polygons: Vec::new(),
+                                    polygon_motion_paths: Vec::new(),
+                                    active_text_items: Vec::new(),
+                                    active_image_items: Vec::new(),
+                                    active_video_items: Vec::new(),
+                                };
+
+                                current_sequence_id.set(sequence_id.clone().to_string());
+                                
+                                saved_state.sequences = vec![first_sequence];
+                                saved_state.timeline_state = helpers::projects::arrange_sequences_in_series(&mut saved_state.sequences);
+                                
+                                // Save the updated state
+                                let _ = stunts_engine::saved_state::save_saved_state_raw(saved_state.clone());
+                                
+                                // Update editor with new project and sequence
+                                editor.saved_state = Some(saved_state.clone());
+                                editor.project_selected = Some(uuid::Uuid::parse_str(&new_project.project_id).unwrap());
+
+                                export_settings_signals.load(&ExportSettings::default());
+                                editor.current_view = "scene".to_string();
+
+                                saved_state.sequences.iter().enumerate().for_each(|(i, s)| {
+                                    editor.restore_sequence_objects(
+                                        &s,
+                                        true,
+                                    );
+                                });
+                                
+                                // Set the current sequence data
+                                if let Some(sequence) = saved_state.sequences.first() {
+                                    editor.current_sequence_data = Some(sequence.clone());
+
+                                    show_sequence(editor, sequence);
+                                }
+                                
+                                // Hide creation form and show main canvas
+                                show_project_creation.set(false);
+                                show_project_list.set(false);
+                                show_editor.set(true);
+                                project_name_text.set("".to_string());
+                                editor.canvas_hidden = false;
+                                
+                                println!("Project created successfully: {}", new_project.project_name);
+                            }
+                            Err(e) => {
+                                println!("Failed to load newly created project state: {}", e);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        println!("Failed to create local project: {}", e);
+                    }
+                }
+            }
+            other => return unsupported(&other, self.family()),
+        }
+
+        Ok(())
+    }
+}
+
+/// Show only the objects of `sequence` and apply its background, as after opening a project
+fn show_sequence(editor: &mut Editor, sequence: &Sequence) {
+    editor.polygons.iter_mut().for_each(|p| {
+        p.hidden = true;
+    });
+    editor.image_items.iter_mut().for_each(|i| {
+        i.hidden = true;
+    });
+    editor.text_items.iter_mut().for_each(|t| {
+        t.hidden = true;
+    });
+    editor.video_items.iter_mut().for_each(|t| {
+        t.hidden = true;
+    });
+
+    sequence.active_polygons.iter().for_each(|ap| {
+        let polygon = editor
+            .polygons
+            .iter_mut()
+            .find(|p| p.id.to_string() == ap.id)
+            .expect("Couldn't find polygon");
+        polygon.hidden = false;
+    });
+    sequence.active_image_items.iter().for_each(|si| {
+        let image = editor
+            .image_items
+            .iter_mut()
+            .find(|i| i.id.to_string() == si.id)
+            .expect("Couldn't find image");
+        image.hidden = false;
+    });
+    sequence.active_text_items.iter().for_each(|tr| {
+        let text = editor
+            .text_items
+            .iter_mut()
+            .find(|t| t.id.to_string() == tr.id)
+            .expect("Couldn't find image");
+        text.hidden = false;
+    });
+    sequence.active_video_items.iter().for_each(|tr| {
+        let video = editor
+            .video_items
+            .iter_mut()
+            .find(|t| t.id.to_string() == tr.id)
+            .expect("Couldn't find image");
+        video.hidden = false;
+    });
+
+    let mut background_fill = Some(BackgroundFill::Color([
+        wgpu_to_human(0.8) as i32,
+        wgpu_to_human(0.8) as i32,
+        wgpu_to_human(0.8) as i32,
+        255,
+    ]));
+
+    if sequence.background_fill.is_some() {
+        background_fill = sequence.background_fill.clone();
+    }
+
+    match background_fill.expect("Couldn't get default background fill")
+    {
+        BackgroundFill::Color(fill) => {
+            editor.replace_background(
+                uuid::Uuid::parse_str(&sequence.id).unwrap(),
+                rgb_to_wgpu(
+                    fill[0] as u8,
+                    fill[1] as u8,
+                    fill[2] as u8,
+                    fill[3] as f32,
+                ),
+            );
+        }
+        _ => {
+            println!("Not supported yet...");
+        }
+    }
+
+    editor.update_motion_paths(sequence);
+}
@@ -0,0 +1,291 @@
+use stunts_engine::animations::{BackgroundFill, Sequence};
+use stunts_engine::editor::rgb_to_wgpu;
+use stunts_engine::saved_state::save_saved_state_raw;
+
+use super::{
+    sequence_options, unsupported, AppSignals, NO_PROJECT_OPEN, Command, CommandContext, CommandFamily,
+    CommandHandler, CommandResult,
+};
+use crate::theme_sidebar;
+
+pub struct SequenceHandler;
+
+impl CommandHandler for SequenceHandler {
+    fn family(&self) -> CommandFamily {
+        CommandFamily::Sequences
+    }
+
+    fn handle(&mut self, command: Command, ctx: &mut CommandContext<'_>) -> CommandResult {
+        let CommandContext { editor, signals, .. } = ctx;
+        let AppSignals {
+            current_sequence_id,
+            available_sequences,
+            ..
+        } = &**signals;
+
+        match command {
+            Command::CreateSequence { name, project_id } => {
+                println!("Processing create sequence command: {} for project {}", name, project_id);
+
+                editor.canvas_hidden = false;
+                
+                // editor is already a MutexGuard, no need to lock again
+                // Create new sequence with unique ID
+                let sequence_id = uuid::Uuid::new_v4().to_string();
+                let new_sequence = Sequence {
+                    id: sequence_id.clone(),
+                    name: name.clone(),
+                    duration_ms: 5000, // Default 5 second duration
+                    background_fill: None,
+                    active_polygons: Vec::new(),
+                    active_text_items: Vec::new(),
+                    active_image_items: Vec::new(),
+                    active_video_items: Vec::new(),
+                    polygon_motion_paths: Vec::new(),
+                };
+                
+                // Set as current sequence first
+                current_sequence_id.set(sequence_id.clone());
+                editor.current_sequence_data = Some(new_sequence.clone());
+
+                editor.update_motion_paths(&new_sequence);
+                
+                // Add to saved state
+                if let Some(ref mut saved_state) = editor.saved_state {
+                    saved_state.sequences.push(new_sequence.clone());
+                    
+                    // Save the updated state
+                    save_saved_state_raw(saved_state.clone());
+                    
+                    // Update available sequences dropdown
+                    available_sequences.set(sequence_options(&saved_state.sequences));
+                }
+            }
+            Command::SelectSequence { sequence_id } => {
+                println!("Selecting sequence: {}", sequence_id);
+
+                editor.canvas_hidden = false;
+                
+                // editor is already a MutexGuard, no need to lock again
+                // Clone the sequence data first to avoid borrowing conflicts
+                let sequence_data = if let Some(ref saved_state) = editor.saved_state {
+                    saved_state.sequences.iter().find(|s| s.id == sequence_id).cloned()
+                } else {
+                    None
+                };
+                
+                if let Some(sequence) = sequence_data {
+                    // Hide all objects first
+                    editor.polygons.iter_mut().for_each(|p| p.hidden = true);
+                    editor.image_items.iter_mut().for_each(|i| i.hidden = true);
+                    editor.text_items.iter_mut().for_each(|t| t.hidden = true);
+                    editor.video_items.iter_mut().for_each(|v| v.hidden = true);
+                    
+                    // Show objects for this sequence
+                    sequence.active_polygons.iter().for_each(|ap| {
+                        if let Some(polygon) = editor.polygons.iter_mut().find(|p| p.id.to_string() == ap.id) {
+                            polygon.hidden = false;
+                        }
+                    });
+                    sequence.active_image_items.iter().for_each(|si| {
+                        if let Some(image) = editor.image_items.iter_mut().find(|i| i.id.to_string() == si.id) {
+                            image.hidden = false;
+                        }
+                    });
+                    sequence.active_text_items.iter().for_each(|tr| {
+                        if let Some(text) = editor.text_items.iter_mut().find(|t| t.id.to_string() == tr.id) {
+                            text.hidden = false;
+                        }
+                    });
+                    sequence.active_video_items.iter().for_each(|vi| {
+                        if let Some(video) = editor.video_items.iter_mut().find(|v| v.id.to_string() == vi.id) {
+                            video.hidden = false;
+                        }
+                    });
+                    
+                    // Set as current sequence
+                    editor.current_sequence_data = Some(sequence.clone());
+                    current_sequence_id.set(sequence_id);
+
+                    editor.update_motion_paths(&sequence);
+                }
+            }
+            Command::LoadSequences => {
+                println!("Loading sequences");
+
+                editor.canvas_hidden = true;
+                
+                // editor is already a MutexGuard, no need to lock again
+                if let Some(ref saved_state) = editor.saved_state {
+                    // Update available sequences dropdown
+                    available_sequences.set(sequence_options(&saved_state.sequences));
+                }
+            }
+            Command::TogglePlay => {
+                if editor.is_playing {
+                    println!("Pause Sequence...");
+
+                    editor.is_playing = false;
+                    editor.start_playing_time = None;
+
+                    // should return objects to the startup positions and state
+                    editor.reset_sequence_objects();
+                } else {
+                    println!("Play Sequence...");
+
+                    let now = std::time::Instant::now();
+                    editor.start_playing_time = Some(now);
+                    editor.is_playing = true;
+                }
+            }
+            Command::ApplyTheme { theme } => {
+                println!("Applying theme: {:?}", theme);
+
+                let background_color_row = theme[0].trunc() as usize;
+                let background_color_column = (theme[0].fract() * 10.0) as usize;
+                let background_color_hex = theme_sidebar::THEME_COLORS[background_color_row][background_color_column];
+
+                let text_color_row = theme[4].trunc() as usize;
+                let text_color_column = (theme[4].fract() * 10.0) as usize;
+                let text_color_hex = theme_sidebar::THEME_COLORS[text_color_row][text_color_column];
+
+                // Parse hex to RGB
+                let background_color = hex_to_rgb(background_color_hex);
+                let text_color = hex_to_rgb(text_color_hex);
+                let font_index = theme[2];
+
+                println!("Updating text color: {:?}", text_color);
+                println!("Updating background color: {:?}", background_color);
+
+                let text_color_wgpu = rgb_to_wgpu(
+                    text_color[0] as u8,
+                    text_color[1] as u8,
+                    text_color[2] as u8,
+                    255.0,
+                );
+
+                let background_color_wgpu = rgb_to_wgpu(
+                    background_color[0] as u8,
+                    background_color[1] as u8,
+                    background_color[2] as u8,
+                    255.0,
+                );
+
+                // Update text items for current sequence
+                let ids_to_update: Vec<_> = editor
+                    .text_items
+                    .iter()
+                    .filter(|text| {
+                        text.current_sequence_id.to_string() == current_sequence_id.get()
+                    })
+                    .map(|text| text.id)
+                    .collect();
+
+                if let Some(font_data) = editor.font_manager.font_data.get(font_index as usize) {
+                    let font_id = font_data.0.clone();
+
+                    for id in ids_to_update.clone() {
+                        editor.update_text_color(id, text_color);
+                        editor.update_text_font_family(font_id.clone(), id);
+                        
+                        // Update text fill colors
+                        editor.update_text(id, "red_fill", stunts_engine::editor::InputValue::Number(text_color_wgpu[0] as f32), false);
+                        editor.update_text(id, "green_fill", stunts_engine::editor::InputValue::Number(text_color_wgpu[1] as f32), false);
+                        editor.update_text(id, "blue_fill", stunts_engine::editor::InputValue::Number(text_color_wgpu[2] as f32), false);
+                    }
+                }
+
+                // loop through polygons and apply text_color (so it contrasts with background)
+                let ids_to_update: Vec<_> = editor
+                    .polygons
+                    .iter()
+                    .filter(|poly| {
+                        poly.current_sequence_id.to_string() == current_sequence_id.get()
+                    })
+                    .map(|poly| poly.id)
+                    .collect();
+
+                for id in ids_to_update.clone() {
+                    editor.update_polygon(id, "red", stunts_engine::editor::InputValue::Number(text_color[0] as f32), false);
+                    editor.update_polygon(id, "green", stunts_engine::editor::InputValue::Number(text_color[1] as f32), false);
+                    editor.update_polygon(id, "blue", stunts_engine::editor::InputValue::Number(text_color[2] as f32), false);
+                }
+
+                // Update background for current sequence
+                if let Ok(background_uuid) = uuid::Uuid::parse_str(&current_sequence_id.get()) {
+                    editor.update_background(
+                        background_uuid,
+                        "red",
+                        stunts_engine::editor::InputValue::Number(background_color[0] as f32),
+                    );
+                    editor.update_background(
+                        background_uuid,
+                        "green",
+                        stunts_engine::editor::InputValue::Number(background_color[1] as f32),
+                    );
+                    editor.update_background(
+                        background_uuid,
+                        "blue",
+                        stunts_engine::editor::InputValue::Number(background_color[2] as f32),
+                    );
+                }
+
+                // Update saved state
+                if let Some(saved_state) = editor.saved_state.as_mut() {
+                    saved_state.sequences.iter_mut().for_each(|s| {
+                        if s.id == current_sequence_id.get() {
+                            // Update text items
+                            s.active_text_items.iter_mut().for_each(|t| {
+                                t.color = text_color;
+                                if let Some(background_fill) = t.background_fill.as_mut() {
+                                    *background_fill = text_color;
+                                }
+                            });
+
+                            // // Update sequence background
+                            // if s.background_fill.is_none() {
+                            //     s.background_fill = Some(BackgroundFill::Color([
+                            //         wgpu_to_human(0.8) as i32,
+                            //         wgpu_to_human(0.8) as i32, 
+                            //         wgpu_to_human(0.8) as i32,
+                            //         255,
+                            //     ]));
+                            // }
+
+                            // if let Some(BackgroundFill::Color(fill)) = s.background_fill.as_mut() {
+                            //     *fill = background_color;
+                            // }
+
+                            // Just set it directly - simpler and clearer
+                            s.background_fill = Some(BackgroundFill::Color(background_color));
+
+                        }
+                    });
+                }
+
+                save_saved_state_raw(editor.saved_state.clone().ok_or(NO_PROJECT_OPEN)?);
+
+                println!("Theme applied successfully!");
+            }
+            Command::RedisplayCanvas => {
+                println!("Redisplaying canvas");
+                editor.canvas_hidden = false;
+            }
+            other => return unsupported(&other, self.family()),
+        }
+
+        Ok(())
+    }
+}
+
+fn hex_to_rgb(hex: &str) -> [i32; 4] {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() == 6 {
+        let r = i32::from_str_radix(&hex[0..2], 16).unwrap_or(0);
+        let g = i32::from_str_radix(&hex[2..4], 16).unwrap_or(0);
+        let b = i32::from_str_radix(&hex[4..6], 16).unwrap_or(0);
+        [r, g, b, 255]
+    } else {
+        [128, 128, 128, 255] // fallback gray
+    }
+}
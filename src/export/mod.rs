@@ -0,0 +1,698 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use stunts_engine::{
+    animations::{AnimationData, AnimationProperty, BackgroundFill, KeyType, KeyframeValue, Sequence, UIKeyframe},
+    export::exporter::{ExportProgress as EngineProgress, Exporter},
+    timelines::SavedTimelineStateConfig,
+};
+use tokio::sync::mpsc as tokio_mpsc;
+
+use crate::helpers::projects::arrange_sequences_in_series;
+
+pub mod queue;
+pub mod settings;
+pub mod transcode;
+
+pub use queue::{ExportJob, ExportJobUpdate, ExportQueue};
+pub use settings::{ExportSettings, OutputFormat, ResolutionPreset};
+
+/// Export status as seen by the UI and CLI. Wraps the engine's progress messages
+/// and adds what only the app knows about: the sequence being rendered and cancellation.
+#[derive(Debug, Clone)]
+pub enum ExportProgress {
+    Progress {
+        percent: f32,
+        sequence_name: Option<String>,
+    },
+    Complete(String),
+    Error(String),
+    Cancelled,
+}
+
+/// Shared flag the export thread watches; cloning shares the same flag
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once `cancel` has been called
+    pub async fn cancelled(&self) {
+        while !self.is_cancelled() {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
+
+/// Which part of the project to render
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ExportScope {
+    /// Every sequence, back-to-back in storage order
+    All,
+    Sequence { sequence_id: String },
+    /// An in/out range on the arranged timeline
+    Range { start_ms: i32, end_ms: i32 },
+}
+
+impl ExportScope {
+    /// Parse a range written in seconds, e.g. `2.5-7`
+    pub fn parse_range(value: &str) -> Result<ExportScope, String> {
+        let (start, end) = value
+            .split_once('-')
+            .ok_or_else(|| format!("Invalid range '{}', expected <in>-<out> in seconds", value))?;
+
+        let start_s = start
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid in-point in '{}'", value))?;
+        let end_s = end
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid out-point in '{}'", value))?;
+
+        if start_s < 0.0 || end_s <= start_s {
+            return Err(format!("Range '{}' must have 0 <= in < out", value));
+        }
+
+        Ok(ExportScope::Range {
+            start_ms: (start_s * 1000.0).round() as i32,
+            end_ms: (end_s * 1000.0).round() as i32,
+        })
+    }
+}
+
+/// Everything the exporter needs to render a project, shared by the GUI and the CLI
+pub struct ExportPlan {
+    pub sequences: Vec<Sequence>,
+    pub timeline_config: SavedTimelineStateConfig,
+    pub total_duration_s: f64,
+    /// Value for the {sequence} filename token, `None` for the whole project
+    pub sequence_name: Option<String>,
+    /// No sequence sets a background fill, so formats with alpha can leave it transparent
+    pub transparent_background: bool,
+    /// Text burned in for the free plan; none unless the caller sets it
+    pub watermark: Option<String>,
+}
+
+impl ExportPlan {
+    /// Start/end times and names of the sequences on the export timeline
+    fn sequence_spans(&self) -> Vec<(i32, i32, String)> {
+        self.timeline_config
+            .timeline_sequences
+            .iter()
+            .filter_map(|ts| {
+                let sequence = self.sequences.iter().find(|s| s.id == ts.sequence_id)?;
+                Some((
+                    ts.start_time_ms,
+                    ts.start_time_ms + sequence.duration_ms,
+                    sequence.name.clone(),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Build the timeline and total duration for the requested scope.
+/// Sequences are laid out back-to-back the same way new projects are arranged.
+pub fn plan_export(mut sequences: Vec<Sequence>, scope: &ExportScope) -> Result<ExportPlan, String> {
+    if sequences.is_empty() {
+        return Err("No sequences to export".to_string());
+    }
+
+    let mut sequence_name = None;
+
+    if let ExportScope::Sequence { sequence_id } = scope {
+        sequences.retain(|s| &s.id == sequence_id);
+
+        match sequences.first() {
+            Some(sequence) => sequence_name = Some(sequence.name.clone()),
+            None => return Err("Selected sequence no longer exists".to_string()),
+        }
+    }
+
+    let mut timeline_config = arrange_sequences_in_series(&mut sequences);
+
+    let mut total_duration_s = sequences
+        .iter()
+        .map(|s| s.duration_ms as f64 / 1000.0)
+        .sum::<f64>();
+
+    if let ExportScope::Range { start_ms, end_ms } = *scope {
+        let timeline_end_ms = (total_duration_s * 1000.0).round() as i32;
+        let end_ms = end_ms.min(timeline_end_ms);
+
+        if start_ms < 0 || start_ms >= end_ms {
+            return Err(format!(
+                "Range {:.1}s-{:.1}s is outside the {:.1}s timeline",
+                start_ms as f64 / 1000.0,
+                end_ms as f64 / 1000.0,
+                total_duration_s
+            ));
+        }
+
+        // Drop sequences outside the range and shift the rest so the in-point lands at 0.
+        // A sequence that starts before the in-point is cut to start there.
+        let durations: std::collections::HashMap<String, i32> = sequences
+            .iter()
+            .map(|s| (s.id.clone(), s.duration_ms))
+            .collect();
+
+        timeline_config.timeline_sequences.retain(|ts| {
+            let sequence_end_ms = ts.start_time_ms + durations.get(&ts.sequence_id).copied().unwrap_or(0);
+            sequence_end_ms > start_ms && ts.start_time_ms < end_ms
+        });
+
+        sequences.retain(|s| {
+            timeline_config
+                .timeline_sequences
+                .iter()
+                .any(|ts| ts.sequence_id == s.id)
+        });
+
+        for ts in &mut timeline_config.timeline_sequences {
+            let cut_ms = (start_ms - ts.start_time_ms).max(0);
+            let keep_ms = end_ms - ts.start_time_ms - cut_ms;
+            ts.start_time_ms = (ts.start_time_ms - start_ms).max(0);
+
+            if let Some(sequence) = sequences.iter_mut().find(|s| s.id == ts.sequence_id) {
+                trim_sequence(sequence, cut_ms, keep_ms);
+            }
+        }
+
+        total_duration_s = (end_ms - start_ms) as f64 / 1000.0;
+        sequence_name = Some(format!(
+            "{}s-{}s",
+            start_ms as f64 / 1000.0,
+            end_ms as f64 / 1000.0
+        ));
+    }
+
+    if total_duration_s <= 0.0 {
+        return Err("Invalid sequence duration".to_string());
+    }
+
+    let transparent_background = sequences.iter().all(|s| s.background_fill.is_none());
+
+    Ok(ExportPlan {
+        sequences,
+        timeline_config,
+        total_duration_s,
+        sequence_name,
+        transparent_background,
+        watermark: None,
+    })
+}
+
+/// Drop the first `cut_ms` of a sequence and keep at most `keep_ms` after that
+fn trim_sequence(sequence: &mut Sequence, cut_ms: i32, keep_ms: i32) {
+    sequence.duration_ms = (sequence.duration_ms - cut_ms).min(keep_ms);
+
+    if cut_ms > 0 {
+        for animation in &mut sequence.polygon_motion_paths {
+            trim_animation(animation, cut_ms);
+        }
+    }
+}
+
+/// Move an animation `cut_ms` earlier, dropping what would play before 0. Properties
+/// caught mid-way start from their value at the cut, interpolated linearly.
+fn trim_animation(animation: &mut AnimationData, cut_ms: i32) {
+    let start_ms = animation.start_time_ms - cut_ms;
+    if start_ms >= 0 {
+        animation.start_time_ms = start_ms;
+        return;
+    }
+
+    let cut = Duration::from_millis(-start_ms as u64);
+    animation.start_time_ms = 0;
+    animation.duration = animation.duration.saturating_sub(cut);
+    for property in &mut animation.properties {
+        trim_keyframes(property, cut);
+    }
+}
+
+fn trim_keyframes(property: &mut AnimationProperty, cut: Duration) {
+    property.keyframes.sort_by_key(|keyframe| keyframe.time);
+
+    let next = property.keyframes.iter().position(|keyframe| keyframe.time >= cut);
+    let first = match next {
+        Some(0) => None,
+        Some(next) => {
+            let (before, after) = (&property.keyframes[next - 1], &property.keyframes[next]);
+            let span = (after.time - before.time).as_secs_f64();
+            let progress = if span > 0.0 { (cut - before.time).as_secs_f64() / span } else { 1.0 };
+            Some(keyframe_at(before, interpolate(&before.value, &after.value, progress)))
+        }
+        // Past the last keyframe the property holds its final value
+        None => property.keyframes.last().map(|last| keyframe_at(last, last.value.clone())),
+    };
+
+    property.keyframes.retain(|keyframe| keyframe.time >= cut);
+    if let Some(first) = first {
+        if property.keyframes.first().map_or(true, |keyframe| keyframe.time > cut) {
+            property.keyframes.insert(0, first);
+        }
+    }
+
+    for keyframe in &mut property.keyframes {
+        keyframe.time = keyframe.time.saturating_sub(cut);
+        if let KeyType::Range(range) = &mut keyframe.key_type {
+            range.end_time = range.end_time.saturating_sub(cut);
+        }
+    }
+
+    for child in &mut property.children {
+        trim_keyframes(child, cut);
+    }
+}
+
+/// A keyframe at the cut, which becomes 0 once the rest are shifted
+fn keyframe_at(template: &UIKeyframe, value: KeyframeValue) -> UIKeyframe {
+    UIKeyframe {
+        id: uuid::Uuid::new_v4().to_string(),
+        time: Duration::ZERO,
+        value,
+        easing: template.easing.clone(),
+        path_type: template.path_type.clone(),
+        key_type: KeyType::Frame,
+    }
+}
+
+fn interpolate(from: &KeyframeValue, to: &KeyframeValue, progress: f64) -> KeyframeValue {
+    let lerp = |a: i32, b: i32| a + ((b - a) as f64 * progress).round() as i32;
+
+    match (from, to) {
+        (KeyframeValue::Position([x1, y1]), KeyframeValue::Position([x2, y2])) => {
+            KeyframeValue::Position([lerp(*x1, *x2), lerp(*y1, *y2)])
+        }
+        (KeyframeValue::Scale(a), KeyframeValue::Scale(b)) => KeyframeValue::Scale(lerp(*a, *b)),
+        (KeyframeValue::Opacity(a), KeyframeValue::Opacity(b)) => KeyframeValue::Opacity(lerp(*a, *b)),
+        (KeyframeValue::Rotation(a), KeyframeValue::Rotation(b)) => KeyframeValue::Rotation(lerp(*a, *b)),
+        (KeyframeValue::Zoom(a), KeyframeValue::Zoom(b)) => KeyframeValue::Zoom(lerp(*a, *b)),
+        (KeyframeValue::PerspectiveX(a), KeyframeValue::PerspectiveX(b)) => KeyframeValue::PerspectiveX(lerp(*a, *b)),
+        (KeyframeValue::PerspectiveY(a), KeyframeValue::PerspectiveY(b)) => KeyframeValue::PerspectiveY(lerp(*a, *b)),
+        (KeyframeValue::Custom(a), KeyframeValue::Custom(b)) if a.len() == b.len() => {
+            KeyframeValue::Custom(a.iter().zip(b).map(|(a, b)| lerp(*a, *b)).collect())
+        }
+        _ => from.clone(),
+    }
+}
+
+/// Run the exporter on its own thread (it owns a separate GPU device and runtime).
+/// Progress is reported through `progress_tx`, finishing with `Complete`, `Error` or `Cancelled`.
+pub fn spawn_export(
+    plan: ExportPlan,
+    output_path: &Path,
+    settings: &ExportSettings,
+    project_id: String,
+    progress_tx: tokio_mpsc::UnboundedSender<ExportProgress>,
+    cancel_token: CancelToken,
+) -> std::thread::JoinHandle<()> {
+    let output_path = output_path.to_path_buf();
+    let settings = settings.clone();
+
+    std::thread::spawn(move || {
+        // Create tokio runtime for this thread
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(run_export(
+            plan,
+            &output_path,
+            &settings,
+            project_id,
+            progress_tx,
+            cancel_token,
+        ));
+    })
+}
+
+/// Render a plan to `output_path` on the current thread's runtime.
+/// Cancelling drops the render at its next await point and removes any partial output.
+pub async fn run_export(
+    plan: ExportPlan,
+    output_path: &Path,
+    settings: &ExportSettings,
+    project_id: String,
+    progress_tx: tokio_mpsc::UnboundedSender<ExportProgress>,
+    cancel_token: CancelToken,
+) {
+    let output_path = output_path.to_path_buf();
+    let export_window_size = settings.window_size();
+    let fps = settings.fps;
+    let format = settings.format;
+    let transparent_background = plan.transparent_background && format.keeps_alpha();
+    let watermark = plan.watermark.clone();
+
+    // The engine only writes unmarked MP4 at its native frame rate, anything else is converted in a second pass
+    let needs_conversion = format != OutputFormat::Mp4 || fps.is_some() || watermark.is_some();
+
+    // The output may be an earlier export with the same name; it's only replaced once the new
+    // one is done, and cancelling removes only what this run created
+    let output_existed = path_exists(&output_path);
+    let render_path = if needs_conversion || output_existed {
+        let mut render_name = output_path.file_name().unwrap_or_default().to_os_string();
+        render_name.push(".render.mp4");
+        output_path.with_file_name(render_name)
+    } else {
+        output_path.clone()
+    };
+    // The engine takes the path as a string
+    let Some(render_path_str) = render_path.to_str().map(str::to_string) else {
+        let e = format!("Can't export to {}, the path isn't valid UTF-8", render_path.display());
+        println!("Export failed: {}", e);
+        let _ = progress_tx.send(ExportProgress::Error(e));
+        return;
+    };
+    let partial_paths: Vec<PathBuf> = [&render_path, &output_path]
+        .into_iter()
+        .filter(|path| !path_exists(path))
+        .cloned()
+        .collect();
+
+    let sequence_spans = plan.sequence_spans();
+    let total_duration_ms = plan.total_duration_s * 1000.0;
+
+    // Render on the key colour so the background can be told apart from grey objects
+    let mut sequences = plan.sequences;
+    if transparent_background {
+        let [r, g, b] = transcode::TRANSPARENT_KEY_RGB;
+        for sequence in &mut sequences {
+            sequence.background_fill = Some(BackgroundFill::Color([r as i32, g as i32, b as i32, 255]));
+        }
+    }
+
+    println!(
+        "Starting export - sequences: {}, duration: {}s, size: {}x{}",
+        sequences.len(),
+        plan.total_duration_s,
+        export_window_size.width,
+        export_window_size.height
+    );
+
+    let mut exporter = Exporter::new(&render_path_str);
+    let (engine_tx, mut engine_rx) = tokio_mpsc::unbounded_channel::<EngineProgress>();
+
+    let forward_progress = |progress: EngineProgress| {
+        // Completion and errors are reported below, once post-processing is done
+        if let EngineProgress::Progress(percent) = progress {
+            let time_ms = (percent as f64 / 100.0 * total_duration_ms) as i32;
+            let sequence_name = sequence_spans
+                .iter()
+                .find(|(start_ms, end_ms, _)| time_ms >= *start_ms && time_ms < *end_ms)
+                .map(|(_, _, name)| name.clone());
+
+            let _ = progress_tx.send(ExportProgress::Progress {
+                percent,
+                sequence_name,
+            });
+        }
+    };
+
+    let run = exporter.run(
+        export_window_size,
+        sequences,
+        plan.timeline_config,
+        export_window_size.width,
+        export_window_size.height,
+        plan.total_duration_s,
+        engine_tx,
+        project_id,
+    );
+    tokio::pin!(run);
+
+    let result = loop {
+        tokio::select! {
+            result = &mut run => break Some(result),
+            Some(progress) = engine_rx.recv() => forward_progress(progress),
+            _ = cancel_token.cancelled() => break None,
+        }
+    };
+
+    while let Ok(progress) = engine_rx.try_recv() {
+        forward_progress(progress);
+    }
+
+    let result = match result {
+        Some(result) => result,
+        None => {
+            println!("Export cancelled, removing partial output");
+            remove_partial_files(&partial_paths);
+            let _ = progress_tx.send(ExportProgress::Cancelled);
+            return;
+        }
+    };
+
+    let result = result.and_then(|_| {
+        if !needs_conversion {
+            if render_path != output_path {
+                return std::fs::rename(&render_path, &output_path)
+                    .map_err(|e| format!("Failed to replace {}: {}", output_path.display(), e));
+            }
+            return Ok(());
+        }
+
+        // Converted next to the output and swapped in once done, so a failed conversion leaves
+        // an earlier export alone and a PNG sequence never mixes in an earlier one's frames
+        let converted_path = converted_path(&output_path, format);
+        remove_partial_files(std::slice::from_ref(&converted_path));
+
+        let converted = transcode::convert(&render_path, &converted_path, format, fps, transparent_background, watermark.as_deref())
+            .and_then(|_| replace_output(&converted_path, &output_path));
+        let _ = std::fs::remove_file(&render_path);
+        if converted.is_err() {
+            remove_partial_files(std::slice::from_ref(&converted_path));
+        }
+        converted
+    });
+
+    match result {
+        Ok(_) => {
+            println!("Export completed successfully");
+            let _ = progress_tx.send(ExportProgress::Complete(
+                output_path.display().to_string(),
+            ));
+        }
+        Err(e) => {
+            println!("Export failed: {}", e);
+            let _ = progress_tx.send(ExportProgress::Error(e));
+        }
+    }
+}
+
+/// Where a conversion is written before it replaces `output_path`, e.g. "clip.gif.convert.gif";
+/// the extension is kept since ffmpeg picks the container from it
+fn converted_path(output_path: &Path, format: OutputFormat) -> PathBuf {
+    let mut converted_name = output_path.file_name().unwrap_or_default().to_os_string();
+    converted_name.push(".convert");
+    if let Some(extension) = format.extension() {
+        converted_name.push(".");
+        converted_name.push(extension);
+    }
+    output_path.with_file_name(converted_name)
+}
+
+/// Move a finished conversion to `output_path`, replacing an earlier export there. A folder
+/// can't be renamed over another one, so an earlier PNG sequence is set aside first and put
+/// back if the move fails.
+fn replace_output(converted_path: &Path, output_path: &Path) -> Result<(), String> {
+    let replace_error = |e: std::io::Error| format!("Failed to replace {}: {}", output_path.display(), e);
+
+    if !output_path.is_dir() {
+        return std::fs::rename(converted_path, output_path).map_err(replace_error);
+    }
+
+    let mut previous_name = output_path.file_name().unwrap_or_default().to_os_string();
+    previous_name.push(".previous");
+    let previous_path = output_path.with_file_name(previous_name);
+    remove_partial_files(std::slice::from_ref(&previous_path));
+
+    std::fs::rename(output_path, &previous_path).map_err(replace_error)?;
+    if let Err(e) = std::fs::rename(converted_path, output_path) {
+        let _ = std::fs::rename(&previous_path, output_path);
+        return Err(replace_error(e));
+    }
+
+    remove_partial_files(std::slice::from_ref(&previous_path));
+    Ok(())
+}
+
+/// Whether anything is at `path`, without following symlinks
+fn path_exists(path: &Path) -> bool {
+    std::fs::symlink_metadata(path).is_ok()
+}
+
+/// Remove the output of a cancelled export. Only pass paths the export created: a PNG
+/// sequence's folder is removed with everything in it.
+fn remove_partial_files(paths: &[PathBuf]) {
+    for path in paths {
+        if path.exists() {
+            // PNG sequences are written to a folder
+            let removed = if path.is_dir() {
+                std::fs::remove_dir_all(path)
+            } else {
+                std::fs::remove_file(path)
+            };
+
+            if let Err(e) = removed {
+                println!("Failed to remove partial export {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use stunts_engine::animations::{EasingType, ObjectType};
+    use stunts_engine::editor::PathType;
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("stunts-export-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn converted_paths_keep_the_format_extension() {
+        let cases = [
+            ("clip.gif", OutputFormat::Gif, "clip.gif.convert.gif"),
+            ("clip.webp", OutputFormat::WebP, "clip.webp.convert.webp"),
+            ("frames", OutputFormat::PngSequence, "frames.convert"),
+        ];
+
+        for (name, format, expected) in cases {
+            let path = converted_path(&Path::new("/exports").join(name), format);
+            assert_eq!(path, Path::new("/exports").join(expected), "{}", name);
+        }
+    }
+
+    #[test]
+    fn a_png_sequence_replaces_every_earlier_frame() {
+        let dir = scratch_dir();
+        let output = dir.join("frames");
+        let converted = dir.join("frames.convert");
+
+        fs::create_dir_all(&output).unwrap();
+        for frame in ["frame_00001.png", "frame_00002.png", "frame_00003.png"] {
+            fs::write(output.join(frame), "earlier").unwrap();
+        }
+        fs::create_dir_all(&converted).unwrap();
+        fs::write(converted.join("frame_00001.png"), "new").unwrap();
+
+        replace_output(&converted, &output).unwrap();
+
+        assert_eq!(file_names(&output), vec!["frame_00001.png"]);
+        assert_eq!(fs::read_to_string(output.join("frame_00001.png")).unwrap(), "new");
+        assert_eq!(file_names(&dir), vec!["frames"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_file_replaces_an_earlier_export() {
+        let dir = scratch_dir();
+        let output = dir.join("clip.gif");
+        let converted = dir.join("clip.gif.convert.gif");
+
+        fs::write(&output, "earlier").unwrap();
+        fs::write(&converted, "new").unwrap();
+
+        replace_output(&converted, &output).unwrap();
+
+        assert_eq!(fs::read_to_string(&output).unwrap(), "new");
+        assert_eq!(file_names(&dir), vec!["clip.gif"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn opacity_animation(start_time_ms: i32, keyframes: &[(u64, i32)]) -> AnimationData {
+        AnimationData {
+            id: "animation".to_string(),
+            object_type: ObjectType::Polygon,
+            polygon_id: "object".to_string(),
+            duration: Duration::from_millis(2000),
+            start_time_ms,
+            properties: vec![AnimationProperty {
+                name: "Opacity".to_string(),
+                property_path: "opacity".to_string(),
+                children: Vec::new(),
+                keyframes: keyframes
+                    .iter()
+                    .map(|&(time_ms, opacity)| UIKeyframe {
+                        id: uuid::Uuid::new_v4().to_string(),
+                        time: Duration::from_millis(time_ms),
+                        value: KeyframeValue::Opacity(opacity),
+                        easing: EasingType::Linear,
+                        path_type: PathType::Linear,
+                        key_type: KeyType::Frame,
+                    })
+                    .collect(),
+                depth: 0,
+            }],
+            position: [0, 0],
+        }
+    }
+
+    fn opacity_keyframes(animation: &AnimationData) -> Vec<(u128, i32)> {
+        animation.properties[0]
+            .keyframes
+            .iter()
+            .map(|keyframe| match keyframe.value {
+                KeyframeValue::Opacity(opacity) => (keyframe.time.as_millis(), opacity),
+                ref other => panic!("expected an opacity value, got {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn trimming_shifts_animations_that_start_after_the_cut() {
+        let mut animation = opacity_animation(2000, &[(0, 0), (2000, 100)]);
+        trim_animation(&mut animation, 1500);
+
+        assert_eq!(animation.start_time_ms, 500);
+        assert_eq!(animation.duration, Duration::from_millis(2000));
+        assert_eq!(opacity_keyframes(&animation), vec![(0, 0), (2000, 100)]);
+    }
+
+    #[test]
+    fn trimming_starts_a_running_animation_from_its_value_at_the_cut() {
+        let cases = [
+            ("between keyframes", 1500, vec![(0, 50), (1000, 100)]),
+            ("on a keyframe", 2500, vec![(0, 100)]),
+            ("after the last keyframe", 3000, vec![(0, 100)]),
+        ];
+
+        for (case, cut_ms, expected) in cases {
+            let mut animation = opacity_animation(500, &[(0, 0), (2000, 100)]);
+            trim_animation(&mut animation, cut_ms);
+
+            assert_eq!(animation.start_time_ms, 0, "{}", case);
+            assert_eq!(opacity_keyframes(&animation), expected, "{}", case);
+        }
+    }
+}
@@ -0,0 +1,510 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use stunts_engine::saved_state::{get_ground_truth_dir, load_project_state};
+use tokio::sync::mpsc as tokio_mpsc;
+use uuid::Uuid;
+
+use crate::helpers::entitlements::Entitlements;
+
+use super::{plan_export, run_export, CancelToken, ExportProgress, ExportScope, ExportSettings};
+
+const QUEUE_FILENAME: &str = "export_queue.json";
+
+/// One render waiting in (or running at the front of) the export queue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportJob {
+    pub id: String,
+    pub project_id: String,
+    pub project_name: String,
+    pub scope: ExportScope,
+    pub settings: ExportSettings,
+    /// Text burned in for the free plan, decided when the job was queued
+    #[serde(default)]
+    pub watermark: Option<String>,
+}
+
+impl ExportJob {
+    pub fn new(project_id: String, project_name: String, scope: ExportScope, settings: ExportSettings) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            project_id,
+            project_name,
+            scope,
+            settings,
+            watermark: None,
+        }
+    }
+
+    pub fn with_watermark(mut self, watermark: Option<String>) -> Self {
+        self.watermark = watermark;
+        self
+    }
+
+    /// Short description for status messages, e.g. "Promo (1080x1920)"
+    pub fn label(&self) -> String {
+        let size = self.settings.window_size();
+        format!("{} ({}x{})", self.project_name, size.width, size.height)
+    }
+}
+
+/// Progress of a single queued job
+#[derive(Debug, Clone)]
+pub struct ExportJobUpdate {
+    pub job_id: String,
+    pub progress: ExportProgress,
+}
+
+/// Renders jobs one after another on a dedicated export thread.
+/// Unfinished jobs are written to disk and picked up again on the next launch.
+pub struct ExportQueue {
+    jobs: Arc<Mutex<VecDeque<ExportJob>>>,
+    job_tx: mpsc::Sender<ExportJob>,
+    running: Arc<Mutex<Option<(String, CancelToken)>>>,
+    update_tx: tokio_mpsc::UnboundedSender<ExportJobUpdate>,
+    /// Where unfinished jobs are saved, `None` to keep them in memory only
+    path: Option<PathBuf>,
+    /// Jobs from the last session, held back until `resume` checks them against the plan
+    saved: Mutex<Vec<ExportJob>>,
+}
+
+impl ExportQueue {
+    /// Start the export thread. Jobs left over from the last session wait for `resume`.
+    pub fn start(update_tx: tokio_mpsc::UnboundedSender<ExportJobUpdate>) -> ExportQueue {
+        let path = match queue_path() {
+            Ok(path) => Some(path),
+            Err(e) => {
+                println!("Queued exports won't be kept across launches: {}", e);
+                None
+            }
+        };
+
+        // Runtime for the export thread
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        Self::with_worker(path, update_tx, move |job, update_tx, cancel_token| {
+            rt.block_on(run_job(job, update_tx, cancel_token))
+        })
+    }
+
+    /// Start a queue whose export thread hands each job to `render`
+    fn with_worker<F>(path: Option<PathBuf>, update_tx: tokio_mpsc::UnboundedSender<ExportJobUpdate>, render: F) -> ExportQueue
+    where
+        F: Fn(&ExportJob, &tokio_mpsc::UnboundedSender<ExportJobUpdate>, CancelToken) -> ExportProgress + Send + 'static,
+    {
+        let saved = path.as_deref().map(load_jobs).unwrap_or_default();
+        let jobs = Arc::new(Mutex::new(saved.clone()));
+        let running: Arc<Mutex<Option<(String, CancelToken)>>> = Arc::new(Mutex::new(None));
+        let (job_tx, job_rx) = mpsc::channel::<ExportJob>();
+
+        std::thread::spawn({
+            let jobs = jobs.clone();
+            let running = running.clone();
+            let update_tx = update_tx.clone();
+            let path = path.clone();
+            move || {
+                while let Ok(job) = job_rx.recv() {
+                    // Skip jobs removed from the queue while they were waiting
+                    if !jobs.lock().unwrap().iter().any(|j| j.id == job.id) {
+                        continue;
+                    }
+
+                    let cancel_token = CancelToken::new();
+                    *running.lock().unwrap() = Some((job.id.clone(), cancel_token.clone()));
+
+                    let progress = render(&job, &update_tx, cancel_token);
+
+                    running.lock().unwrap().take();
+                    {
+                        let mut jobs = jobs.lock().unwrap();
+                        jobs.retain(|j| j.id != job.id);
+                        save_jobs(path.as_deref(), &jobs);
+                    }
+
+                    // Sent after the job leaves the queue so listeners see the remaining count
+                    let _ = update_tx.send(ExportJobUpdate {
+                        job_id: job.id.clone(),
+                        progress,
+                    });
+                }
+            }
+        });
+
+        ExportQueue {
+            jobs,
+            job_tx,
+            running,
+            update_tx,
+            path,
+            saved: Mutex::new(saved.into_iter().collect()),
+        }
+    }
+
+    /// A queue that renders nothing and doesn't touch the saved jobs, for tests
+    #[cfg(test)]
+    pub(crate) fn idle() -> ExportQueue {
+        let (update_tx, _) = tokio_mpsc::unbounded_channel();
+
+        ExportQueue {
+            jobs: Arc::new(Mutex::new(VecDeque::new())),
+            job_tx: mpsc::channel().0,
+            running: Arc::new(Mutex::new(None)),
+            update_tx,
+            path: None,
+            saved: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Whether jobs from the last session are still waiting for `resume`
+    pub fn has_saved_jobs(&self) -> bool {
+        !self.saved.lock().unwrap().is_empty()
+    }
+
+    /// Render the jobs left over from the last session under the current plan. Saved jobs
+    /// can be edited on disk, so each is checked again: one over the plan's resolution is
+    /// dropped, and the watermark follows the plan rather than what was saved.
+    pub fn resume(&self, entitlements: &Entitlements) {
+        let saved = std::mem::take(&mut *self.saved.lock().unwrap());
+        if saved.is_empty() {
+            return;
+        }
+        println!("Resuming {} queued export(s)", saved.len());
+
+        for mut job in saved {
+            let size = job.settings.window_size();
+            if let Err(e) = entitlements.check_export_resolution(size.width, size.height) {
+                println!("Not resuming export {}: {}", job.label(), e);
+                self.remove(&job.id);
+                let _ = self.update_tx.send(ExportJobUpdate {
+                    job_id: job.id.clone(),
+                    progress: ExportProgress::Error(e),
+                });
+                continue;
+            }
+
+            job.watermark = entitlements.watermark.clone();
+            {
+                let mut jobs = self.jobs.lock().unwrap();
+                if let Some(queued) = jobs.iter_mut().find(|j| j.id == job.id) {
+                    queued.watermark = job.watermark.clone();
+                }
+                save_jobs(self.path.as_deref(), &jobs);
+            }
+
+            let _ = self.job_tx.send(job);
+        }
+    }
+
+    /// Drop a job from the queue, returning whether it was there
+    fn remove(&self, job_id: &str) -> bool {
+        let mut jobs = self.jobs.lock().unwrap();
+        let before = jobs.len();
+        jobs.retain(|j| j.id != job_id);
+        save_jobs(self.path.as_deref(), &jobs);
+        jobs.len() != before
+    }
+
+    pub fn enqueue(&self, job: ExportJob) {
+        println!("Queueing export {}", job.label());
+
+        {
+            let mut jobs = self.jobs.lock().unwrap();
+            jobs.push_back(job.clone());
+            save_jobs(self.path.as_deref(), &jobs);
+        }
+
+        let _ = self.job_tx.send(job);
+    }
+
+    /// Jobs not yet finished, the running one first
+    pub fn jobs(&self) -> Vec<ExportJob> {
+        self.jobs.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn running_job_id(&self) -> Option<String> {
+        self.running.lock().unwrap().as_ref().map(|(job_id, _)| job_id.clone())
+    }
+
+    /// Cancel a job; a running job stops rendering, a waiting one is dropped from the queue
+    pub fn cancel(&self, job_id: &str) {
+        if let Some((running_id, cancel_token)) = self.running.lock().unwrap().as_ref() {
+            if running_id == job_id {
+                cancel_token.cancel();
+                return;
+            }
+        }
+
+        self.saved.lock().unwrap().retain(|j| j.id != job_id);
+
+        if self.remove(job_id) {
+            let _ = self.update_tx.send(ExportJobUpdate {
+                job_id: job_id.to_string(),
+                progress: ExportProgress::Cancelled,
+            });
+        }
+    }
+
+    /// Cancel the running job and everything waiting behind it
+    pub fn cancel_all(&self) {
+        let waiting: Vec<String> = {
+            let running_id = self.running_job_id();
+            self.jobs
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|j| Some(&j.id) != running_id.as_ref())
+                .map(|j| j.id.clone())
+                .collect()
+        };
+
+        for job_id in waiting {
+            self.cancel(&job_id);
+        }
+
+        if let Some(job_id) = self.running_job_id() {
+            self.cancel(&job_id);
+        }
+    }
+}
+
+/// Load the project from disk and render it, returning the final progress message.
+/// Intermediate progress is forwarded to `update_tx` as it arrives.
+async fn run_job(
+    job: &ExportJob,
+    update_tx: &tokio_mpsc::UnboundedSender<ExportJobUpdate>,
+    cancel_token: CancelToken,
+) -> ExportProgress {
+    println!("Starting queued export {}", job.label());
+
+    let saved_state = match load_project_state(job.project_id.clone()) {
+        Ok(saved_state) => saved_state,
+        Err(e) => return ExportProgress::Error(format!("Failed to load project: {}", e)),
+    };
+
+    let mut plan = match plan_export(saved_state.sequences, &job.scope) {
+        Ok(plan) => plan,
+        Err(e) => return ExportProgress::Error(e),
+    };
+    plan.watermark = job.watermark.clone();
+
+    let output_path = match job
+        .settings
+        .output_path(&job.project_name, plan.sequence_name.as_deref())
+    {
+        Ok(output_path) => output_path,
+        Err(e) => return ExportProgress::Error(e),
+    };
+
+    if let Some(exports_dir) = output_path.parent() {
+        if let Err(e) = fs::create_dir_all(exports_dir) {
+            return ExportProgress::Error(format!("Failed to create exports directory: {}", e));
+        }
+    }
+
+    let (progress_tx, mut progress_rx) = tokio_mpsc::unbounded_channel::<ExportProgress>();
+
+    let render = run_export(
+        plan,
+        &output_path,
+        &job.settings,
+        job.project_id.clone(),
+        progress_tx,
+        cancel_token,
+    );
+    tokio::pin!(render);
+
+    let mut finished = false;
+    let mut outcome = ExportProgress::Error("Export ended without a result".to_string());
+
+    // Keep forwarding progress while the render runs, then drain what's left
+    loop {
+        tokio::select! {
+            _ = &mut render, if !finished => finished = true,
+            progress = progress_rx.recv() => match progress {
+                Some(ExportProgress::Progress { percent, sequence_name }) => {
+                    let _ = update_tx.send(ExportJobUpdate {
+                        job_id: job.id.clone(),
+                        progress: ExportProgress::Progress { percent, sequence_name },
+                    });
+                }
+                Some(final_progress) => outcome = final_progress,
+                None => break,
+            },
+        }
+    }
+
+    outcome
+}
+
+fn queue_path() -> Result<PathBuf, String> {
+    get_ground_truth_dir()
+        .map(|dir| dir.join(QUEUE_FILENAME))
+        .ok_or_else(|| "Couldn't get CommonOS directory".to_string())
+}
+
+fn load_jobs(path: &Path) -> VecDeque<ExportJob> {
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            println!("Failed to parse export queue, starting empty: {}", e);
+            VecDeque::new()
+        }),
+        Err(_) => VecDeque::new(),
+    }
+}
+
+/// Write the unfinished jobs to `path`; without one they're only kept in memory
+fn save_jobs(path: Option<&Path>, jobs: &VecDeque<ExportJob>) {
+    let Some(path) = path else {
+        return;
+    };
+
+    let result = serde_json::to_string_pretty(jobs)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(path, json).map_err(|e| e.to_string()));
+
+    if let Err(e) = result {
+        println!("Failed to save export queue: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::utilities::PlanLimits;
+    use std::time::{Duration, Instant};
+
+    fn job(name: &str) -> ExportJob {
+        ExportJob::new("project".to_string(), name.to_string(), ExportScope::All, ExportSettings::default())
+    }
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("stunts-export-queue-{}.json", Uuid::new_v4()))
+    }
+
+    /// A queue whose renders record the job's name and wait for a message on the returned sender
+    fn blocking_queue(
+        path: Option<PathBuf>,
+    ) -> (
+        ExportQueue,
+        Arc<Mutex<Vec<String>>>,
+        mpsc::Sender<()>,
+        tokio_mpsc::UnboundedReceiver<ExportJobUpdate>,
+    ) {
+        let rendered = Arc::new(Mutex::new(Vec::new()));
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let (update_tx, update_rx) = tokio_mpsc::unbounded_channel();
+
+        let queue = ExportQueue::with_worker(path, update_tx, {
+            let rendered = rendered.clone();
+            move |job, _, _| {
+                rendered.lock().unwrap().push(job.project_name.clone());
+                let _ = release_rx.recv();
+                ExportProgress::Complete(job.project_name.clone())
+            }
+        });
+
+        (queue, rendered, release_tx, update_rx)
+    }
+
+    fn wait_until(condition: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "timed out");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn jobs_survive_a_restart() {
+        let path = temp_path();
+        let jobs: VecDeque<ExportJob> = [job("Promo"), job("Teaser").with_watermark(Some("Stunts".to_string()))].into();
+
+        save_jobs(Some(&path), &jobs);
+        assert_eq!(load_jobs(&path), jobs);
+
+        // They wait for the plan before rendering again
+        let (queue, rendered, release_tx, _updates) = blocking_queue(Some(path.clone()));
+        assert!(queue.has_saved_jobs());
+        assert_eq!(queue.jobs(), Vec::from(jobs));
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(rendered.lock().unwrap().is_empty());
+
+        queue.resume(&Entitlements::unrestricted(chrono::Utc::now()));
+        assert!(!queue.has_saved_jobs());
+        wait_until(|| rendered.lock().unwrap().len() == 1);
+        assert_eq!(*rendered.lock().unwrap(), vec!["Promo"]);
+
+        drop(release_tx);
+        wait_until(|| queue.is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn resumed_jobs_are_checked_against_the_plan() {
+        let path = temp_path();
+        let mut small = job("Small");
+        small.settings.resolution = crate::export::ResolutionPreset::Landscape720;
+        save_jobs(Some(&path), &[job("Large"), small].into());
+
+        let (queue, rendered, release_tx, mut updates) = blocking_queue(Some(path.clone()));
+        let limits = PlanLimits {
+            max_export_resolution: Some(720),
+            watermark: true,
+            ..PlanLimits::default()
+        };
+        queue.resume(&Entitlements::with_limits("free".to_string(), &limits, "Stunts", String::new(), None));
+
+        let update = updates.blocking_recv().unwrap();
+        assert!(matches!(update.progress, ExportProgress::Error(_)), "{:?}", update.progress);
+
+        wait_until(|| rendered.lock().unwrap().len() == 1);
+        assert_eq!(*rendered.lock().unwrap(), vec!["Small"]);
+        let jobs = queue.jobs();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].watermark.as_deref(), Some("Stunts"));
+        assert_eq!(load_jobs(&path).len(), 1);
+
+        drop(release_tx);
+        wait_until(|| queue.is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn cancelled_waiting_jobs_are_skipped() {
+        let (queue, rendered, release_tx, mut updates) = blocking_queue(None);
+        let first = job("First");
+        let second = job("Second");
+        let third = job("Third");
+
+        queue.enqueue(first.clone());
+        queue.enqueue(second.clone());
+        queue.enqueue(third.clone());
+        wait_until(|| queue.running_job_id().as_deref() == Some(first.id.as_str()));
+
+        queue.cancel(&second.id);
+        let update = updates.blocking_recv().unwrap();
+        assert_eq!(update.job_id, second.id);
+        assert!(matches!(update.progress, ExportProgress::Cancelled));
+        assert_eq!(queue.len(), 2);
+
+        release_tx.send(()).unwrap();
+        release_tx.send(()).unwrap();
+        for expected in [&first, &third] {
+            let update = updates.blocking_recv().unwrap();
+            assert_eq!(update.job_id, expected.id);
+            assert!(matches!(update.progress, ExportProgress::Complete(_)));
+        }
+
+        assert_eq!(*rendered.lock().unwrap(), vec!["First", "Third"]);
+        assert!(queue.is_empty());
+    }
+}
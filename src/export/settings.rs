@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use stunts_engine::editor::WindowSize;
+
+use crate::helpers::projects::{load_project_file, save_project_file};
+
+const SETTINGS_FILENAME: &str = "export_settings.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResolutionPreset {
+    Landscape1080,
+    Vertical1080,
+    Square1080,
+    Uhd4k,
+    /// Fits plans that cap exports at 720p
+    Landscape720,
+    Custom { width: u32, height: u32 },
+}
+
+impl ResolutionPreset {
+    pub const PRESETS: [ResolutionPreset; 5] = [
+        ResolutionPreset::Landscape1080,
+        ResolutionPreset::Vertical1080,
+        ResolutionPreset::Square1080,
+        ResolutionPreset::Uhd4k,
+        ResolutionPreset::Landscape720,
+    ];
+
+    pub fn size(&self) -> WindowSize {
+        let (width, height) = match *self {
+            ResolutionPreset::Landscape1080 => (1920, 1080),
+            ResolutionPreset::Vertical1080 => (1080, 1920),
+            ResolutionPreset::Square1080 => (1080, 1080),
+            ResolutionPreset::Uhd4k => (3840, 2160),
+            ResolutionPreset::Landscape720 => (1280, 720),
+            ResolutionPreset::Custom { width, height } => (width, height),
+        };
+
+        WindowSize { width, height }
+    }
+
+    pub fn label(&self) -> String {
+        match *self {
+            ResolutionPreset::Landscape1080 => "1080p Landscape (1920x1080)".to_string(),
+            ResolutionPreset::Vertical1080 => "1080p Vertical (1080x1920)".to_string(),
+            ResolutionPreset::Square1080 => "Square (1080x1080)".to_string(),
+            ResolutionPreset::Uhd4k => "4K UHD (3840x2160)".to_string(),
+            ResolutionPreset::Landscape720 => "720p Landscape (1280x720)".to_string(),
+            ResolutionPreset::Custom { width, height } => format!("Custom ({}x{})", width, height),
+        }
+    }
+
+    /// Stable key used as the dropdown value
+    pub fn key(&self) -> &'static str {
+        match self {
+            ResolutionPreset::Landscape1080 => "landscape_1080",
+            ResolutionPreset::Vertical1080 => "vertical_1080",
+            ResolutionPreset::Square1080 => "square_1080",
+            ResolutionPreset::Uhd4k => "uhd_4k",
+            ResolutionPreset::Landscape720 => "landscape_720",
+            ResolutionPreset::Custom { .. } => "custom",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<ResolutionPreset> {
+        Self::PRESETS.into_iter().find(|preset| preset.key() == key)
+    }
+}
+
+/// File type written by an export. Everything except MP4 is converted from the rendered video with ffmpeg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutputFormat {
+    Mp4,
+    /// A folder of numbered PNG frames, transparent where the sequence has no background fill
+    PngSequence,
+    Gif,
+    WebP,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 4] = [
+        OutputFormat::Mp4,
+        OutputFormat::PngSequence,
+        OutputFormat::Gif,
+        OutputFormat::WebP,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OutputFormat::Mp4 => "MP4 video",
+            OutputFormat::PngSequence => "PNG sequence",
+            OutputFormat::Gif => "Animated GIF",
+            OutputFormat::WebP => "Animated WebP",
+        }
+    }
+
+    /// Stable key used as the dropdown value and on the command line
+    pub fn key(&self) -> &'static str {
+        match self {
+            OutputFormat::Mp4 => "mp4",
+            OutputFormat::PngSequence => "png",
+            OutputFormat::Gif => "gif",
+            OutputFormat::WebP => "webp",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<OutputFormat> {
+        Self::ALL.into_iter().find(|format| format.key() == key)
+    }
+
+    /// Whether the format can leave the background transparent
+    pub fn keeps_alpha(&self) -> bool {
+        matches!(self, OutputFormat::PngSequence | OutputFormat::WebP)
+    }
+
+    /// File extension of the output, `None` for formats written to a folder
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            OutputFormat::PngSequence => None,
+            format => Some(format.key()),
+        }
+    }
+}
+
+/// Per-project export configuration, stored next to the project's saved state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    pub resolution: ResolutionPreset,
+    pub format: OutputFormat,
+    /// Frame rate of the delivered file, `None` keeps the exporter's native rate
+    pub fps: Option<u32>,
+    /// Folder to write into, `None` means `<working dir>/exports`
+    pub destination_dir: Option<PathBuf>,
+    /// Supports the tokens {project}, {sequence}, {width}, {height}, {date} and {timestamp}
+    pub filename_template: String,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            resolution: ResolutionPreset::Landscape1080,
+            format: OutputFormat::Mp4,
+            fps: None,
+            destination_dir: None,
+            filename_template: "export_{timestamp}".to_string(),
+        }
+    }
+}
+
+impl ExportSettings {
+    pub fn load(project_id: &str) -> ExportSettings {
+        load_project_file(project_id, SETTINGS_FILENAME)
+    }
+
+    pub fn save(&self, project_id: &str) -> anyhow::Result<()> {
+        save_project_file(project_id, SETTINGS_FILENAME, self)
+    }
+
+    pub fn window_size(&self) -> WindowSize {
+        self.resolution.size()
+    }
+
+    pub fn destination_dir(&self) -> PathBuf {
+        self.destination_dir.clone().unwrap_or_else(|| {
+            std::env::current_dir()
+                .unwrap_or_else(|_| PathBuf::from("."))
+                .join("exports")
+        })
+    }
+
+    /// Expand the filename template. `sequence_name` is `None` when the whole project is exported.
+    /// Fails when nothing usable is left of the name, which would otherwise point at the
+    /// destination folder itself (or its parent, for "..").
+    pub fn render_filename(&self, project_name: &str, sequence_name: Option<&str>) -> Result<String, String> {
+        let now = chrono::Local::now();
+        let size = self.window_size();
+
+        let template = if self.filename_template.trim().is_empty() {
+            ExportSettings::default().filename_template
+        } else {
+            self.filename_template.clone()
+        };
+
+        let filename = template
+            .replace("{project}", project_name)
+            .replace("{sequence}", sequence_name.unwrap_or("all"))
+            .replace("{width}", &size.width.to_string())
+            .replace("{height}", &size.height.to_string())
+            .replace("{date}", &now.format("%Y-%m-%d").to_string())
+            .replace("{timestamp}", &now.timestamp().to_string());
+
+        let mut filename = sanitize_filename(&filename);
+
+        // Drop an extension typed into the template so it can't disagree with the format
+        for format in OutputFormat::ALL {
+            if let Some(extension) = format.extension() {
+                let suffix = format!(".{}", extension);
+                if filename.to_lowercase().ends_with(&suffix) {
+                    filename.truncate(filename.len() - suffix.len());
+                }
+            }
+        }
+
+        if filename.chars().all(|c| matches!(c, '.' | ' ')) {
+            return Err(format!(
+                "The filename template \"{}\" gives an empty filename, add some text or a token such as {{timestamp}}",
+                template
+            ));
+        }
+
+        Ok(match self.format.extension() {
+            Some(extension) => format!("{}.{}", filename, extension),
+            None => filename,
+        })
+    }
+
+    pub fn output_path(&self, project_name: &str, sequence_name: Option<&str>) -> Result<PathBuf, String> {
+        Ok(self.destination_dir().join(self.render_filename(project_name, sequence_name)?))
+    }
+}
+
+fn sanitize_filename(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ' ') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(format: OutputFormat, filename_template: &str) -> ExportSettings {
+        ExportSettings {
+            format,
+            filename_template: filename_template.to_string(),
+            destination_dir: Some(PathBuf::from("exports")),
+            ..ExportSettings::default()
+        }
+    }
+
+    #[test]
+    fn expands_the_template() {
+        let settings = settings(OutputFormat::Mp4, "{project}-{sequence}-{width}x{height}");
+
+        assert_eq!(settings.render_filename("Demo", Some("Intro")), Ok("Demo-Intro-1920x1080.mp4".to_string()));
+        assert_eq!(settings.render_filename("Demo", None), Ok("Demo-all-1920x1080.mp4".to_string()));
+    }
+
+    #[test]
+    fn replaces_unsafe_characters_and_typed_extensions() {
+        let settings = settings(OutputFormat::Gif, "{project}/clip.mp4");
+
+        assert_eq!(settings.render_filename("a:b", None), Ok("a_b_clip.gif".to_string()));
+    }
+
+    #[test]
+    fn rejects_names_that_point_at_a_folder() {
+        for (template, project_name) in [("{project}", ""), ("..", "Demo"), (" . ", "Demo"), ("{project}", "..")] {
+            let png = settings(OutputFormat::PngSequence, template);
+            assert!(png.render_filename(project_name, None).is_err(), "{:?} {:?}", template, project_name);
+            assert!(png.output_path(project_name, None).is_err(), "{:?} {:?}", template, project_name);
+
+            let mp4 = settings(OutputFormat::Mp4, template);
+            assert!(mp4.render_filename(project_name, None).is_err(), "{:?} {:?}", template, project_name);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+use std::process::Command as ProcessCommand;
+
+use super::settings::OutputFormat;
+
+/// Background painted behind exports that should come out transparent, then keyed out again.
+/// The engine can't render alpha, so object pixels of this colour turn transparent too; magenta
+/// is rare in designs, which keeps that to a minimum.
+pub const TRANSPARENT_KEY_RGB: [u8; 3] = [255, 0, 255];
+const TRANSPARENT_KEY_HEX: &str = "0xFF00FF";
+
+/// How far from the key colour a pixel may be and still be keyed out; loose enough for the
+/// colour shifts of the intermediate MP4, tight enough to keep nearby pinks and purples
+const TRANSPARENT_KEY_SIMILARITY: f32 = 0.1;
+
+/// Frame rate used for GIFs when none is set, since full-rate GIFs get very large
+const DEFAULT_GIF_FPS: u32 = 15;
+
+/// Run ffmpeg, turning a missing binary or a failed encode into a readable error
+pub fn run_ffmpeg<I, S>(args: I) -> Result<(), String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let output = ProcessCommand::new("ffmpeg")
+        .arg("-y")
+        .arg("-hide_banner")
+        .args(["-loglevel", "error"])
+        .args(args)
+        .output()
+        .map_err(|e| format!("Couldn't run ffmpeg (is it installed and on PATH?): {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "ffmpeg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Re-encode a rendered video at a different frame rate
+pub fn retime(input: &Path, output: &Path, fps: u32) -> Result<(), String> {
+    run_ffmpeg([
+        OsStr::new("-i"),
+        input.as_os_str(),
+        OsStr::new("-r"),
+        OsStr::new(&fps.to_string()),
+        OsStr::new("-c:v"),
+        OsStr::new("libx264"),
+        OsStr::new("-pix_fmt"),
+        OsStr::new("yuv420p"),
+        output.as_os_str(),
+    ])
+}
+
+/// `text` in the bottom-right corner, scaled with the frame height
+fn watermark_filter(text: &str) -> String {
+    format!(
+        "drawtext=text='{}':fontcolor=white@0.7:fontsize=h/24:borderw=1:bordercolor=black@0.4:x=w-tw-h/40:y=h-th-h/40",
+        text.replace('\\', "\\\\").replace('\'', "\\'").replace(':', "\\:")
+    )
+}
+
+/// Convert a rendered MP4 into the requested output format.
+/// With `transparent_background` the video was rendered on `TRANSPARENT_KEY_RGB`, which is keyed
+/// out for formats that carry alpha, and `watermark` is text burned in for the free plan.
+pub fn convert(
+    input: &Path,
+    output: &Path,
+    format: OutputFormat,
+    fps: Option<u32>,
+    transparent_background: bool,
+    watermark: Option<&str>,
+) -> Result<(), String> {
+    if format == OutputFormat::Mp4 && watermark.is_none() {
+        return match fps {
+            Some(fps) => retime(input, output, fps),
+            None => std::fs::copy(input, output)
+                .map(|_| ())
+                .map_err(|e| format!("Failed to write {}: {}", output.display(), e)),
+        };
+    }
+
+    let mut filters = Vec::new();
+
+    let fps = match format {
+        OutputFormat::Gif => Some(fps.unwrap_or(DEFAULT_GIF_FPS)),
+        _ => fps,
+    };
+    if let Some(fps) = fps {
+        filters.push(format!("fps={}", fps));
+    }
+
+    if transparent_background && format.keeps_alpha() {
+        filters.push(format!("colorkey={}:{}:0", TRANSPARENT_KEY_HEX, TRANSPARENT_KEY_SIMILARITY));
+        filters.push("format=rgba".to_string());
+    }
+
+    if let Some(text) = watermark {
+        filters.push(watermark_filter(text));
+    }
+
+    let mut args: Vec<OsString> = vec![OsString::from("-i"), input.as_os_str().to_owned()];
+
+    match format {
+        OutputFormat::Mp4 => {
+            // Only watermarked MP4 gets here, and drawing on it means encoding again
+            args.push("-vf".into());
+            args.push(filters.join(",").into());
+            args.extend(["-c:v", "libx264", "-pix_fmt", "yuv420p"].map(OsString::from));
+            args.push(output.as_os_str().to_owned());
+        }
+        OutputFormat::PngSequence => {
+            std::fs::create_dir_all(output)
+                .map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+
+            if !filters.is_empty() {
+                args.push("-vf".into());
+                args.push(filters.join(",").into());
+            }
+            args.push(output.join("frame_%05d.png").into_os_string());
+        }
+        OutputFormat::Gif => {
+            // A palette generated from the clip itself looks far better than the default one
+            filters.push("split[a][b];[a]palettegen[p];[b][p]paletteuse".to_string());
+
+            args.push("-filter_complex".into());
+            args.push(filters.join(",").into());
+            args.push("-loop".into());
+            args.push("0".into());
+            args.push(output.as_os_str().to_owned());
+        }
+        OutputFormat::WebP => {
+            if !filters.is_empty() {
+                args.push("-vf".into());
+                args.push(filters.join(",").into());
+            }
+            args.extend(["-c:v", "libwebp", "-lossless", "0", "-q:v", "80", "-loop", "0"].map(OsString::from));
+            args.push(output.as_os_str().to_owned());
+        }
+    }
+
+    run_ffmpeg(args)
+}
@@ -0,0 +1,173 @@
+use gui_core::{Element, widgets::*};
+use gui_core::widgets::container::{Background, Padding};
+use gui_core::widgets::text::text_signal;
+use gui_reactive::Signal;
+use std::sync::mpsc;
+use std::time::Duration;
+use vello::peniko::{Color, Gradient};
+
+use crate::commands::Command;
+
+/// Text shown in the export progress panel, updated from the render loop
+#[derive(Clone)]
+pub struct ExportProgressSignals {
+    pub visible: Signal<bool>,
+    pub job: Signal<String>,
+    pub percent: Signal<String>,
+    pub current_sequence: Signal<String>,
+    pub elapsed: Signal<String>,
+    pub eta: Signal<String>,
+    pub queued: Signal<String>,
+}
+
+impl ExportProgressSignals {
+    pub fn new() -> Self {
+        Self {
+            visible: Signal::new(false),
+            job: Signal::new(String::new()),
+            percent: Signal::new(String::new()),
+            current_sequence: Signal::new(String::new()),
+            elapsed: Signal::new(String::new()),
+            eta: Signal::new(String::new()),
+            queued: Signal::new(String::new()),
+        }
+    }
+
+    pub fn start(&self, job_label: &str) {
+        self.job.set(job_label.to_string());
+        self.percent.set("Starting export...".to_string());
+        self.current_sequence.set(String::new());
+        self.elapsed.set("Elapsed: 0:00".to_string());
+        self.eta.set("Remaining: estimating...".to_string());
+        self.visible.set(true);
+    }
+
+    pub fn update(&self, percent: f32, sequence_name: Option<&str>, elapsed: Duration) {
+        self.percent.set(format!("{:.1}%", percent));
+
+        if let Some(sequence_name) = sequence_name {
+            self.current_sequence.set(format!("Rendering: {}", sequence_name));
+        }
+
+        self.elapsed.set(format!("Elapsed: {}", format_duration(elapsed)));
+
+        // Extrapolate from the average speed so far; too noisy to be useful in the first percent
+        if percent >= 1.0 && percent < 100.0 {
+            let total_s = elapsed.as_secs_f64() * 100.0 / percent as f64;
+            let remaining = Duration::from_secs_f64((total_s - elapsed.as_secs_f64()).max(0.0));
+            self.eta.set(format!("Remaining: {}", format_duration(remaining)));
+        }
+    }
+
+    /// Number of jobs waiting behind the one being rendered
+    pub fn set_queued(&self, count: usize) {
+        self.queued.set(match count {
+            0 => String::new(),
+            1 => "1 more export queued".to_string(),
+            count => format!("{} more exports queued", count),
+        });
+    }
+
+    pub fn finish(&self) {
+        self.queued.set(String::new());
+        self.visible.set(false);
+    }
+}
+
+/// Format as m:ss, or h:mm:ss for long renders
+pub fn format_duration(duration: Duration) -> String {
+    let total_s = duration.as_secs();
+    let (hours, minutes, seconds) = (total_s / 3600, (total_s % 3600) / 60, total_s % 60);
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+pub fn create_export_progress_panel(
+    command_tx: mpsc::Sender<Command>,
+    signals: ExportProgressSignals,
+    button_normal: Gradient,
+    button_hover: Gradient,
+    button_pressed: Gradient,
+) -> Element {
+    let line = |signal: Signal<String>, font_size: f32| {
+        Element::new_widget(Box::new(
+            text_signal(signal)
+                .with_font_size(font_size)
+                .with_color(Color::rgba8(200, 200, 200, 255))
+        ))
+    };
+
+    container()
+        .absolute()
+        .with_position(850.0, 60.0)
+        .with_size(300.0, 210.0)
+        .with_background_color(Color::rgba8(50, 50, 60, 240))
+        .with_border_radius(8.0)
+        .with_padding(Padding::all(15.0))
+        .with_shadow(4.0, 4.0, 8.0, Color::rgba8(0, 0, 0, 150))
+        .with_display_signal(signals.visible.clone())
+        .with_child(
+            column()
+                .with_size(270.0, 180.0)
+                .with_main_axis_alignment(MainAxisAlignment::Start)
+                .with_cross_axis_alignment(CrossAxisAlignment::Start)
+                .with_child(Element::new_widget(Box::new(
+                    text("Exporting")
+                        .with_font_size(14.0)
+                        .with_color(Color::rgba8(255, 255, 255, 255))
+                )))
+                .with_child(line(signals.job.clone(), 12.0))
+                .with_child(line(signals.percent.clone(), 12.0))
+                .with_child(line(signals.current_sequence.clone(), 11.0))
+                .with_child(line(signals.elapsed.clone(), 11.0))
+                .with_child(line(signals.eta.clone(), 11.0))
+                .with_child(line(signals.queued.clone(), 11.0))
+                .with_child(
+                    row()
+                        .with_size(270.0, 35.0)
+                        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+                        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+                        .with_child(Element::new_widget(Box::new(
+                            button("Cancel Export")
+                                .with_font_size(12.0)
+                                .with_width(110.0)
+                                .with_height(25.0)
+                                .with_backgrounds(
+                                    Background::Gradient(button_normal.clone()),
+                                    Background::Gradient(button_hover.clone()),
+                                    Background::Gradient(button_pressed.clone())
+                                )
+                                .on_click({
+                                    let tx = command_tx.clone();
+                                    move || {
+                                        let _ = tx.send(Command::CancelExport);
+                                    }
+                                })
+                        )))
+                        .with_child(Element::new_widget(Box::new(
+                            button("Cancel All")
+                                .with_font_size(12.0)
+                                .with_width(110.0)
+                                .with_height(25.0)
+                                .with_backgrounds(
+                                    Background::Gradient(button_normal.clone()),
+                                    Background::Gradient(button_hover.clone()),
+                                    Background::Gradient(button_pressed.clone())
+                                )
+                                .on_click({
+                                    let tx = command_tx.clone();
+                                    move || {
+                                        let _ = tx.send(Command::ClearExportQueue);
+                                    }
+                                })
+                        )))
+                        .into_container_element()
+                )
+                .into_container_element()
+        )
+        .into_container_element()
+}
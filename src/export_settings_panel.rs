@@ -0,0 +1,553 @@
+use gui_core::{Element, widgets::*};
+use gui_core::widgets::container::{Background, Padding};
+use gui_core::widgets::dropdown::{DropdownOption, dropdown};
+use gui_core::widgets::text::text_signal;
+use gui_reactive::Signal;
+use rfd::FileDialog;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use vello::peniko::{Color, Gradient};
+
+use crate::commands::Command;
+use crate::export::{ExportScope, ExportSettings, OutputFormat, ResolutionPreset};
+
+/// Prebuilt copies of a dropdown, one per selectable value. The dropdown widget
+/// only takes its selection when built, so loading settings shows the copy
+/// that was built with the loaded value.
+#[derive(Clone)]
+struct DropdownCopies {
+    selected: Signal<String>,
+    shown: Vec<(String, Signal<bool>)>,
+}
+
+impl DropdownCopies {
+    fn new(selected: Signal<String>, keys: Vec<String>) -> Self {
+        let shown = keys.into_iter().map(|key| (key, Signal::new(false))).collect();
+        let copies = Self { selected, shown };
+        copies.sync();
+        copies
+    }
+
+    /// Show the copy matching the current value of the selection signal
+    fn sync(&self) {
+        let selected = self.selected.get();
+        for (key, shown) in &self.shown {
+            shown.set(*key == selected);
+        }
+    }
+
+    fn build(
+        &self,
+        options: Vec<DropdownOption>,
+        on_selection_changed: impl Fn(String) + Clone + 'static,
+    ) -> Element {
+        let mut copies = row().with_size(220.0, 25.0);
+        for (key, shown) in &self.shown {
+            copies = copies.with_child(
+                container()
+                    .with_size(220.0, 25.0)
+                    .with_display_signal(shown.clone())
+                    .with_child(Element::new_widget(Box::new(
+                        dropdown()
+                            .with_options(options.clone())
+                            .with_selected_value(key.clone())
+                            .with_size(220.0, 25.0)
+                            .on_selection_changed(on_selection_changed.clone())
+                    )))
+                    .into_container_element()
+            );
+        }
+        copies.into_container_element()
+    }
+}
+
+/// Form state for the export settings dialog, kept outside the panel so that
+/// project loading can prefill it with the persisted settings
+#[derive(Clone)]
+pub struct ExportSettingsSignals {
+    pub visible: Signal<bool>,
+    pub resolution_key: Signal<String>,
+    pub resolution_label: Signal<String>,
+    pub format_key: Signal<String>,
+    pub custom_width: Signal<String>,
+    pub custom_height: Signal<String>,
+    pub fps: Signal<String>,
+    pub destination_dir: Signal<String>,
+    pub filename_template: Signal<String>,
+    /// "all", "sequence" or "range"; the scope is chosen per export and not persisted
+    pub scope_key: Signal<String>,
+    pub range_in: Signal<String>,
+    pub range_out: Signal<String>,
+    pub error: Signal<String>,
+    /// App-wide preference rather than a project setting, shown here since it only affects exports
+    pub reveal_after_export: Signal<bool>,
+    pub reveal_label: Signal<String>,
+    resolution_dropdown: DropdownCopies,
+    format_dropdown: DropdownCopies,
+    scope_dropdown: DropdownCopies,
+}
+
+impl ExportSettingsSignals {
+    pub fn new() -> Self {
+        let resolution_key = Signal::new(String::new());
+        let format_key = Signal::new(String::new());
+        let scope_key = Signal::new("all".to_string());
+
+        let mut resolution_keys: Vec<String> = ResolutionPreset::PRESETS
+            .iter()
+            .map(|preset| preset.key().to_string())
+            .collect();
+        resolution_keys.push("custom".to_string());
+
+        let signals = Self {
+            visible: Signal::new(false),
+            resolution_dropdown: DropdownCopies::new(resolution_key.clone(), resolution_keys),
+            format_dropdown: DropdownCopies::new(
+                format_key.clone(),
+                OutputFormat::ALL.iter().map(|format| format.key().to_string()).collect(),
+            ),
+            scope_dropdown: DropdownCopies::new(
+                scope_key.clone(),
+                vec!["all".to_string(), "sequence".to_string(), "range".to_string()],
+            ),
+            resolution_key,
+            resolution_label: Signal::new(String::new()),
+            format_key,
+            custom_width: Signal::new(String::new()),
+            custom_height: Signal::new(String::new()),
+            fps: Signal::new(String::new()),
+            destination_dir: Signal::new(String::new()),
+            filename_template: Signal::new(String::new()),
+            scope_key,
+            range_in: Signal::new(String::new()),
+            range_out: Signal::new(String::new()),
+            error: Signal::new(String::new()),
+            reveal_after_export: Signal::new(true),
+            reveal_label: Signal::new(String::new()),
+        };
+
+        signals.load(&ExportSettings::default());
+        signals.set_reveal_after_export(true);
+        signals
+    }
+
+    pub fn set_reveal_after_export(&self, enabled: bool) {
+        self.reveal_after_export.set(enabled);
+        self.reveal_label.set(if enabled {
+            "Show file when done: On".to_string()
+        } else {
+            "Show file when done: Off".to_string()
+        });
+    }
+
+    pub fn load(&self, settings: &ExportSettings) {
+        let size = settings.window_size();
+
+        self.resolution_key.set(settings.resolution.key().to_string());
+        self.resolution_label.set(settings.resolution.label());
+        self.format_key.set(settings.format.key().to_string());
+        self.custom_width.set(size.width.to_string());
+        self.custom_height.set(size.height.to_string());
+        self.fps.set(settings.fps.map(|fps| fps.to_string()).unwrap_or_default());
+        // Left empty for the default folder so saving doesn't pin it
+        self.destination_dir.set(
+            settings.destination_dir
+                .as_ref()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_default()
+        );
+        self.filename_template.set(settings.filename_template.clone());
+        self.error.set(String::new());
+
+        self.resolution_dropdown.sync();
+        self.format_dropdown.sync();
+        self.scope_dropdown.sync();
+    }
+
+    /// Validate the form and build the settings it describes
+    pub fn to_settings(&self) -> Result<ExportSettings, String> {
+        let resolution = match ResolutionPreset::from_key(&self.resolution_key.get()) {
+            Some(preset) => preset,
+            None => {
+                let width = self.custom_width.get().trim().parse::<u32>()
+                    .map_err(|_| "Width must be a whole number".to_string())?;
+                let height = self.custom_height.get().trim().parse::<u32>()
+                    .map_err(|_| "Height must be a whole number".to_string())?;
+
+                if width < 16 || height < 16 || width > 7680 || height > 7680 {
+                    return Err("Width and height must be between 16 and 7680".to_string());
+                }
+
+                ResolutionPreset::Custom { width, height }
+            }
+        };
+
+        let fps_text = self.fps.get();
+        let fps = if fps_text.trim().is_empty() {
+            None
+        } else {
+            Some(crate::cli::parse_fps(&fps_text)?)
+        };
+
+        let destination_text = self.destination_dir.get();
+        let destination_dir = if destination_text.trim().is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(destination_text.trim()))
+        };
+
+        let format = OutputFormat::from_key(&self.format_key.get()).unwrap_or(OutputFormat::Mp4);
+
+        Ok(ExportSettings {
+            resolution,
+            format,
+            fps,
+            destination_dir,
+            filename_template: self.filename_template.get(),
+        })
+    }
+
+    pub fn to_scope(&self, current_sequence_id: &str) -> Result<ExportScope, String> {
+        match self.scope_key.get().as_str() {
+            "sequence" => {
+                if current_sequence_id.is_empty() {
+                    return Err("Select a sequence to export first".to_string());
+                }
+                Ok(ExportScope::Sequence {
+                    sequence_id: current_sequence_id.to_string(),
+                })
+            }
+            "range" => ExportScope::parse_range(&format!(
+                "{}-{}",
+                self.range_in.get().trim(),
+                self.range_out.get().trim()
+            )),
+            _ => Ok(ExportScope::All),
+        }
+    }
+}
+
+fn labeled_input(label: &str, signal: Signal<String>, placeholder: &str) -> Element {
+    row()
+        .with_size(360.0, 35.0)
+        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+        .with_child(Element::new_widget(Box::new(
+            text(label)
+                .with_font_size(12.0)
+                .with_color(Color::rgba8(200, 200, 200, 255))
+        )))
+        .with_child(Element::new_widget(Box::new(
+            input()
+                .with_width(220.0)
+                .with_height(25.0)
+                .with_placeholder(placeholder)
+                .with_signal(signal)
+        )))
+        .into_container_element()
+}
+
+pub fn create_export_settings_panel(
+    command_tx: mpsc::Sender<Command>,
+    signals: ExportSettingsSignals,
+    current_sequence_id: Signal<String>,
+    button_normal: Gradient,
+    button_hover: Gradient,
+    button_pressed: Gradient,
+) -> Element {
+    let mut resolution_options: Vec<DropdownOption> = ResolutionPreset::PRESETS
+        .iter()
+        .map(|preset| DropdownOption::new(&preset.label(), preset.key()))
+        .collect();
+    resolution_options.push(DropdownOption::new("Custom", "custom"));
+
+    let resolution_row = row()
+        .with_size(360.0, 35.0)
+        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+        .with_child(Element::new_widget(Box::new(
+            text("Resolution:")
+                .with_font_size(12.0)
+                .with_color(Color::rgba8(200, 200, 200, 255))
+        )))
+        .with_child(signals.resolution_dropdown.build(
+            resolution_options,
+            {
+                let signals = signals.clone();
+                move |selection: String| {
+                    let label = match ResolutionPreset::from_key(&selection) {
+                        Some(preset) => {
+                            let size = preset.size();
+                            signals.custom_width.set(size.width.to_string());
+                            signals.custom_height.set(size.height.to_string());
+                            preset.label()
+                        }
+                        None => "Custom".to_string(),
+                    };
+                    signals.resolution_key.set(selection);
+                    signals.resolution_label.set(label);
+                }
+            }
+        ));
+
+    let format_row = row()
+        .with_size(360.0, 35.0)
+        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+        .with_child(Element::new_widget(Box::new(
+            text("Format:")
+                .with_font_size(12.0)
+                .with_color(Color::rgba8(200, 200, 200, 255))
+        )))
+        .with_child(signals.format_dropdown.build(
+            OutputFormat::ALL
+                .iter()
+                .map(|format| DropdownOption::new(format.label(), format.key()))
+                .collect(),
+            {
+                let format_key = signals.format_key.clone();
+                move |selection: String| {
+                    format_key.set(selection);
+                }
+            }
+        ));
+
+    let scope_row = row()
+        .with_size(360.0, 35.0)
+        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+        .with_child(Element::new_widget(Box::new(
+            text("Export:")
+                .with_font_size(12.0)
+                .with_color(Color::rgba8(200, 200, 200, 255))
+        )))
+        .with_child(signals.scope_dropdown.build(
+            vec![
+                DropdownOption::new("Whole project", "all"),
+                DropdownOption::new("Current sequence", "sequence"),
+                DropdownOption::new("Time range", "range"),
+            ],
+            {
+                let scope_key = signals.scope_key.clone();
+                move |selection: String| {
+                    scope_key.set(selection);
+                }
+            }
+        ));
+
+    let range_row = row()
+        .with_size(360.0, 35.0)
+        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+        .with_child(Element::new_widget(Box::new(
+            text("Range (s):")
+                .with_font_size(12.0)
+                .with_color(Color::rgba8(200, 200, 200, 255))
+        )))
+        .with_child(Element::new_widget(Box::new(
+            input()
+                .with_width(105.0)
+                .with_height(25.0)
+                .with_placeholder("in, ex. 2.5")
+                .with_signal(signals.range_in.clone())
+        )))
+        .with_child(Element::new_widget(Box::new(
+            input()
+                .with_width(105.0)
+                .with_height(25.0)
+                .with_placeholder("out, ex. 7")
+                .with_signal(signals.range_out.clone())
+        )));
+
+    let default_destination = format!(
+        "Default: {}",
+        ExportSettings::default().destination_dir().display()
+    );
+
+    let destination_row = row()
+        .with_size(360.0, 35.0)
+        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+        .with_child(Element::new_widget(Box::new(
+            input()
+                .with_width(270.0)
+                .with_height(25.0)
+                .with_placeholder(&default_destination)
+                .with_signal(signals.destination_dir.clone())
+        )))
+        .with_child(Element::new_widget(Box::new(
+            button("Browse")
+                .with_font_size(12.0)
+                .with_width(80.0)
+                .with_height(25.0)
+                .with_backgrounds(
+                    Background::Gradient(button_normal.clone()),
+                    Background::Gradient(button_hover.clone()),
+                    Background::Gradient(button_pressed.clone())
+                )
+                .on_click({
+                    let destination_dir = signals.destination_dir.clone();
+                    move || {
+                        // Spawn a task to handle the folder dialog
+                        let destination_dir = destination_dir.clone();
+                        tokio::spawn(async move {
+                            if let Some(folder) = FileDialog::new().pick_folder() {
+                                destination_dir.set(folder.display().to_string());
+                            }
+                        });
+                    }
+                })
+        )));
+
+    let reveal_button = button_signal(signals.reveal_label.clone())
+        .with_font_size(12.0)
+        .with_width(180.0)
+        .with_height(25.0)
+        .with_backgrounds(
+            Background::Gradient(button_normal.clone()),
+            Background::Gradient(button_hover.clone()),
+            Background::Gradient(button_pressed.clone())
+        )
+        .on_click({
+            let signals = signals.clone();
+            let tx = command_tx.clone();
+            move || {
+                let enabled = !signals.reveal_after_export.get();
+                signals.set_reveal_after_export(enabled);
+                let _ = tx.send(Command::SetRevealAfterExport { enabled });
+            }
+        });
+
+    let actions_row = row()
+        .with_size(360.0, 40.0)
+        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+        .with_child(Element::new_widget(Box::new(
+            button("Cancel")
+                .with_font_size(12.0)
+                .with_width(80.0)
+                .with_height(30.0)
+                .with_backgrounds(
+                    Background::Gradient(button_normal.clone()),
+                    Background::Gradient(button_hover.clone()),
+                    Background::Gradient(button_pressed.clone())
+                )
+                .on_click({
+                    let visible = signals.visible.clone();
+                    move || {
+                        visible.set(false);
+                    }
+                })
+        )))
+        .with_child(Element::new_widget(Box::new(
+            button("Save")
+                .with_font_size(12.0)
+                .with_width(80.0)
+                .with_height(30.0)
+                .with_backgrounds(
+                    Background::Gradient(button_normal.clone()),
+                    Background::Gradient(button_hover.clone()),
+                    Background::Gradient(button_pressed.clone())
+                )
+                .on_click({
+                    let signals = signals.clone();
+                    let tx = command_tx.clone();
+                    move || {
+                        match signals.to_settings() {
+                            Ok(settings) => {
+                                signals.error.set(String::new());
+                                let _ = tx.send(Command::SaveExportSettings { settings });
+                            }
+                            Err(e) => signals.error.set(e),
+                        }
+                    }
+                })
+        )))
+        .with_child(Element::new_widget(Box::new(
+            button("Export")
+                .with_font_size(12.0)
+                .with_width(100.0)
+                .with_height(30.0)
+                .with_backgrounds(
+                    Background::Gradient(button_normal.clone()),
+                    Background::Gradient(button_hover.clone()),
+                    Background::Gradient(button_pressed.clone())
+                )
+                .on_click({
+                    let signals = signals.clone();
+                    let tx = command_tx.clone();
+                    move || {
+                        let form = signals.to_settings().and_then(|settings| {
+                            let scope = signals.to_scope(&current_sequence_id.get())?;
+                            Ok((settings, scope))
+                        });
+
+                        match form {
+                            Ok((settings, scope)) => {
+                                signals.error.set(String::new());
+                                signals.visible.set(false);
+                                let _ = tx.send(Command::Export {
+                                    settings,
+                                    scope,
+                                });
+                            }
+                            Err(e) => signals.error.set(e),
+                        }
+                    }
+                })
+        )));
+
+    container()
+        .absolute()
+        .with_position(350.0, 100.0)
+        .with_size(400.0, 570.0)
+        .with_background_color(Color::rgba8(50, 50, 60, 240))
+        .with_border_radius(12.0)
+        .with_padding(Padding::all(20.0))
+        .with_shadow(4.0, 4.0, 8.0, Color::rgba8(0, 0, 0, 150))
+        .with_display_signal(signals.visible.clone())
+        .with_child(
+            column()
+                .with_size(360.0, 530.0)
+                .with_main_axis_alignment(MainAxisAlignment::Start)
+                .with_cross_axis_alignment(CrossAxisAlignment::Start)
+                .with_child(Element::new_widget(Box::new(
+                    text("Export Settings")
+                        .with_font_size(18.0)
+                        .with_color(Color::rgba8(255, 255, 255, 255))
+                )))
+                .with_child(scope_row.into_container_element())
+                .with_child(range_row.into_container_element())
+                .with_child(format_row.into_container_element())
+                .with_child(resolution_row.into_container_element())
+                .with_child(Element::new_widget(Box::new(
+                    text_signal(signals.resolution_label.clone())
+                        .with_font_size(10.0)
+                        .with_color(Color::rgba8(170, 170, 170, 255))
+                )))
+                .with_child(labeled_input("Width:", signals.custom_width.clone(), "ex. 1080"))
+                .with_child(labeled_input("Height:", signals.custom_height.clone(), "ex. 1920"))
+                .with_child(labeled_input("Frame rate:", signals.fps.clone(), "native"))
+                .with_child(Element::new_widget(Box::new(
+                    text("Destination:")
+                        .with_font_size(12.0)
+                        .with_color(Color::rgba8(200, 200, 200, 255))
+                )))
+                .with_child(destination_row.into_container_element())
+                .with_child(labeled_input("Filename:", signals.filename_template.clone(), "{project}_{sequence}_{date}"))
+                .with_child(Element::new_widget(Box::new(
+                    text("Tokens: {project} {sequence} {width} {height} {date} {timestamp}")
+                        .with_font_size(10.0)
+                        .with_color(Color::rgba8(170, 170, 170, 255))
+                )))
+                .with_child(Element::new_widget(Box::new(reveal_button)))
+                .with_child(Element::new_widget(Box::new(
+                    text_signal(signals.error.clone())
+                        .with_font_size(11.0)
+                        .with_color(Color::rgba8(255, 120, 120, 255))
+                )))
+                .with_child(actions_row.into_container_element())
+                .into_container_element()
+        )
+        .into_container_element()
+}
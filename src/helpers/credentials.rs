@@ -0,0 +1,377 @@
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use stunts_engine::saved_state::get_ground_truth_dir;
+
+/// Keyring service the app's secrets are stored under
+pub const KEYRING_SERVICE: &str = "stunts-native";
+
+/// Picks the store: `keyring`, `file` or `memory`; unset tries the keyring and falls back to the file
+pub const CREDENTIAL_STORE_ENV: &str = "STUNTS_CREDENTIAL_STORE";
+
+const CREDENTIALS_FILENAME: &str = "credentials.json";
+const CREDENTIALS_KEY_FILENAME: &str = "credentials.key";
+
+/// Somewhere to keep secrets such as the auth token, by name
+pub trait CredentialStore: Send + Sync {
+    /// Shown in logs
+    fn name(&self) -> &'static str;
+
+    /// `None` when nothing is stored under `key`
+    fn get(&self, key: &str) -> anyhow::Result<Option<String>>;
+
+    fn set(&self, key: &str, secret: &str) -> anyhow::Result<()>;
+
+    /// Remove the secret; succeeds when there was none
+    fn delete(&self, key: &str) -> anyhow::Result<()>;
+}
+
+/// The operating system's keyring (Keychain, Credential Manager or Secret Service)
+pub struct KeyringStore {
+    service: String,
+}
+
+impl KeyringStore {
+    pub fn new(service: &str) -> Self {
+        Self { service: service.to_string() }
+    }
+
+    /// Whether the keyring can be reached at all, e.g. a Secret Service is running on Linux.
+    /// Platforms without a keyring backend get keyring's in-memory mock, which doesn't count.
+    pub fn is_available(&self) -> bool {
+        let Ok(entry) = keyring::Entry::new(&self.service, "availability-check") else {
+            return false;
+        };
+
+        if entry.get_credential().is::<keyring::mock::MockCredential>() {
+            return false;
+        }
+
+        matches!(entry.get_password(), Ok(_) | Err(keyring::Error::NoEntry))
+    }
+}
+
+impl CredentialStore for KeyringStore {
+    fn name(&self) -> &'static str {
+        "keyring"
+    }
+
+    fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        match keyring::Entry::new(&self.service, key)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set(&self, key: &str, secret: &str) -> anyhow::Result<()> {
+        keyring::Entry::new(&self.service, key)?.set_password(secret)?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> anyhow::Result<()> {
+        match keyring::Entry::new(&self.service, key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Secrets encrypted into a file next to the app preferences, for machines without a keyring.
+/// The key lives in a separate file readable only by the user, so this keeps the token out of
+/// backups and casual reads of the credentials file but not away from the user's own account.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    key_path: PathBuf,
+    /// Serializes read-modify-write of the file
+    lock: Mutex<()>,
+}
+
+impl EncryptedFileStore {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            path: dir.join(CREDENTIALS_FILENAME),
+            key_path: dir.join(CREDENTIALS_KEY_FILENAME),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn cipher(&self) -> anyhow::Result<XChaCha20Poly1305> {
+        let key = match fs::read(&self.key_path) {
+            Ok(key) if key.len() == 32 => key,
+            Ok(_) => anyhow::bail!("{} is damaged", self.key_path.display()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let mut key = vec![0u8; 32];
+                rand::thread_rng().fill_bytes(&mut key);
+                write_private(&self.key_path, &key)?;
+                key
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(XChaCha20Poly1305::new(Key::from_slice(&key)))
+    }
+
+    /// Encrypted secrets by name, each a hex nonce followed by hex ciphertext
+    fn load(&self) -> anyhow::Result<HashMap<String, String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, secrets: &HashMap<String, String>) -> anyhow::Result<()> {
+        write_private(&self.path, serde_json::to_string_pretty(secrets)?.as_bytes())
+    }
+}
+
+impl CredentialStore for EncryptedFileStore {
+    fn name(&self) -> &'static str {
+        "encrypted file"
+    }
+
+    fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        let _guard = self.lock.lock().unwrap();
+        let Some(encrypted) = self.load()?.remove(key) else {
+            return Ok(None);
+        };
+
+        let bytes = from_hex(&encrypted).ok_or_else(|| anyhow::anyhow!("Stored secret '{}' is damaged", key))?;
+        if bytes.len() < 24 {
+            anyhow::bail!("Stored secret '{}' is damaged", key);
+        }
+
+        let (nonce, ciphertext) = bytes.split_at(24);
+        let plaintext = self
+            .cipher()?
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow::anyhow!("Couldn't decrypt stored secret '{}'", key))?;
+
+        Ok(Some(String::from_utf8(plaintext)?))
+    }
+
+    fn set(&self, key: &str, secret: &str) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+
+        let mut nonce = [0u8; 24];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher()?
+            .encrypt(XNonce::from_slice(&nonce), secret.as_bytes())
+            .map_err(|_| anyhow::anyhow!("Couldn't encrypt secret '{}'", key))?;
+
+        let mut secrets = self.load()?;
+        secrets.insert(key.to_string(), to_hex(&[&nonce[..], &ciphertext].concat()));
+        self.save(&secrets)
+    }
+
+    fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+
+        let mut secrets = self.load()?;
+        if secrets.remove(key).is_some() {
+            self.save(&secrets)?;
+        }
+        Ok(())
+    }
+}
+
+/// Secrets kept only for the life of the process, for tests and automation runs
+#[derive(Default)]
+pub struct MemoryStore {
+    secrets: Mutex<HashMap<String, String>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CredentialStore for MemoryStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.secrets.lock().unwrap().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, secret: &str) -> anyhow::Result<()> {
+        self.secrets.lock().unwrap().insert(key.to_string(), secret.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> anyhow::Result<()> {
+        self.secrets.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// The store the app uses, chosen on first use from `STUNTS_CREDENTIAL_STORE` and what's available
+pub fn credential_store() -> Arc<dyn CredentialStore> {
+    static STORE: OnceLock<Arc<dyn CredentialStore>> = OnceLock::new();
+
+    STORE
+        .get_or_init(|| {
+            let file_store = || -> Arc<dyn CredentialStore> {
+                let dir = get_ground_truth_dir().expect("Couldn't get CommonOS directory");
+                Arc::new(EncryptedFileStore::new(&dir))
+            };
+
+            let store: Arc<dyn CredentialStore> = match std::env::var(CREDENTIAL_STORE_ENV).unwrap_or_default().trim() {
+                "memory" => Arc::new(MemoryStore::new()),
+                "file" => file_store(),
+                "keyring" => Arc::new(KeyringStore::new(KEYRING_SERVICE)),
+                _ => {
+                    let keyring = KeyringStore::new(KEYRING_SERVICE);
+                    if keyring.is_available() {
+                        Arc::new(keyring)
+                    } else {
+                        println!("No keyring available, storing credentials in an encrypted file");
+                        file_store()
+                    }
+                }
+            };
+
+            println!("Using the {} credential store", store.name());
+            store
+        })
+        .clone()
+}
+
+/// The store in use plus the keyring and encrypted file, since earlier runs may have picked
+/// another one and a secret left there would outlive deleting it
+pub fn credential_stores() -> Vec<Arc<dyn CredentialStore>> {
+    let current = credential_store();
+    let mut stores = Vec::new();
+
+    let keyring = KeyringStore::new(KEYRING_SERVICE);
+    if current.name() != keyring.name() && keyring.is_available() {
+        stores.push(Arc::new(keyring) as Arc<dyn CredentialStore>);
+    }
+
+    let dir = get_ground_truth_dir().expect("Couldn't get CommonOS directory");
+    let file_store = EncryptedFileStore::new(&dir);
+    if current.name() != file_store.name() {
+        stores.push(Arc::new(file_store));
+    }
+
+    stores.insert(0, current);
+    stores
+}
+
+/// Write a file only the current user can read
+pub(crate) fn write_private(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies to new files
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+
+    options.open(path)?.write_all(contents)?;
+    Ok(())
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(store: &dyn CredentialStore) {
+        assert_eq!(store.get("auth_token").unwrap(), None);
+
+        store.set("auth_token", "first").unwrap();
+        store.set("auth_token", "second").unwrap();
+        store.set("other", "kept").unwrap();
+        assert_eq!(store.get("auth_token").unwrap().as_deref(), Some("second"));
+
+        store.delete("auth_token").unwrap();
+        assert_eq!(store.get("auth_token").unwrap(), None);
+        assert_eq!(store.get("other").unwrap().as_deref(), Some("kept"));
+
+        // Deleting what isn't there succeeds
+        store.delete("auth_token").unwrap();
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("stunts-credentials-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn memory_store_round_trip() {
+        round_trip(&MemoryStore::new());
+    }
+
+    #[test]
+    fn file_store_round_trip() {
+        let dir = temp_dir();
+        round_trip(&EncryptedFileStore::new(&dir));
+
+        // Secrets outlive the store and aren't written in the clear
+        let store = EncryptedFileStore::new(&dir);
+        store.set("auth_token", "secret-token").unwrap();
+        let reopened = EncryptedFileStore::new(&dir);
+        assert_eq!(reopened.get("auth_token").unwrap().as_deref(), Some("secret-token"));
+        let file = fs::read_to_string(dir.join(CREDENTIALS_FILENAME)).unwrap();
+        assert!(!file.contains("secret-token"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn file_store_rejects_another_key() {
+        let dir = temp_dir();
+        EncryptedFileStore::new(&dir).set("auth_token", "secret-token").unwrap();
+        write_private(&dir.join(CREDENTIALS_KEY_FILENAME), &[7u8; 32]).unwrap();
+
+        assert!(EncryptedFileStore::new(&dir).get("auth_token").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_store_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir();
+        EncryptedFileStore::new(&dir).set("auth_token", "secret-token").unwrap();
+        for name in [CREDENTIALS_FILENAME, CREDENTIALS_KEY_FILENAME] {
+            let mode = fs::metadata(dir.join(name)).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "{}", name);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,471 @@
+//! What the user's plan allows, worked out from the subscription the backend reports.
+//!
+//! These checks are advisory. The cached subscription and the generation counter are plain files
+//! in the app data folder, so anyone can edit them; the backend remains the authority for anything
+//! it serves, such as cloud motion generation.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use stunts_engine::saved_state::get_ground_truth_dir;
+
+use super::utilities::{PlanLimits, SubscriptionDetails};
+
+const ENTITLEMENT_SETTINGS_FILENAME: &str = "entitlements.json";
+const SUBSCRIPTION_CACHE_FILENAME: &str = "subscription.json";
+const GENERATION_USAGE_FILENAME: &str = "generation_usage.json";
+
+/// Projects a lapsed subscription keeps, as on the free plan
+const FREE_MAX_PROJECTS: usize = 3;
+
+/// Largest shorter side of a lapsed subscription's exports, as on the free plan
+const FREE_MAX_EXPORT_RESOLUTION: u32 = 720;
+
+/// How lapsed subscriptions are treated, stored next to the app preferences
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntitlementSettings {
+    /// Limits once a subscription has lapsed and its grace period is over, and while no
+    /// subscription is known; the free plan's by default
+    pub lapsed_limits: PlanLimits,
+    /// Days a subscription keeps its plan's features after its period ends unpaid or unrenewed
+    pub grace_period_days: i64,
+    /// Drawn in the corner of exports when the plan asks for a watermark
+    pub watermark_text: String,
+}
+
+impl Default for EntitlementSettings {
+    fn default() -> Self {
+        Self {
+            // Cloud generations are left to the backend, which knows the subscription lapsed
+            lapsed_limits: PlanLimits {
+                max_projects: Some(FREE_MAX_PROJECTS),
+                max_export_resolution: Some(FREE_MAX_EXPORT_RESOLUTION),
+                generation_quota: None,
+                watermark: true,
+            },
+            grace_period_days: 7,
+            watermark_text: "Stunts".to_string(),
+        }
+    }
+}
+
+impl EntitlementSettings {
+    fn path() -> PathBuf {
+        get_ground_truth_dir()
+            .expect("Couldn't get CommonOS directory")
+            .join(ENTITLEMENT_SETTINGS_FILENAME)
+    }
+
+    /// The settings as read on first use; edits to the file apply from the next start
+    pub fn current() -> &'static EntitlementSettings {
+        static SETTINGS: OnceLock<EntitlementSettings> = OnceLock::new();
+        SETTINGS.get_or_init(EntitlementSettings::load)
+    }
+
+    pub fn load() -> EntitlementSettings {
+        match fs::read_to_string(Self::path()) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                println!("Failed to parse entitlement settings, using defaults: {}", e);
+                EntitlementSettings::default()
+            }),
+            Err(_) => EntitlementSettings::default(),
+        }
+    }
+}
+
+/// What the current plan allows
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entitlements {
+    /// Shown in messages, e.g. "Pro"
+    pub plan_name: String,
+    /// Projects that may exist at once, `None` for no limit
+    pub max_projects: Option<usize>,
+    /// Largest shorter side of an export in pixels (720 for 720p), `None` for any size
+    pub max_export_resolution: Option<u32>,
+    /// Cloud motion generations per period, `None` for no limit
+    pub generation_quota: Option<u32>,
+    /// Text burned into the corner of exports, `None` for no watermark
+    pub watermark: Option<String>,
+    /// Generations are counted against this: the billing period's end date, or the month without one
+    pub period: String,
+    /// Something to tell the user about their plan, such as an upcoming cancellation
+    pub notice: Option<String>,
+}
+
+impl Entitlements {
+    pub fn with_limits(
+        plan_name: String,
+        limits: &PlanLimits,
+        watermark_text: &str,
+        period: String,
+        notice: Option<String>,
+    ) -> Self {
+        Self {
+            plan_name,
+            max_projects: limits.max_projects,
+            max_export_resolution: limits.max_export_resolution,
+            generation_quota: limits.generation_quota,
+            watermark: limits.watermark.then(|| watermark_text.to_string()),
+            period,
+            notice,
+        }
+    }
+
+    /// No limits, for headless runs on machines that never saw a plan, such as a build server
+    pub fn unrestricted(now: DateTime<Utc>) -> Self {
+        Self::with_limits(String::new(), &PlanLimits::default(), "", now.format("%Y-%m").to_string(), None)
+    }
+
+    /// Work out what `subscription` allows at `now`. An active subscription gets the limits the
+    /// backend sets on its plan. One that isn't active, or whose period has passed without a
+    /// renewal being seen, keeps them for the configured grace period after the period ends and
+    /// then gets the configured lapsed limits. Without a subscription, e.g. working offline
+    /// before one was ever fetched, the lapsed limits apply too.
+    pub fn for_subscription(
+        subscription: Option<&SubscriptionDetails>,
+        settings: &EntitlementSettings,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let Some(subscription) = subscription else {
+            return Self::with_limits(
+                "free".to_string(),
+                &settings.lapsed_limits,
+                &settings.watermark_text,
+                now.format("%Y-%m").to_string(),
+                None,
+            );
+        };
+
+        let plan = subscription.plan.clone().unwrap_or_default();
+        let plan_name = plan.name.clone().unwrap_or_else(|| "paid".to_string());
+        let status = subscription.subscription_status.to_uppercase();
+        let is_paying = matches!(status.as_str(), "ACTIVE" | "TRIALING");
+
+        let period_end = subscription.current_period_end;
+        let grace_end = period_end.map(|end| end + chrono::Duration::days(settings.grace_period_days.max(0)));
+        let period = period_end
+            .map(|end| end.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| now.format("%Y-%m").to_string());
+
+        if is_paying && period_end.map_or(true, |end| now <= end) {
+            let notice = match period_end {
+                Some(end) if subscription.cancel_at_period_end => Some(format!(
+                    "Your {} plan ends on {}",
+                    plan_name,
+                    format_date(end)
+                )),
+                _ => None,
+            };
+            return Self::with_limits(plan_name, &plan.limits, &settings.watermark_text, period, notice);
+        }
+
+        match grace_end {
+            Some(grace_end) if now <= grace_end => {
+                let notice = if is_paying {
+                    // Seen when working offline from a cached subscription
+                    format!(
+                        "Couldn't confirm your {} plan renewed, go online before {} to keep its features",
+                        plan_name,
+                        format_date(grace_end)
+                    )
+                } else {
+                    format!(
+                        "Your {} plan is {}, its features stay on until {}",
+                        plan_name,
+                        status.to_lowercase().replace('_', " "),
+                        format_date(grace_end)
+                    )
+                };
+                Self::with_limits(plan_name, &plan.limits, &settings.watermark_text, period, Some(notice))
+            }
+            _ => {
+                let limits = describe_limits(&settings.lapsed_limits);
+                let notice = if limits.is_empty() {
+                    format!("Your {} plan has lapsed", plan_name)
+                } else {
+                    format!("Your {} plan has lapsed: {}", plan_name, limits)
+                };
+                Self::with_limits(
+                    format!("lapsed {}", plan_name),
+                    &settings.lapsed_limits,
+                    &settings.watermark_text,
+                    now.format("%Y-%m").to_string(),
+                    Some(notice),
+                )
+            }
+        }
+    }
+
+    /// Whether another project may be created next to `existing` ones
+    pub fn check_project_count(&self, existing: usize) -> Result<(), String> {
+        match self.max_projects {
+            Some(max) if existing >= max => Err(format!(
+                "The {} plan allows up to {} projects, delete one or upgrade to create another",
+                self.plan_name, max
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether an export of `width` by `height` is allowed
+    pub fn check_export_resolution(&self, width: u32, height: u32) -> Result<(), String> {
+        match self.max_export_resolution {
+            Some(max) if width.min(height) > max => Err(format!(
+                "The {} plan exports up to {}p, pick a smaller resolution or upgrade",
+                self.plan_name, max
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether `requested` more cloud generations fit in the quota after `used`
+    pub fn check_generations(&self, used: u32, requested: u32) -> Result<(), String> {
+        match self.generation_quota {
+            Some(quota) if used.saturating_add(requested) > quota => Err(format!(
+                "{} of the {} plan's {} cloud motion generations this period are used",
+                used.min(quota),
+                self.plan_name,
+                quota
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Cloud motion generations made in the current period
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationUsage {
+    pub period: String,
+    pub count: u32,
+}
+
+impl GenerationUsage {
+    fn path() -> PathBuf {
+        get_ground_truth_dir()
+            .expect("Couldn't get CommonOS directory")
+            .join(GENERATION_USAGE_FILENAME)
+    }
+
+    pub fn load() -> GenerationUsage {
+        match fs::read_to_string(Self::path()) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                println!("Failed to parse generation usage, starting from zero: {}", e);
+                GenerationUsage::default()
+            }),
+            Err(_) => GenerationUsage::default(),
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Generations counted in `period`; a new period starts from zero
+    pub fn used(&self, period: &str) -> u32 {
+        if self.period == period {
+            self.count
+        } else {
+            0
+        }
+    }
+}
+
+/// Count `count` successful cloud generations against `period`
+pub fn record_generations(period: &str, count: u32) {
+    // Variants finish on different tasks, so keep their read-modify-writes apart
+    static USAGE_LOCK: Mutex<()> = Mutex::new(());
+    let _guard = USAGE_LOCK.lock().unwrap();
+
+    let mut usage = GenerationUsage::load();
+    if usage.period != period {
+        usage = GenerationUsage {
+            period: period.to_string(),
+            count: 0,
+        };
+    }
+    usage.count += count;
+
+    if let Err(e) = usage.save() {
+        println!("Failed to save generation usage: {}", e);
+    }
+}
+
+fn subscription_cache_path() -> PathBuf {
+    get_ground_truth_dir()
+        .expect("Couldn't get CommonOS directory")
+        .join(SUBSCRIPTION_CACHE_FILENAME)
+}
+
+/// Remember the last subscription the backend reported, so offline sessions keep their plan
+pub fn cache_subscription(subscription: &SubscriptionDetails) {
+    let result = serde_json::to_string_pretty(subscription)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(subscription_cache_path(), json).map_err(|e| e.to_string()));
+
+    if let Err(e) = result {
+        println!("Failed to cache subscription details: {}", e);
+    }
+}
+
+/// The subscription last reported by the backend, if any
+pub fn cached_subscription() -> Option<SubscriptionDetails> {
+    let json = fs::read_to_string(subscription_cache_path()).ok()?;
+    serde_json::from_str(&json)
+        .map_err(|e| println!("Failed to parse cached subscription details: {}", e))
+        .ok()
+}
+
+/// Forget the cached subscription, e.g. when signing out
+pub fn clear_cached_subscription() {
+    let path = subscription_cache_path();
+    if path.exists() {
+        if let Err(e) = fs::remove_file(&path) {
+            println!("Failed to remove cached subscription details: {}", e);
+        }
+    }
+}
+
+/// "up to 3 projects, 720p exports, ..." for the limits that are set, empty without any
+fn describe_limits(limits: &PlanLimits) -> String {
+    let mut parts = Vec::new();
+    if let Some(max) = limits.max_projects {
+        parts.push(format!("up to {} projects", max));
+    }
+    if let Some(max) = limits.max_export_resolution {
+        parts.push(format!("exports up to {}p", max));
+    }
+    if limits.watermark {
+        parts.push("watermarked exports".to_string());
+    }
+    if let Some(quota) = limits.generation_quota {
+        parts.push(format!("{} cloud motion generations a month", quota));
+    }
+    parts.join(", ")
+}
+
+pub fn format_date(date: DateTime<Utc>) -> String {
+    date.format("%B %-d, %Y").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::utilities::Plan;
+
+    fn subscription(status: &str, period_end: DateTime<Utc>, limits: PlanLimits) -> SubscriptionDetails {
+        SubscriptionDetails {
+            subscription_status: status.to_string(),
+            current_period_end: Some(period_end),
+            plan: Some(Plan {
+                id: None,
+                name: Some("Starter".to_string()),
+                description: None,
+                limits,
+            }),
+            cancel_at_period_end: false,
+        }
+    }
+
+    fn capped() -> PlanLimits {
+        PlanLimits {
+            max_projects: Some(5),
+            max_export_resolution: Some(720),
+            generation_quota: Some(10),
+            watermark: true,
+        }
+    }
+
+    #[test]
+    fn an_unknown_subscription_gets_the_free_plan_limits() {
+        let entitlements = Entitlements::for_subscription(None, &EntitlementSettings::default(), Utc::now());
+
+        assert_eq!(entitlements.max_projects, Some(FREE_MAX_PROJECTS));
+        assert!(entitlements.check_export_resolution(1280, 720).is_ok());
+        assert!(entitlements.check_export_resolution(3840, 2160).is_err());
+        assert_eq!(entitlements.watermark.as_deref(), Some("Stunts"));
+    }
+
+    #[test]
+    fn headless_runs_without_a_plan_are_unrestricted() {
+        let entitlements = Entitlements::unrestricted(Utc::now());
+
+        assert_eq!(entitlements.max_projects, None);
+        assert!(entitlements.check_export_resolution(3840, 2160).is_ok());
+        assert_eq!(entitlements.watermark, None);
+    }
+
+    #[test]
+    fn active_plans_use_the_backend_limits() {
+        let now = Utc::now();
+        let active = subscription("ACTIVE", now + chrono::Duration::days(10), capped());
+        let entitlements = Entitlements::for_subscription(Some(&active), &EntitlementSettings::default(), now);
+
+        assert!(entitlements.check_project_count(4).is_ok());
+        assert!(entitlements.check_project_count(5).is_err());
+        assert!(entitlements.check_export_resolution(1280, 720).is_ok());
+        assert!(entitlements.check_export_resolution(1920, 1080).is_err());
+        assert!(entitlements.check_generations(9, 1).is_ok());
+        assert!(entitlements.check_generations(9, 2).is_err());
+        assert!(entitlements.watermark.is_some());
+    }
+
+    #[test]
+    fn lapsed_plans_keep_their_features_through_the_grace_period() {
+        let now = Utc::now();
+        let settings = EntitlementSettings {
+            lapsed_limits: capped(),
+            ..EntitlementSettings::default()
+        };
+
+        let past_due = subscription("PAST_DUE", now - chrono::Duration::days(3), PlanLimits::default());
+        let in_grace = Entitlements::for_subscription(Some(&past_due), &settings, now);
+        assert_eq!(in_grace.max_projects, None);
+        assert!(in_grace.notice.is_some());
+
+        let lapsed = subscription("PAST_DUE", now - chrono::Duration::days(30), PlanLimits::default());
+        let after_grace = Entitlements::for_subscription(Some(&lapsed), &settings, now);
+        assert_eq!(after_grace.max_projects, Some(5));
+        assert!(after_grace.watermark.is_some());
+    }
+
+    #[test]
+    fn lapsed_plans_get_the_free_plan_limits_by_default() {
+        let now = Utc::now();
+
+        for status in ["CANCELED", "PAST_DUE", "ACTIVE"] {
+            let lapsed = subscription(status, now - chrono::Duration::days(30), PlanLimits::default());
+            let entitlements = Entitlements::for_subscription(Some(&lapsed), &EntitlementSettings::default(), now);
+
+            assert!(entitlements.check_project_count(FREE_MAX_PROJECTS - 1).is_ok(), "{}", status);
+            assert!(entitlements.check_project_count(FREE_MAX_PROJECTS).is_err(), "{}", status);
+            assert!(entitlements.check_export_resolution(1280, 720).is_ok(), "{}", status);
+            assert!(entitlements.check_export_resolution(1920, 1080).is_err(), "{}", status);
+            assert!(entitlements.watermark.is_some(), "{}", status);
+            assert!(entitlements.notice.is_some(), "{}", status);
+        }
+    }
+
+    #[test]
+    fn lapsed_limits_can_be_turned_off() {
+        let now = Utc::now();
+        let settings = EntitlementSettings {
+            lapsed_limits: PlanLimits::default(),
+            ..EntitlementSettings::default()
+        };
+        let lapsed = subscription("CANCELED", now - chrono::Duration::days(30), capped());
+        let entitlements = Entitlements::for_subscription(Some(&lapsed), &settings, now);
+
+        assert_eq!(entitlements.max_projects, None);
+        assert!(entitlements.check_export_resolution(1920, 1080).is_ok());
+    }
+}
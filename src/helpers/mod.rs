@@ -1,2 +1,6 @@
+pub mod credentials;
+pub mod entitlements;
+pub mod preferences;
 pub mod projects;
+pub mod reveal;
 pub mod utilities;
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use stunts_engine::saved_state::get_ground_truth_dir;
+
+const PREFERENCES_FILENAME: &str = "preferences.json";
+
+/// App-wide user preferences, shared by every project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Show the exported file in the file manager when an export finishes
+    pub reveal_after_export: bool,
+    /// Send motion prompts to the cloud service; when off, motion is generated locally
+    pub cloud_motion_generation: bool,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            reveal_after_export: true,
+            cloud_motion_generation: true,
+        }
+    }
+}
+
+impl Preferences {
+    fn path() -> PathBuf {
+        get_ground_truth_dir()
+            .expect("Couldn't get CommonOS directory")
+            .join(PREFERENCES_FILENAME)
+    }
+
+    pub fn load() -> Preferences {
+        match fs::read_to_string(Self::path()) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                println!("Failed to parse preferences, using defaults: {}", e);
+                Preferences::default()
+            }),
+            Err(_) => Preferences::default(),
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
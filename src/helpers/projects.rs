@@ -1,7 +1,13 @@
 use stunts_engine::saved_state::{get_ground_truth_dir, load_projects_datafile};
+use stunts_engine::animations::Sequence;
+use stunts_engine::timelines::{SavedTimelineStateConfig, TimelineSequence, TrackType};
 use chrono::{DateTime, Local};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct ProjectInfo {
@@ -76,3 +82,70 @@ pub fn get_projects() -> Result<Vec<ProjectInfo>, Box<dyn std::error::Error>> {
 
     Ok(projects)
 }
+
+/// Directory holding a project's saved state, also used for per-project sidecar files
+pub fn get_project_dir(project_id: &str) -> PathBuf {
+    let sync_dir = get_ground_truth_dir().expect("Couldn't get CommonOS directory");
+    sync_dir.join("projects").join(project_id)
+}
+
+/// Read one of a project's JSON sidecar files, e.g. its export settings. A missing or
+/// unreadable file gives the default.
+pub fn load_project_file<T: DeserializeOwned + Default>(project_id: &str, filename: &str) -> T {
+    load_json_file(&get_project_dir(project_id).join(filename))
+}
+
+pub fn save_project_file<T: Serialize>(project_id: &str, filename: &str, value: &T) -> anyhow::Result<()> {
+    save_json_file(&get_project_dir(project_id).join(filename), value)
+}
+
+pub fn load_json_file<T: DeserializeOwned + Default>(path: &Path) -> T {
+    match fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            println!("Failed to parse {}, using defaults: {}", path.display(), e);
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+pub fn save_json_file<T: Serialize>(path: &Path, value: &T) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+pub fn get_project_name(project_id: &str) -> Option<String> {
+    let projects_datafile = load_projects_datafile().ok()?;
+
+    projects_datafile
+        .projects
+        .into_iter()
+        .find(|p| p.project_id == project_id)
+        .map(|p| p.project_name)
+}
+
+// Helper function to arrange sequences in series automatically
+pub fn arrange_sequences_in_series(sequences: &mut Vec<Sequence>) -> SavedTimelineStateConfig {
+    let mut timeline_sequences = Vec::new();
+    let mut current_start_time = 0;
+    
+    for (index, sequence) in sequences.iter().enumerate() {
+        timeline_sequences.push(TimelineSequence {
+            id: Uuid::new_v4().to_string(),
+            sequence_id: sequence.id.clone(),
+            start_time_ms: current_start_time,
+            track_type: TrackType::Video,
+        });
+        current_start_time += sequence.duration_ms;
+        
+        println!("Arranged sequence {} '{}' at time {}ms", index + 1, sequence.name, current_start_time - sequence.duration_ms);
+    }
+    
+    SavedTimelineStateConfig {
+        timeline_sequences,
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::Path;
+use std::process::Command as ProcessCommand;
+
+/// Show a file or folder in the platform's file manager.
+/// Files are selected in their folder where the platform supports it, otherwise the folder is opened.
+pub fn reveal_in_file_manager(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("{} does not exist", path.display()));
+    }
+
+    reveal_platform(path)
+}
+
+#[cfg(target_os = "windows")]
+fn reveal_platform(path: &Path) -> Result<(), String> {
+    let mut command = ProcessCommand::new("explorer");
+
+    if path.is_file() {
+        // explorer expects "/select," and the path as a single argument
+        let mut select_arg = std::ffi::OsString::from("/select,");
+        select_arg.push(path.as_os_str());
+        command.arg(select_arg);
+    } else {
+        command.arg(path);
+    }
+
+    spawn(command, "explorer")
+}
+
+#[cfg(target_os = "macos")]
+fn reveal_platform(path: &Path) -> Result<(), String> {
+    let mut command = ProcessCommand::new("open");
+
+    if path.is_file() {
+        command.arg("-R");
+    }
+    command.arg(path);
+
+    spawn(command, "open")
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn reveal_platform(path: &Path) -> Result<(), String> {
+    // There's no portable "select this file" on Linux, so open the containing folder
+    let folder = if path.is_file() {
+        path.parent().unwrap_or(path)
+    } else {
+        path
+    };
+
+    let mut xdg_open = ProcessCommand::new("xdg-open");
+    xdg_open.arg(folder);
+
+    spawn(xdg_open, "xdg-open").or_else(|xdg_error| {
+        let mut gio = ProcessCommand::new("gio");
+        gio.arg("open").arg(folder);
+
+        spawn(gio, "gio").map_err(|gio_error| format!("{}; {}", xdg_error, gio_error))
+    })
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn reveal_platform(path: &Path) -> Result<(), String> {
+    Err(format!(
+        "Revealing {} is not supported on this platform",
+        path.display()
+    ))
+}
+
+fn spawn(mut command: ProcessCommand, program: &str) -> Result<(), String> {
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Couldn't run {}: {}", program, e))?;
+
+    // Wait for it elsewhere so it doesn't linger as a zombie
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
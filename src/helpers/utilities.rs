@@ -2,10 +2,12 @@
 // use floem::reactive::RwSignal;
 // use floem::reactive::SignalGet;
 // use floem::reactive::SignalUpdate;
-use reqwest::Client;
+use crate::api::{ApiClient, ApiError};
+use crate::helpers::entitlements::{cache_subscription, cached_subscription, EntitlementSettings, Entitlements};
 use serde::{Deserialize, Serialize};
 
 
+// Default backend, overridden by api.json or STUNTS_API_URL (see `ApiSettings`)
 #[cfg(feature = "production")]
 pub const API_URL: &str = "https://madebycommon.com";
 
@@ -25,14 +27,31 @@ pub struct AuthToken {
 //     pub is_authenticated: bool,
 // }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Plan {
     pub id: Option<String>,
     pub name: Option<String>,
     pub description: Option<String>,
+    /// Sent by `/api/subscription/details` as `plan.limits`, e.g.
+    /// `{"maxProjects": 3, "maxExportResolution": 720, "generationQuota": 50, "watermark": true}`.
+    /// Every key is optional; a plan without the object, as the backend sends today, has no limits.
+    #[serde(default)]
+    pub limits: PlanLimits,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// What a plan restricts; every limit is optional and absent means no limit
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PlanLimits {
+    pub max_projects: Option<usize>,
+    /// Largest shorter side of an export in pixels, e.g. 720
+    pub max_export_resolution: Option<u32>,
+    /// Cloud motion generations per billing period
+    pub generation_quota: Option<u32>,
+    pub watermark: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SubscriptionDetails {
     pub subscription_status: String,
@@ -47,39 +66,54 @@ pub struct AuthState {
     pub token: Option<AuthToken>,
     pub is_authenticated: bool,
     pub subscription: Option<SubscriptionDetails>,
+    /// Working without the backend: local projects and export only. A stored token is kept
+    /// in `token` so the session can be resumed, but it hasn't been checked.
+    pub offline: bool,
 }
 
 impl AuthState {
-    pub fn can_create_projects(&self) -> bool {
-        if !self.is_authenticated {
-            return false;
+    /// Signed in or working offline
+    pub fn has_local_access(&self) -> bool {
+        self.is_authenticated || self.offline
+    }
+
+    /// Whether cloud features such as motion generation may be used
+    pub fn cloud_available(&self) -> bool {
+        self.is_authenticated && !self.offline
+    }
+
+    /// The subscription the backend reported; offline, the one last seen online stands in
+    pub fn known_subscription(&self) -> Option<SubscriptionDetails> {
+        match (&self.subscription, self.offline) {
+            (None, true) => cached_subscription(),
+            (subscription, _) => subscription.clone(),
         }
+    }
 
-        return true;
+    /// What the plan allows
+    pub fn entitlements(&self) -> Entitlements {
+        Entitlements::for_subscription(
+            self.known_subscription().as_ref(),
+            EntitlementSettings::current(),
+            chrono::Utc::now(),
+        )
+    }
 
-        // match &self.subscription {
-        //     Some(sub) => matches!(sub.subscription_status.as_str(), "ACTIVE" | "TRIALING"),
-        //     None => false,
-        // }
+    /// Whether another project may be created next to `existing_projects`, and why not
+    pub fn can_create_projects(&self, existing_projects: usize) -> Result<(), String> {
+        if !self.has_local_access() {
+            return Err("Sign in or work offline to create projects".to_string());
+        }
+
+        self.entitlements().check_project_count(existing_projects)
     }
 }
 
 // Function to fetch subscription details
 pub async fn fetch_subscription_details(
     token: &str,
-) -> Result<SubscriptionDetails, Box<dyn std::error::Error>> {
-    let client = Client::new();
-
-    let response = client
-        .get(API_URL.to_owned() + &"/api/subscription/details")
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .await?;
-
-    if response.status().is_success() {
-        let details = response.json::<SubscriptionDetails>().await?;
-        Ok(details)
-    } else {
-        Err(response.text().await?.into())
-    }
+) -> Result<SubscriptionDetails, ApiError> {
+    let subscription = ApiClient::from_settings().subscription_details(token).await?;
+    cache_subscription(&subscription);
+    Ok(subscription)
 }
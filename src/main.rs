@@ -15,34 +15,25 @@ use tokio::sync::mpsc as tokio_mpsc;
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
-use stunts_engine::{
-    editor::{Viewport, WindowSize, Editor, Point, ObjectProperty},
-    capture::{get_sources, WindowInfo, MousePosition, SourceData},
-    export::exporter::{ExportProgress, Exporter},
-    timelines::{SavedTimelineStateConfig, TimelineSequence, TrackType},
-};
-use stunts_engine::polygon::{
-    PolygonConfig, SavedPoint, SavedPolygonConfig, SavedStroke, Stroke,
-};
-use stunts_engine::editor::rgb_to_wgpu;
-use stunts_engine::st_image::{SavedStImageConfig, StImageConfig};
-use stunts_engine::st_video::{SavedStVideoConfig, StVideoConfig};
-use stunts_engine::text_due::{SavedTextRendererConfig, TextRendererConfig};
+use stunts_engine::editor::{Viewport, WindowSize, Editor};
 use uuid::Uuid;
 use rand::Rng;
 use undo::Record;
-use stunts_engine::{
-    animations::{BackgroundFill, Sequence, ObjectType},
-};
-use std::fs;
-use stunts_engine::editor::wgpu_to_human;
-use keyring::Entry;
 use stunts_engine::saved_state::ProjectData;
 use chrono;
-use stunts_engine::saved_state::get_random_coords;
+use crate::helpers::preferences::Preferences;
+use crate::helpers::reveal::reveal_in_file_manager;
+use crate::api::AuthSession;
+use crate::helpers::credentials::{credential_store, credential_stores, CredentialStore};
 use crate::helpers::utilities::{AuthState, AuthToken};
 use anyhow::Result;
-use stunts_engine::saved_state::save_saved_state_raw;
+use crate::commands::{
+    AppSignals, Command, CommandContext, CommandDispatcher, GpuContext, MotionFormSignals, PendingCommands,
+    REGENERATE_ALL_LABEL,
+};
+use crate::export::{ExportJobUpdate, ExportProgress, ExportQueue};
+use crate::automation::{AutomationRequest, AutomationServer};
+use crate::motion::{MergeMode, MotionUpdate, MAX_VARIANTS};
 
 mod primary_canvas;
 mod pipeline;
@@ -53,281 +44,80 @@ mod event_handlers;
 mod text_properties;
 mod theme_sidebar;
 mod animation_ideas;
-
-#[derive(Debug, Clone)]
-enum Command {
-    AddSquarePolygon,
-    AddText,
-    AddImage { file_path: String },
-    AddVideo { file_path: String },
-    AddMotion,
-    SubmitMotionForm {
-        description: String,
-        position: String,
-        scale: String,
-        rotation: String,
-        opacity: String,
-        delay: String,
-        duration: String,
-    },
-    UpdateTextProperty {
-        // text_id: String,
-        property_key: String,
-        property_value: String,
-    },
-    TogglePlay,
-    ShowCaptureSources,
-    StartScreenCapture { hwnd: usize, width: usize, height: usize },
-    StopScreenCapture,
-    Export {
-        progress_tx: tokio_mpsc::UnboundedSender<ExportProgress>,
-    },
-    // Authentication commands
-    SubmitSignIn { email: String, password: String },
-    SignOut,
-    // CheckAuthentication,
-    // Project management commands
-    LoadProjects,
-    SelectProject { project_id: String },
-    CreateProject { name: String },
-    CreateSequence { name: String, project_id: String },
-    SelectSequence { sequence_id: String },
-    LoadSequences,
-    ApplyTheme { theme: [f64; 5] },
-    RedisplayCanvas,
-}
-
-// Authentication and Project Management structs  
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct JwtData {
-    token: String,
-    expiry: i64, // Unix timestamp
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct AuthResponse {
-    #[serde(rename = "jwtData")]
-    jwt_data: JwtData,
-}
-
-// Intermediate structs to parse the API response format
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
-#[serde(default)]
-struct ApiAnimationData {
-    pub id: String,
-    pub duration: i32, // milliseconds as i32
-    pub properties: Vec<ApiAnimationProperty>,
-}
-
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
-#[serde(default)]
-struct ApiAnimationProperty {
-    pub name: String,
-    pub keyframes: Vec<ApiKeyframe>,
-}
-
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
-#[serde(default)]
-struct ApiKeyframe {
-    pub time: i32, // milliseconds as i32
-    pub value: ApiKeyframeValue,
-    pub easing: String,
-}
-
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
-#[serde(untagged)]
-enum ApiKeyframeValue {
-    Position { Position: ApiPosition },
-    Scale { Scale: i32 },
-    Opacity { Opacity: i32 },
-    Rotation { Rotation: i32 },
-}
-
-#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
-#[serde(default)]
-struct ApiPosition {
-    pub x: i32,
-    pub y: i32,
-}
-
-// Default implementations
-impl Default for ApiAnimationData {
-    fn default() -> Self {
-        Self {
-            id: String::new(),
-            duration: 0,
-            properties: Vec::new(),
-        }
-    }
-}
-
-impl Default for ApiAnimationProperty {
-    fn default() -> Self {
-        Self {
-            name: String::new(),
-            keyframes: Vec::new(),
-        }
-    }
-}
-
-impl Default for ApiKeyframe {
-    fn default() -> Self {
-        Self {
-            time: 0,
-            value: ApiKeyframeValue::Position { Position: ApiPosition::default() },
-            easing: "Linear".to_string(),
-        }
-    }
-}
-
-impl Default for ApiPosition {
-    fn default() -> Self {
-        Self { x: 0, y: 0 }
-    }
-}
-
-// Conversion functions
-impl ApiAnimationData {
-    fn to_animation_data(self, polygon_id: String, object_type: ObjectType) -> stunts_engine::animations::AnimationData {
-        use stunts_engine::animations::{AnimationData, AnimationProperty, UIKeyframe, KeyframeValue, EasingType};
-        use stunts_engine::editor::PathType;
-        
-        // Convert API properties to AnimationProperty
-        let properties = self.properties.into_iter().map(|api_prop| {
-            let keyframes = api_prop.keyframes.into_iter().map(|api_keyframe| {
-                let value = match api_keyframe.value {
-                    ApiKeyframeValue::Position { Position: pos } => {
-                        KeyframeValue::Position([pos.x, pos.y])
-                    }
-                    ApiKeyframeValue::Scale { Scale: scale } => {
-                        KeyframeValue::Scale(scale)
-                    }
-                    ApiKeyframeValue::Opacity { Opacity: opacity } => {
-                        KeyframeValue::Opacity(opacity)
-                    }
-                    ApiKeyframeValue::Rotation { Rotation: rotation } => {
-                        KeyframeValue::Rotation(rotation)
-                    }
-                };
-                
-                let easing = match api_keyframe.easing.as_str() {
-                    "EaseIn" => EasingType::EaseIn,
-                    "EaseOut" => EasingType::EaseOut,
-                    "EaseInOut" => EasingType::EaseInOut,
-                    _ => EasingType::Linear,
-                };
-                
-                UIKeyframe {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    time: Duration::from_millis(api_keyframe.time as u64),
-                    value,
-                    easing,
-                    path_type: PathType::Linear,
-                    key_type: stunts_engine::animations::KeyType::Frame,
-                }
-            }).collect();
-            
-            AnimationProperty {
-                name: api_prop.name,
-                property_path: String::new(),
-                children: Vec::new(),
-                keyframes,
-                depth: 0,
-            }
-        }).collect();
-        
-        AnimationData {
-            id: self.id,
-            object_type,
-            polygon_id,
-            duration: Duration::from_millis(self.duration as u64),
-            start_time_ms: 0,
-            properties,
-            position: [0, 0],
-        }
-    }
-}
+mod cli;
+mod export;
+mod export_settings_panel;
+mod export_progress_panel;
+mod motion_candidates_panel;
+mod motion_generation_panel;
+mod account_panel;
+mod commands;
+mod automation;
+mod api;
+mod motion;
 
 fn split_format_string(input: &str) -> Vec<&str> {
     input.split(&['-', 'x'][..]).collect()
 }
 
-// Authentication helper functions using keyring for token storage
-fn store_auth_token(token: &AuthToken) -> anyhow::Result<()> {
-    let entry = Entry::new("stunts-native", "auth_token")?;
+/// Name the auth token is stored under in the credential store
+const AUTH_TOKEN_KEY: &str = "auth_token";
+
+// Authentication helper functions using the credential store for token storage
+fn store_auth_token(store: &dyn CredentialStore, token: &AuthToken) -> anyhow::Result<()> {
     let token_json = serde_json::to_string(token)?;
-    entry.set_password(&token_json)?;
-    Ok(())
+    store.set(AUTH_TOKEN_KEY, &token_json)
 }
 
-fn get_stored_auth_token() -> Option<AuthToken> {
+fn get_stored_auth_token(store: &dyn CredentialStore) -> Option<AuthToken> {
     println!("get_stored_auth_token");
-    match Entry::new("stunts-native", "auth_token") {
-        Ok(entry) => {
-            println!("Entry created successfully");
-            match entry.get_password() {
-                Ok(token_json) => {
-                    println!("Got password from keyring, length: {}", token_json.len());
-                    match serde_json::from_str::<AuthToken>(&token_json) {
-                        Ok(token) => {
-                            // Check if token is expired
-                            if let Some(expiry) = token.expiry {
-                                let is_valid = expiry > chrono::Utc::now();
-                                println!("Token expiry check: expired = {}, expiry = {:?}", !is_valid, expiry);
-                                if is_valid {
-                                    println!("Returning valid token");
-                                    return Some(token);
-                                } else {
-                                    println!("Token is expired");
-                                }
-                            } else {
-                                println!("Token has no expiry, returning it");
-                                return Some(token);
-                            }
-                        }
-                        Err(e) => {
-                            println!("Failed to parse token JSON: {}", e);
-                            println!("Raw token data: {}", token_json);
+    match store.get(AUTH_TOKEN_KEY) {
+        Ok(Some(token_json)) => {
+            println!("Got token from the {} credential store, length: {}", store.name(), token_json.len());
+            match serde_json::from_str::<AuthToken>(&token_json) {
+                Ok(token) => {
+                    // Check if token is expired
+                    if let Some(expiry) = token.expiry {
+                        let is_valid = expiry > chrono::Utc::now();
+                        println!("Token expiry check: expired = {}, expiry = {:?}", !is_valid, expiry);
+                        if is_valid {
+                            println!("Returning valid token");
+                            return Some(token);
+                        } else {
+                            println!("Token is expired");
                         }
+                    } else {
+                        println!("Token has no expiry, returning it");
+                        return Some(token);
                     }
                 }
                 Err(e) => {
-                    println!("Failed to get password from keyring: {}", e);
+                    println!("Failed to parse token JSON: {}", e);
+                    println!("Raw token data: {}", token_json);
                 }
             }
         }
+        Ok(None) => {
+            println!("No token in the {} credential store", store.name());
+        }
         Err(e) => {
-            println!("Failed to create keyring entry: {}", e);
+            println!("Failed to read the {} credential store: {}", store.name(), e);
         }
     }
     println!("Returning None - no valid token found");
     None
 }
 
-fn clear_stored_auth_token() -> anyhow::Result<()> {
-    let entry = Entry::new("stunts-native", "auth_token")?;
-    // entry.delete_credential()?; // how to delete a password?
-    Ok(())
+fn clear_stored_auth_token(store: &dyn CredentialStore) -> anyhow::Result<()> {
+    store.delete(AUTH_TOKEN_KEY)
 }
 
-async fn authenticate_user(email: &str, password: &str) -> anyhow::Result<AuthResponse> {
-    let client = reqwest::Client::new();
-    let auth_data = serde_json::json!({
-        "email": email,
-        "password": password
-    });
-    
-    let response = client
-        .post("http://localhost:3000/api/auth/login")
-        .json(&auth_data)
-        .send()
-        .await?;
-    
-    if response.status().is_success() {
-        let auth_response: AuthResponse = response.json().await?;
-        Ok(auth_response)
-    } else {
-        Err(anyhow::anyhow!("Authentication failed: {}", response.status()))
+/// Remove the token from every credential store, not just the one in use
+fn clear_auth_token_everywhere() {
+    for store in credential_stores() {
+        if let Err(e) = clear_stored_auth_token(&*store) {
+            println!("Failed to remove the stored auth token from the {} credential store: {}", store.name(), e);
+        }
     }
 }
 
@@ -348,30 +138,35 @@ fn create_local_project(name: &str) -> anyhow::Result<ProjectData> {
     })
 }
 
-// Helper function to arrange sequences in series automatically
-fn arrange_sequences_in_series(sequences: &mut Vec<Sequence>) -> SavedTimelineStateConfig {
-    let mut timeline_sequences = Vec::new();
-    let mut current_start_time = 0;
-    
-    for (index, sequence) in sequences.iter().enumerate() {
-        timeline_sequences.push(TimelineSequence {
-            id: Uuid::new_v4().to_string(),
-            sequence_id: sequence.id.clone(),
-            start_time_ms: current_start_time,
-            track_type: TrackType::Video,
-        });
-        current_start_time += sequence.duration_ms;
-        
-        println!("Arranged sequence {} '{}' at time {}ms", index + 1, sequence.name, current_start_time - sequence.duration_ms);
-    }
-    
-    SavedTimelineStateConfig {
-        timeline_sequences,
-    }
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut editor_args = cli::EditorArgs::default();
+
+    // Headless subcommands (e.g. `export`) run without opening the editor window
+    match cli::parse_args(std::env::args().skip(1)) {
+        Ok(Some(cli::CliCommand::Export(export_args))) => {
+            let exit_code = cli::run_headless_export(export_args).await;
+            std::process::exit(exit_code);
+        }
+        Ok(Some(cli::CliCommand::Editor(args))) => editor_args = args,
+        Ok(Some(cli::CliCommand::MockApi { port })) => {
+            if let Err(e) = api::mock::run_mock_server(port).await {
+                eprintln!("Mock API failed: {}", e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Ok(Some(cli::CliCommand::Help)) => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    }
+
     println!("Starting Stunts Native...");
 
     // Initialize authentication state
@@ -379,6 +174,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         token: None,
         is_authenticated: false,
         subscription: None,
+        offline: false,
     };
 
     let auth_state = Signal::new(auth_state.clone());
@@ -387,11 +183,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut selected_project: Option<ProjectData> = None;
     
     // Check for stored authentication token
-    if let Some(stored_token) = get_stored_auth_token() {
+    if let Some(stored_token) = get_stored_auth_token(&*credential_store()) {
         let new_auth_state = AuthState {
             token: Some(stored_token.clone()),
             is_authenticated: true,
             subscription: None,
+            offline: false,
         };
 
         auth_state.set(new_auth_state);
@@ -408,6 +205,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     token: Some(stored_token.clone()),
                     is_authenticated: true,
                     subscription: Some(subscription),
+                    offline: false,
                 };
 
                 auth_state.set(new_auth_state);
@@ -423,18 +221,41 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 }
             }
-            Err(e) => {
+            Err(e) if e.is_unauthorized() => {
                 println!("Failed to validate stored token: {}", e);
                 // Clear invalid token
-                let _ = clear_stored_auth_token();
+                let _ = clear_stored_auth_token(&*credential_store());
                 let new_auth_state = AuthState {
                     token: None,
                     is_authenticated: false,
                     subscription: None,
+                    offline: false,
                 };
 
                 auth_state.set(new_auth_state);
             }
+            Err(e) => {
+                // The token may well be fine; keep it and open local projects without the backend
+                println!("Couldn't check the stored token, starting offline: {}", e);
+                let new_auth_state = AuthState {
+                    token: Some(stored_token.clone()),
+                    is_authenticated: false,
+                    subscription: None,
+                    offline: true,
+                };
+
+                auth_state.set(new_auth_state);
+
+                match load_local_projects() {
+                    Ok(projects) => {
+                        local_projects = projects;
+                        println!("Successfully loaded {} local projects", local_projects.len());
+                    }
+                    Err(e) => {
+                        println!("Failed to load local projects: {}", e);
+                    }
+                }
+            }
         }
     }
 
@@ -512,6 +333,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let editor = Arc::new(Mutex::new(editor));
 
+    // Commands from automation scripts, handled alongside the UI's commands
+    let (automation_tx, automation_rx) = mpsc::channel::<AutomationRequest>();
+    let automation_server = match &editor_args.automation {
+        Some(address) => match AutomationServer::start(address, automation_tx) {
+            Ok(server) => {
+                println!("Automation endpoint listening on {}", address);
+                Some(server)
+            }
+            Err(e) => {
+                eprintln!("Failed to start automation endpoint: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
     // editor_state holds saved data, not active gpu data
     let cloned_editor = Arc::clone(&editor);
     let record = Arc::new(Mutex::new(Record::new()));
@@ -520,10 +357,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let editor_state = Arc::new(Mutex::new(editor_state));
 
     // Create channel for API responses
-    let (api_response_tx, api_response_rx) = mpsc::channel::<stunts_engine::animations::AnimationData>();
+    let (api_response_tx, api_response_rx) = mpsc::channel::<MotionUpdate>();
     
     // Create channel for export progress
-    let (export_progress_tx, export_progress_rx) = tokio_mpsc::unbounded_channel::<ExportProgress>();
+    let (export_progress_tx, export_progress_rx) = tokio_mpsc::unbounded_channel::<ExportJobUpdate>();
 
     // Create gradients for button1 states
     let button_normal = Gradient::new_linear((0.0, 0.0), (0.0, 40.0))
@@ -534,7 +371,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_stops([Color::rgb8(50, 50, 55), Color::rgb8(65, 65, 70)]);
 
     let display_motion_form = Signal::new(false);
-    let display_motion_loading = Signal::new(false);
     
     // Screen capture state
     let capture_sources_visible = Signal::new(false);
@@ -550,6 +386,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let is_exporting = Signal::new(false);
     let export_progress = Signal::new(0.0f32);
     let export_status = Signal::new("Ready to export".to_string());
+    let export_settings_signals = export_settings_panel::ExportSettingsSignals::new();
+    export_settings_signals.set_reveal_after_export(Preferences::load().reveal_after_export);
+    let export_progress_signals = export_progress_panel::ExportProgressSignals::new();
+    let motion_candidate_signals = motion_candidates_panel::MotionCandidateSignals::new();
+    let motion_generation_signals = motion_generation_panel::MotionGenerationSignals::new();
+    // Jobs left over from the last session resume once the user signs in or works offline
+    let export_queue = ExportQueue::start(export_progress_tx);
     
     // Sidebar state for property editing
     let sidebar_visible = Signal::new(false);
@@ -561,9 +404,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let local_projects_signal = Signal::new(local_projects.clone());
     let selected_project_signal = Signal::new(selected_project.clone());
     let current_sequence_id = Signal::new(String::new());
-    let show_editor = Signal::new(auth_state.get().is_authenticated && selected_project.is_some());
-    let show_auth_form = Signal::new(!auth_state.get().is_authenticated);
-    let show_project_list = Signal::new(auth_state.get().is_authenticated && selected_project.is_none());
+    let show_editor = Signal::new(auth_state.get().has_local_access() && selected_project.is_some());
+    let show_auth_form = Signal::new(!auth_state.get().has_local_access());
+    let is_offline = Signal::new(auth_state.get().offline);
+    let auth_session = AuthSession::new(auth_state.clone(), show_auth_form.clone());
+    auth_session.start_refreshing();
+    let plan_notice = Signal::new(auth_state.get().entitlements().notice.unwrap_or_default());
+    let account_signals = account_panel::AccountSignals::new();
+    let show_project_list = Signal::new(auth_state.get().has_local_access() && selected_project.is_none());
     let show_project_creation = Signal::new(false);
     let auth_loading = Signal::new(false);
     
@@ -580,9 +428,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let rotation_text = Signal::new("".to_string());
     let duration_text = Signal::new("".to_string());
     let delay_text = Signal::new("".to_string());
+    let motion_duration_error = Signal::new(String::new());
+    let motion_delay_error = Signal::new(String::new());
+    let motion_form_signals = MotionFormSignals {
+        description: description_text.clone(),
+        position: position_text.clone(),
+        scale: scale_text.clone(),
+        opacity: opacity_text.clone(),
+        rotation: rotation_text.clone(),
+        delay: delay_text.clone(),
+        duration: duration_text.clone(),
+        object_id: Signal::new(String::new()),
+        waypoints: Signal::new(String::new()),
+    };
+    let cloud_motion_generation = Signal::new(Preferences::load().cloud_motion_generation);
+    let cloud_motion_label = Signal::new(String::new());
+    let set_cloud_motion_label = {
+        let cloud_motion_label = cloud_motion_label.clone();
+        move |enabled: bool| {
+            cloud_motion_label.set(if enabled {
+                "AI generation: On".to_string()
+            } else {
+                "AI generation: Off (local)".to_string()
+            });
+        }
+    };
+    set_cloud_motion_label(cloud_motion_generation.get());
+    let motion_merge_mode = Signal::new(MergeMode::default().key().to_string());
+    let motion_variants = Signal::new(1usize);
 
     let motion_form = container()
-        .with_size(400.0, 450.0)
+        .with_size(400.0, 650.0)
         .with_background_color(Color::rgba8(255, 200, 150, 200))
         .with_border_radius(12.0)
         .with_padding(Padding::all(15.0))
@@ -591,7 +467,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_child(
             // Element::new_widget(Box::new(
             column()
-                .with_size(400.0, 450.0)
+                .with_size(400.0, 650.0)
                 .with_main_axis_alignment(MainAxisAlignment::Start)
                 .with_cross_axis_alignment(CrossAxisAlignment::Start)
                 .with_child(Element::new_widget(Box::new(
@@ -706,13 +582,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .with_placeholder("ex. Slow, 5 seconds, 1500ms")
                         .with_signal(duration_text.clone())
                         .on_change({
-                            let duration_text = duration_text.clone();
+                            let motion_duration_error = motion_duration_error.clone();
 
                             move |text| {
-                                // rotation_text.set(text.to_string());
+                                motion_duration_error.set(motion::parse_duration(text).err().unwrap_or_default());
                             }
                         })
                 )))
+                .with_child(Element::new_widget(Box::new(
+                    text_signal(motion_duration_error.clone())
+                        .with_font_size(11.0)
+                        .with_color(Color::rgba8(180, 30, 30, 255))
+                )))
                 .with_child(Element::new_widget(Box::new(
                     text("Delay:")
                         .with_font_size(12.0)
@@ -725,13 +606,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .with_placeholder("ex. Momentary, 2 seconds, 1500ms")
                         .with_signal(delay_text.clone())
                         .on_change({
-                            let delay_text = delay_text.clone();
+                            let motion_delay_error = motion_delay_error.clone();
 
                             move |text| {
-                                // rotation_text.set(text.to_string());
+                                motion_delay_error.set(motion::parse_delay(text).err().unwrap_or_default());
                             }
                         })
                 )))
+                .with_child(Element::new_widget(Box::new(
+                    text_signal(motion_delay_error.clone())
+                        .with_font_size(11.0)
+                        .with_color(Color::rgba8(180, 30, 30, 255))
+                )))
                 .with_child(Element::new_widget(Box::new(
                     button("Try Random Animation")
                         .with_font_size(12.0)
@@ -766,6 +652,135 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             }
                         })
                 )))
+                .with_child(Element::new_widget(Box::new(
+                    button_signal(cloud_motion_label.clone())
+                        .with_font_size(12.0)
+                        .with_width(180.0)
+                        .with_height(30.0)
+                        .with_backgrounds(
+                            Background::Gradient(button_normal.clone()),
+                            Background::Gradient(button_hover.clone()),
+                            Background::Gradient(button_pressed.clone())
+                        )
+                        .on_click({
+                            let cloud_motion_generation = cloud_motion_generation.clone();
+                            let set_cloud_motion_label = set_cloud_motion_label.clone();
+                            let tx = command_tx.clone();
+
+                            move || {
+                                let enabled = !cloud_motion_generation.get();
+                                cloud_motion_generation.set(enabled);
+                                set_cloud_motion_label(enabled);
+                                let _ = tx.send(Command::SetCloudMotionGeneration { enabled });
+                            }
+                        })
+                )))
+                .with_child(
+                    row()
+                        .with_size(370.0, 35.0)
+                        .with_main_axis_alignment(MainAxisAlignment::Start)
+                        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+                        .with_child(Element::new_widget(Box::new(
+                            text_signal(motion_form_signals.waypoints.clone())
+                                .with_font_size(12.0)
+                                .with_color(Color::rgba8(80, 40, 0, 255))
+                        )))
+                        .with_child(Element::new_widget(Box::new(
+                            button("Add Waypoint")
+                                .with_font_size(12.0)
+                                .with_width(120.0)
+                                .with_height(25.0)
+                                .with_backgrounds(
+                                    Background::Gradient(button_normal.clone()),
+                                    Background::Gradient(button_hover.clone()),
+                                    Background::Gradient(button_pressed.clone())
+                                )
+                                .on_click({
+                                    let tx = command_tx.clone();
+
+                                    move || {
+                                        let _ = tx.send(Command::AddMotionWaypoint);
+                                    }
+                                })
+                        )))
+                        .into_container_element()
+                )
+                .with_child(Element::new_widget(Box::new(
+                    text("Existing motion:")
+                        .with_font_size(12.0)
+                        .with_color(Color::rgba8(80, 40, 0, 255))
+                )))
+                .with_child(Element::new_widget(Box::new(
+                    dropdown()
+                        .with_options(
+                            MergeMode::ALL
+                                .iter()
+                                .map(|mode| DropdownOption {
+                                    label: mode.label().to_string(),
+                                    value: mode.key().to_string(),
+                                })
+                                .collect(),
+                        )
+                        .with_selected_value(motion_merge_mode.get())
+                        .with_size(220.0, 25.0)
+                        .on_selection_changed({
+                            let motion_merge_mode = motion_merge_mode.clone();
+                            move |selection: String| {
+                                motion_merge_mode.set(selection);
+                            }
+                        })
+                )))
+                .with_child(
+                    row()
+                        .with_size(370.0, 35.0)
+                        .with_main_axis_alignment(MainAxisAlignment::Start)
+                        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+                        .with_child(Element::new_widget(Box::new(
+                            text("Variants:")
+                                .with_font_size(12.0)
+                                .with_color(Color::rgba8(80, 40, 0, 255))
+                        )))
+                        .with_child(Element::new_widget(Box::new(
+                            dropdown()
+                                .with_options(
+                                    (1..=MAX_VARIANTS)
+                                        .map(|count| DropdownOption {
+                                            label: count.to_string(),
+                                            value: count.to_string(),
+                                        })
+                                        .collect(),
+                                )
+                                .with_selected_value(motion_variants.get().to_string())
+                                .with_size(60.0, 25.0)
+                                .on_selection_changed({
+                                    let motion_variants = motion_variants.clone();
+                                    move |selection: String| {
+                                        motion_variants.set(selection.parse().unwrap_or(1));
+                                    }
+                                })
+                        )))
+                        .with_child(Element::new_widget(Box::new(
+                            button("Saved Variants")
+                                .with_font_size(12.0)
+                                .with_width(120.0)
+                                .with_height(25.0)
+                                .with_backgrounds(
+                                    Background::Gradient(button_normal.clone()),
+                                    Background::Gradient(button_hover.clone()),
+                                    Background::Gradient(button_pressed.clone())
+                                )
+                                .on_click({
+                                    let display_motion_form = display_motion_form.clone();
+                                    let tx = command_tx.clone();
+
+                                    move || {
+                                        display_motion_form.set(false);
+                                        let _ = tx.send(Command::BrowseMotionCandidates);
+                                    }
+                                })
+                        )))
+                        .into_container_element()
+                )
                 .with_child(Element::new_widget(Box::new(
                     button("Confirm")
                         .with_font_size(12.0)
@@ -786,9 +801,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             let duration = duration_text.clone();
                             let delay = delay_text.clone();
                             let editor_for_api = editor.clone();
+                            let motion_duration_error = motion_duration_error.clone();
+                            let motion_delay_error = motion_delay_error.clone();
+                            let motion_merge_mode = motion_merge_mode.clone();
+                            let motion_variants = motion_variants.clone();
                             let tx = command_tx.clone();
 
                             move || {
+                                // Keep the form open until the timing fields make sense
+                                let duration_error = motion::parse_duration(&duration.get()).err();
+                                let delay_error = motion::parse_delay(&delay.get()).err();
+                                motion_duration_error.set(duration_error.clone().unwrap_or_default());
+                                motion_delay_error.set(delay_error.clone().unwrap_or_default());
+                                if duration_error.is_some() || delay_error.is_some() {
+                                    return;
+                                }
+
                                 // Send command to be processed in async context
                                 tx.send(Command::SubmitMotionForm {
                                     description: description.get(),
@@ -798,6 +826,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                     rotation: rotation.get(),
                                     duration: duration.get(),
                                     delay: delay.get(),
+                                    merge_mode: MergeMode::from_key(&motion_merge_mode.get()).unwrap_or_default(),
+                                    variants: motion_variants.get(),
                                 });
                             }
                         })
@@ -868,28 +898,52 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     text("")
                         .with_font_size(12.0) // Spacer
                 )))
-                .with_child(Element::new_widget(Box::new(
-                    button("Sign In")
-                        .with_font_size(14.0)
-                        .with_width(120.0)
-                        .with_height(40.0)
-                        .with_backgrounds(
-                            Background::Gradient(button_normal.clone()),
-                            Background::Gradient(button_hover.clone()),
-                            Background::Gradient(button_pressed.clone())
-                        )
-                        .on_click({
-                            let email = email_text.clone();
-                            let password = password_text.clone();
-                            let tx = command_tx.clone();
-                            move || {
-                                tx.send(Command::SubmitSignIn {
-                                    email: email.get(),
-                                    password: password.get(),
-                                });
-                            }
-                        })
-                )))
+                .with_child(
+                    row()
+                        .with_size(320.0, 45.0)
+                        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+                        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+                        .with_child(Element::new_widget(Box::new(
+                            button("Sign In")
+                                .with_font_size(14.0)
+                                .with_width(120.0)
+                                .with_height(40.0)
+                                .with_backgrounds(
+                                    Background::Gradient(button_normal.clone()),
+                                    Background::Gradient(button_hover.clone()),
+                                    Background::Gradient(button_pressed.clone())
+                                )
+                                .on_click({
+                                    let email = email_text.clone();
+                                    let password = password_text.clone();
+                                    let tx = command_tx.clone();
+                                    move || {
+                                        tx.send(Command::SubmitSignIn {
+                                            email: email.get(),
+                                            password: password.get(),
+                                        });
+                                    }
+                                })
+                        )))
+                        .with_child(Element::new_widget(Box::new(
+                            button("Work Offline")
+                                .with_font_size(14.0)
+                                .with_width(120.0)
+                                .with_height(40.0)
+                                .with_backgrounds(
+                                    Background::Gradient(button_normal.clone()),
+                                    Background::Gradient(button_hover.clone()),
+                                    Background::Gradient(button_pressed.clone())
+                                )
+                                .on_click({
+                                    let tx = command_tx.clone();
+                                    move || {
+                                        let _ = tx.send(Command::WorkOffline);
+                                    }
+                                })
+                        )))
+                        .into_container_element()
+                )
                 .into_container_element()
         );
 
@@ -963,6 +1017,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                         tx.send(Command::SignOut);
                                     }
                                 })
+                        )))
+                        .with_child(Element::new_widget(Box::new(
+                            button("Account")
+                                .with_font_size(12.0)
+                                .with_width(80.0)
+                                .with_height(30.0)
+                                .with_backgrounds(
+                                    Background::Gradient(button_normal.clone()),
+                                    Background::Gradient(button_hover.clone()),
+                                    Background::Gradient(button_pressed.clone())
+                                )
+                                .on_click({
+                                    let tx = command_tx.clone();
+                                    move || {
+                                        let _ = tx.send(Command::ShowAccountPanel);
+                                    }
+                                })
                         ))).into_container_element()
                 )
                 .with_child(
@@ -1045,6 +1116,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             }
                         })
                 )))
+                .with_child(Element::new_widget(Box::new(
+                    text_signal(plan_notice.clone())
+                        .with_font_size(12.0)
+                        .with_color(Color::rgba8(255, 200, 120, 255))
+                )))
                 .with_child(Element::new_widget(Box::new(
                     text("")
                         .with_font_size(8.0) // Spacer
@@ -1234,22 +1310,62 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         });
 
-    // optional button for regenerate each object according to its arrow (maybe too much at once? could batch it)
-    // let button3 = button("Regenerate All")
-    //     .with_font_size(10.0)
-    //     .with_width(90.0)
-    //     .with_height(20.0)
-    //     .with_backgrounds(
-    //         Background::Gradient(button_normal.clone()),
-    //         Background::Gradient(button_hover.clone()),
-    //         Background::Gradient(button_pressed.clone())
-    //     )
-    //     .on_click({
-    //         let tx = command_tx.clone();
-    //         move || {
-    //             tx.send(Command::AddMotion);
-    //         }
-    //     });
+    // regenerate each object according to its arrow, a few requests at a time
+    let regenerate_all_label = Signal::new(REGENERATE_ALL_LABEL.to_string());
+    let is_regenerating = Signal::new(false);
+    let regenerate_all_button = button_signal(regenerate_all_label.clone())
+        .with_font_size(10.0)
+        .with_width(110.0)
+        .with_height(20.0)
+        .with_backgrounds(
+            Background::Gradient(button_normal.clone()),
+            Background::Gradient(button_hover.clone()),
+            Background::Gradient(button_pressed.clone())
+        )
+        .on_click({
+            let tx = command_tx.clone();
+            move || {
+                let _ = tx.send(Command::RegenerateAllMotion);
+            }
+        });
+
+    // run the selected object's last motion prompt again, with its saved arrow
+    let rerun_motion_button = button("Re-run Motion")
+        .with_font_size(10.0)
+        .with_width(90.0)
+        .with_height(20.0)
+        .with_backgrounds(
+            Background::Gradient(button_normal.clone()),
+            Background::Gradient(button_hover.clone()),
+            Background::Gradient(button_pressed.clone())
+        )
+        .on_click({
+            let tx = command_tx.clone();
+            move || {
+                let _ = tx.send(Command::RerunMotionPrompt);
+            }
+        });
+
+    // Only shown while working offline
+    let go_online_button = container()
+        .with_display_signal(is_offline.clone())
+        .with_child(Element::new_widget(Box::new(
+            button("Go Online")
+                .with_font_size(10.0)
+                .with_width(90.0)
+                .with_height(20.0)
+                .with_backgrounds(
+                    Background::Gradient(button_normal.clone()),
+                    Background::Gradient(button_hover.clone()),
+                    Background::Gradient(button_pressed.clone())
+                )
+                .on_click({
+                    let tx = command_tx.clone();
+                    move || {
+                        let _ = tx.send(Command::GoOnline);
+                    }
+                })
+        )));
 
     // export the video - create reactive button text
     let export_button_text = Signal::new("Export".to_string());
@@ -1263,15 +1379,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Background::Gradient(button_pressed.clone())
         )
         .on_click({
-            let tx = command_tx.clone();
-            let export_progress_tx = export_progress_tx.clone();
-            let is_exporting = is_exporting.clone();
+            let export_settings_visible = export_settings_signals.visible.clone();
             move || {
-                if !is_exporting.get() {
-                    tx.send(Command::Export {
-                        progress_tx: export_progress_tx.clone(),
-                    });
-                }
+                // Choose resolution, frame rate and destination before exporting.
+                // Exports started while another is rendering are queued behind it.
+                export_settings_visible.set(!export_settings_visible.get());
             }
         });
 
@@ -1563,11 +1675,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 })
         )))
+        .with_child(Element::new_widget(Box::new(
+            button("Account")
+                .with_font_size(10.0)
+                .with_width(80.0)
+                .with_height(20.0)
+                .with_backgrounds(
+                    Background::Gradient(button_normal.clone()),
+                    Background::Gradient(button_hover.clone()),
+                    Background::Gradient(button_pressed.clone())
+                )
+                .on_click({
+                    let tx = command_tx.clone();
+                    move || {
+                        let _ = tx.send(Command::ShowAccountPanel);
+                    }
+                })
+        )))
         .with_child(Element::new_widget(Box::new(export_button)))
         .with_child(Element::new_widget(Box::new(
             text_signal(export_status.clone())
                 .with_font_size(10.0)
                 .with_color(Color::rgba8(200, 200, 200, 255))
+        )))
+        .with_child(Element::new_widget(Box::new(
+            text_signal(plan_notice.clone())
+                .with_font_size(10.0)
+                .with_color(Color::rgba8(255, 200, 120, 255))
         )));
 
     let bottom_tools = row()
@@ -1580,8 +1714,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_child(Element::new_widget(Box::new(button_image)))
         .with_child(Element::new_widget(Box::new(button_video)))
         .with_child(Element::new_widget(Box::new(button_capture)))
-        .with_child(capture_sources_dropdown.into_container_element());
-        // .with_child(Element::new_widget(Box::new(button3)))
+        .with_child(capture_sources_dropdown.into_container_element())
+        .with_child(Element::new_widget(Box::new(regenerate_all_button)))
+        .with_child(Element::new_widget(Box::new(rerun_motion_button)))
+        .with_child(go_online_button.into_container_element());
         // .with_child(Element::new_widget(Box::new(export_button)))
         // .with_child(Element::new_widget(Box::new(button_properties)))
         // .with_child(Element::new_widget(Box::new(button_themes)))
@@ -1633,6 +1769,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         sidebar_width,
     );
 
+    let export_settings_widget = export_settings_panel::create_export_settings_panel(
+        command_tx.clone(),
+        export_settings_signals.clone(),
+        current_sequence_id.clone(),
+        button_normal.clone(),
+        button_hover.clone(),
+        button_pressed.clone(),
+    );
+
+    let export_progress_widget = export_progress_panel::create_export_progress_panel(
+        command_tx.clone(),
+        export_progress_signals.clone(),
+        button_normal.clone(),
+        button_hover.clone(),
+        button_pressed.clone(),
+    );
+
+    let motion_candidates_widget = motion_candidates_panel::create_motion_candidates_panel(
+        command_tx.clone(),
+        motion_candidate_signals.clone(),
+        button_normal.clone(),
+        button_hover.clone(),
+        button_pressed.clone(),
+    );
+
+    let motion_generation_widget = motion_generation_panel::create_motion_generation_panel(
+        command_tx.clone(),
+        motion_generation_signals.clone(),
+        button_normal.clone(),
+        button_hover.clone(),
+        button_pressed.clone(),
+    );
+
+    let account_widget = account_panel::create_account_panel(
+        command_tx.clone(),
+        account_signals.clone(),
+        button_normal.clone(),
+        button_hover.clone(),
+        button_pressed.clone(),
+    );
+
     // Create themes sidebar widget  
     let themes_sidebar_widget = theme_sidebar::create_themes_sidebar_panel(
         command_tx.clone(),
@@ -1698,6 +1875,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_child(project_selection_form.into_container_element())
         .with_child(auth_form.into_container_element()) 
         .with_child(motion_form.into_container_element())
+        .with_child(export_settings_widget)
+        .with_child(export_progress_widget)
+        .with_child(motion_candidates_widget)
+        .with_child(motion_generation_widget)
+        .with_child(account_widget)
         .with_child(editor_container.into_container_element());
 
     let main_container = container()
@@ -1793,6 +1975,42 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         });
 
+    // UI state shared with the command handlers
+    let app_signals = AppSignals {
+        display_motion_form: display_motion_form.clone(),
+        motion_duration_error: motion_duration_error.clone(),
+        motion_delay_error: motion_delay_error.clone(),
+        motion_generation_signals: motion_generation_signals.clone(),
+        capture_sources_visible: capture_sources_visible.clone(),
+        available_capture_sources: available_capture_sources.clone(),
+        is_recording: is_recording.clone(),
+        capture_button_text: capture_button_text.clone(),
+        export_status: export_status.clone(),
+        export_button_text: export_button_text.clone(),
+        is_exporting: is_exporting.clone(),
+        export_progress_signals: export_progress_signals.clone(),
+        export_settings_signals: export_settings_signals.clone(),
+        motion_candidate_signals: motion_candidate_signals.clone(),
+        regenerate_all_label: regenerate_all_label.clone(),
+        is_regenerating: is_regenerating.clone(),
+        motion_form_signals: motion_form_signals.clone(),
+        auth_state: auth_state.clone(),
+        auth_loading: auth_loading.clone(),
+        show_auth_form: show_auth_form.clone(),
+        is_offline: is_offline.clone(),
+        auth_session: auth_session.clone(),
+        plan_notice: plan_notice.clone(),
+        account_signals: account_signals.clone(),
+        local_projects_signal: local_projects_signal.clone(),
+        selected_project_signal: selected_project_signal.clone(),
+        show_project_list: show_project_list.clone(),
+        show_project_creation: show_project_creation.clone(),
+        show_editor: show_editor.clone(),
+        project_name_text: project_name_text.clone(),
+        current_sequence_id: current_sequence_id.clone(),
+        available_sequences: available_sequences.clone(),
+    };
+
     // Use the new run_with_editor_state method that avoids Send + Sync constraints
     app.run_with_editor_state(
         editor.clone(),
@@ -1810,1563 +2028,208 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let editor_for_render = editor.clone();
             let state_for_render = editor_state.clone();
             let command_rx_for_render = Arc::new(Mutex::new(command_rx));
+            let automation_rx_for_render = Arc::new(Mutex::new(automation_rx));
             let api_response_rx_for_render = Arc::new(Mutex::new(api_response_rx));
             let api_response_tx_for_render = api_response_tx.clone();
             let export_progress_rx_for_render = Arc::new(Mutex::new(export_progress_rx));
             let engine_handle_cache: RefCell<Option<render_integration::EngineHandle>> = RefCell::new(None);
+            let export_started_at: RefCell<Option<std::time::Instant>> = RefCell::new(None);
+            let current_export_job_id: RefCell<Option<String>> = RefCell::new(None);
+            let command_dispatcher = RefCell::new(CommandDispatcher::with_default_handlers());
+            let pending_commands = RefCell::new(PendingCommands::new());
+            let preferences: RefCell<Preferences> = RefCell::new(Preferences::load());
+            let last_selected_object = RefCell::new(Uuid::nil());
             
             Arc::new(move |device: &wgpu::Device, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder, external_resources: &[vello::ExternalResource<'_>], view: &wgpu::TextureView| -> Result<(), vello::Error> {
                 // Check if motion arrow was just placed and show form
                 if let Ok(mut editor) = editor_for_render.try_lock() {
                     if editor.motion_arrow_just_placed {
                         editor.motion_arrow_just_placed = false;
-                        display_motion_form.set(true);
+                        let _ = tx.send(Command::MotionArrowPlaced);
+                    }
+
+                    // Load the saved prompt of an object when it's selected again
+                    let selected_object = editor.selected_polygon_id;
+                    if selected_object != last_selected_object.replace(selected_object) && !selected_object.is_nil() {
+                        let _ = tx.send(Command::LoadMotionPrompt { object_id: selected_object.to_string() });
                     }
                 }
                 
                 // Process API responses
                 // uses std, not tokio
                 if let Ok(rx) = api_response_rx_for_render.try_lock() {
-                    while let Ok(animation_data) = rx.try_recv() {
-                        if let Ok(mut editor) = editor_for_render.try_lock() {
-                            if let Ok(mut editor_state) = state_for_render.try_lock() { 
-                                let sequence_data = editor.current_sequence_data.clone();
-                                let last_motion_arrow_object_id = editor.last_motion_arrow_object_id.to_string();
-                                let last_motion_arrow_object_type = editor.last_motion_arrow_object_type.clone();
-
-                                let mut video_item_dur_ms = 1000;
-                                if last_motion_arrow_object_type == ObjectType::VideoItem {
-                                    video_item_dur_ms = editor.video_items.iter().find(|v| v.id == last_motion_arrow_object_id).expect("Couldn't find video item").source_duration_ms.clone();
+                    // Take the locks first so responses wait in the channel instead of being dropped
+                    if let (Ok(mut editor), Ok(mut editor_state)) = (editor_for_render.try_lock(), state_for_render.try_lock()) {
+                        while let Ok(update) = rx.try_recv() {
+                            let project_id = selected_project_signal.get().map(|project| project.project_id);
+
+                            // The user may have switched sequence or project while it was generating
+                            if !update.is_for_current_sequence(&editor) {
+                                println!("Dropping motion generated for sequence {}, which is no longer open", update.sequence_id());
+
+                                if let MotionUpdate::Batch { .. } = update {
+                                    app_signals.is_regenerating.set(false);
+                                    app_signals.regenerate_all_label.set(REGENERATE_ALL_LABEL.to_string());
                                 }
+                                continue;
+                            }
 
-                                println!("video_item_dur_ms {:?}", video_item_dur_ms);
-
-                                if let Some(ref mut saved_state) = editor.saved_state {
-                                    // Clean up data
-                                    let mut final_animation = animation_data.clone();
-                                    final_animation.id = Uuid::new_v4().to_string();
-                                    // final_animation.object_type = ObjectType::Polygon;
-                                    final_animation.polygon_id = last_motion_arrow_object_id;
-                                    final_animation.start_time_ms = 0;
-                                    final_animation.position = [0, 0];
-
-                                    if last_motion_arrow_object_type == ObjectType::VideoItem {
-                                        let zoom_prop = editor_state.save_default_zoom();
-
-                                        final_animation.properties.push(zoom_prop);
-                                    }
-
-                                    // println!("final_animation: {:?}", final_animation);
-
-
-                                    // Find the current sequence and add/overwrite the animation data
-                                    if let Some(current_seq_data) = &sequence_data {
-                                        for sequence in &mut saved_state.sequences {
-                                            if sequence.id == current_seq_data.id {
-                                                // Remove any existing motion paths for this polygon_id
-                                                sequence.polygon_motion_paths.retain(|path| 
-                                                    path.polygon_id != final_animation.polygon_id
-                                                );
-                                                
-                                                // Add the new motion path
-                                                sequence.polygon_motion_paths.push(final_animation.clone());
-
-                                                if last_motion_arrow_object_type == ObjectType::VideoItem {
-
-                                                    let scaled_paths = editor_state.scale_keyframes(
-                                                        sequence,
-                                                        (video_item_dur_ms / 1000) as f32,
-                                                    );
-
-                                                    sequence.polygon_motion_paths = scaled_paths;
-                                                }
-                                                break;
-                                            }
-                                        }
-                                        
-                                        // // Update the editor's current_sequence_data
-                                        // let mut updated_sequence = current_seq_data.clone();
-                                        
-                                        // // Remove existing motion paths for this polygon_id from the editor's sequence
-                                        // updated_sequence.polygon_motion_paths.retain(|path| 
-                                        //     path.polygon_id != final_animation.polygon_id
-                                        // );
+                            match (update, project_id) {
+                                (MotionUpdate::Generated { motion, .. }, Some(project_id)) if motion.candidate => {
+                                    commands::receive_motion_candidate(&mut editor, &mut editor_state, &app_signals, &project_id, motion);
+                                }
+                                (MotionUpdate::Generated { motion, .. }, _) => {
+                                    if let Err(e) = motion::apply_generated_motion(&mut editor, &mut editor_state, motion) {
+                                        println!("Failed to apply generated motion: {}", e);
                                     }
                                 }
+                                (MotionUpdate::Batch { motions, .. }, _) => {
+                                    if let Err(e) = motion::apply_generated_motions(&mut editor, &mut editor_state, motions) {
+                                        println!("Failed to apply regenerated motion, nothing was changed: {}", e);
+                                    }
 
-                                // save and sync
-                                if let Some(current_seq_data) = &sequence_data {
-                                    let saved_state = editor.saved_state.clone().expect("Couldn't get saved state");
-                                    let updated_sequence = saved_state.sequences.iter().find(|s| s.id == current_seq_data.id).expect("Couldn't get saved state");
-                                
-                                    // // Add the new motion path
-                                    // updated_sequence.polygon_motion_paths.push(final_animation);
-                                    editor.current_sequence_data = Some(updated_sequence.clone());
-                                    
-                                    // Call update_motion_paths to refresh the editor
-                                    editor.update_motion_paths(&updated_sequence);
-
-                                    editor.canvas_hidden = false;
-
-                                    save_saved_state_raw(editor.saved_state.clone().expect("Couldn't get saved state"));
-                                    
-                                    println!("Animation data successfully integrated into sequence (overwrote existing)");
+                                    app_signals.is_regenerating.set(false);
+                                    app_signals.regenerate_all_label.set(REGENERATE_ALL_LABEL.to_string());
                                 }
-                                
                             }
                         }
                     }
                 }
                 
+                // Jobs from the last session render under the plan of whoever signs in
+                if export_queue.has_saved_jobs() {
+                    let auth_state = app_signals.auth_state.get();
+                    if auth_state.has_local_access() {
+                        export_queue.resume(&auth_state.entitlements());
+                    }
+                }
+
                 // Process export progress updates
                 // uses tokio, not std
                 if let Ok(mut rx) = export_progress_rx_for_render.try_lock() {
-                    while let Ok(progress) = rx.try_recv() {
+                    while let Ok(ExportJobUpdate { job_id, progress }) = rx.try_recv() {
+                        if let Some(automation_server) = &automation_server {
+                            automation_server.notify_export(&job_id, &progress);
+                        }
+
+                        let is_running = matches!(progress, ExportProgress::Progress { .. });
+
+                        // A new job started rendering, including jobs resumed from the last session
+                        if is_running && current_export_job_id.borrow().as_deref() != Some(job_id.as_str()) {
+                            let job_label = export_queue.jobs()
+                                .into_iter()
+                                .find(|job| job.id == job_id)
+                                .map(|job| job.label())
+                                .unwrap_or_default();
+
+                            *current_export_job_id.borrow_mut() = Some(job_id.clone());
+                            *export_started_at.borrow_mut() = Some(std::time::Instant::now());
+                            export_progress_signals.start(&job_label);
+                            is_exporting.set(true);
+                        }
+
                         match progress {
-                            ExportProgress::Progress(percent) => {
+                            ExportProgress::Progress { percent, sequence_name } => {
                                 export_progress.set(percent);
                                 export_status.set(format!("Exporting: {:.1}%", percent));
                                 export_button_text.set(format!("Exporting {:.0}%", percent));
+
+                                let elapsed = export_started_at.borrow()
+                                    .map(|started_at| started_at.elapsed())
+                                    .unwrap_or_default();
+                                export_progress_signals.update(percent, sequence_name.as_deref(), elapsed);
+                                export_progress_signals.set_queued(export_queue.len().saturating_sub(1));
                             }
                             ExportProgress::Complete(output_path) => {
                                 export_progress.set(100.0);
                                 export_status.set("Export complete!".to_string());
-                                export_button_text.set("Export".to_string());
-                                is_exporting.set(false);
-                                
-                                // Open the output folder in explorer
-                                tokio::spawn(async move {
-                                    if let Err(e) = std::process::Command::new("explorer")
-                                        .arg(&output_path)
-                                        .spawn() 
-                                    {
-                                        println!("Failed to open file browser: {}", e);
-                                    }
-                                });
+
+                                if preferences.borrow().reveal_after_export {
+                                    tokio::spawn(async move {
+                                        if let Err(e) = reveal_in_file_manager(Path::new(&output_path)) {
+                                            println!("Failed to open file browser: {}", e);
+                                        }
+                                    });
+                                }
                             }
                             ExportProgress::Error(err) => {
                                 export_status.set(format!("Export failed: {}", err));
+                            }
+                            ExportProgress::Cancelled => {
+                                export_status.set("Export cancelled".to_string());
+                            }
+                        }
+
+                        if !is_running {
+                            if export_queue.is_empty() {
                                 export_button_text.set("Export".to_string());
                                 is_exporting.set(false);
+                                export_progress_signals.finish();
+                                current_export_job_id.borrow_mut().take();
+                            } else {
+                                export_progress_signals.set_queued(export_queue.len().saturating_sub(1));
                             }
                         }
                     }
                 }
                 
-                // Process any pending commands from the UI thread
+                // Move new commands from the UI thread into the pending queue so a busy
+                // editor lock delays them instead of dropping them
                 if let Ok(rx) = command_rx_for_render.try_lock() {
+                    let mut pending_commands = pending_commands.borrow_mut();
                     while let Ok(command) = rx.try_recv() {
-                        if let Ok(mut editor) = editor_for_render.try_lock() {
-                            if let Ok(mut editor_state) = state_for_render.try_lock() {
+                        pending_commands.push(command);
+                    }
+                }
+                if let Ok(rx) = automation_rx_for_render.try_lock() {
+                    let mut pending_commands = pending_commands.borrow_mut();
+                    while let Ok(AutomationRequest { command, reply }) = rx.try_recv() {
+                        pending_commands.push_with_reply(command, reply);
+                    }
+                }
+
+                // Process pending commands, oldest first
+                {
+                    let mut pending_commands = pending_commands.borrow_mut();
+                    if !pending_commands.is_empty() {
+                        let editor_lock = editor_for_render.try_lock();
+                        let state_lock = state_for_render.try_lock();
+
+                        if let (Ok(mut editor), Ok(mut editor_state)) = (editor_lock, state_lock) {
+                            while let Some((command, reply)) = pending_commands.pop() {
                                 let selected_project = selected_project_signal.get().unwrap_or(ProjectData {
                                     project_id: Uuid::new_v4().to_string(),
                                     project_name: "Secret name".to_string()
                                 });
 
-                                match command {
-                                    Command::AddMotion => {
-                                        println!("Processing add motion command from channel");
-                                        editor.motion_mode = true;
-                                        println!("Motion mode enabled - user can now place arrows by clicking and dragging");
-                                    }
-                                    Command::AddSquarePolygon => {
-                                        println!("Processing add square polygon command from channel");
-                                        let random_coords = get_random_coords(window_size);
-                                        let new_id = Uuid::new_v4();
-
-                                        let polygon_config = PolygonConfig {
-                                            id: new_id,
-                                            name: "Square".to_string(),
-                                            points: vec![
-                                                Point { x: 0.0, y: 0.0 },
-                                                Point { x: 1.0, y: 0.0 },
-                                                Point { x: 1.0, y: 1.0 },
-                                                Point { x: 0.0, y: 1.0 },
-                                            ],
-                                            dimensions: (100.0, 100.0),
-                                            position: Point {
-                                                x: random_coords.0 as f32,
-                                                y: random_coords.1 as f32,
-                                            },
-                                            border_radius: 0.0,
-                                            fill: [1.0, 0.0, 0.0, 1.0], // Red color
-                                            stroke: Stroke {
-                                                fill: [0.0, 0.0, 0.0, 1.0], // Black border
-                                                thickness: 2.0,
-                                            },
-                                            layer: 2,
-                                        };
-
-                                        // println!("add polyong {:?}", current_sequence_id.get().clone());
-                                        
-                                        editor.add_polygon(
-                                            polygon_config.clone(),
-                                            polygon_config.name.clone(),
-                                            polygon_config.id,
-                                            current_sequence_id.get().clone(),
-                                        );
-
-                                        editor_state.add_saved_polygon(
-                                            &mut editor.saved_state.as_mut().expect("Couldn't get saved state"),
-                                            current_sequence_id.get().clone(),
-                                            SavedPolygonConfig {
-                                                id: polygon_config.id.to_string().clone(),
-                                                name: polygon_config.name.clone(),
-                                                dimensions: (
-                                                    polygon_config.dimensions.0 as i32,
-                                                    polygon_config.dimensions.1 as i32,
-                                                ),
-                                                fill: [
-                                                    polygon_config.fill[0] as i32,
-                                                    polygon_config.fill[1] as i32,
-                                                    polygon_config.fill[2] as i32,
-                                                    polygon_config.fill[3] as i32,
-                                                ],
-                                                border_radius: polygon_config.border_radius as i32, // multiply by 100?
-                                                position: SavedPoint {
-                                                    x: polygon_config.position.x as i32,
-                                                    y: polygon_config.position.y as i32,
-                                                },
-                                                stroke: SavedStroke {
-                                                    thickness: polygon_config.stroke.thickness as i32,
-                                                    fill: [
-                                                        polygon_config.stroke.fill[0] as i32,
-                                                        polygon_config.stroke.fill[1] as i32,
-                                                        polygon_config.stroke.fill[2] as i32,
-                                                        polygon_config.stroke.fill[3] as i32,
-                                                    ],
-                                                },
-                                                layer: polygon_config.layer.clone(),
-                                            },
-                                        );
-
-                                        let saved_state = editor
-                                            .saved_state
-                                            .as_ref()
-                                            .expect("Couldn't get saved state");
-                                        let updated_sequence = saved_state
-                                            .sequences
-                                            .iter()
-                                            .find(|s| s.id == current_sequence_id.get().clone())
-                                            .expect("Couldn't get updated sequence");
-                                        
-                                        let sequence_cloned = updated_sequence.clone();
-                                        
-                                        // drop(editor_state);
-
-                                        // let mut editor = editor_cloned.lock().unwrap();
-
-                                        editor.current_sequence_data = Some(sequence_cloned.clone());
-                                        editor.update_motion_paths(&sequence_cloned);
-                                        
-                                        // drop(editor);
-
-                                        println!("Square polygon added to editor successfully: {}", polygon_config.id);
-                                    }
-                                    Command::AddText => {
-                                        println!("Processing add text command from channel");
-                                        let random_coords = get_random_coords(window_size);
-                                        let new_id = Uuid::new_v4();
-
-                                        let text_config = TextRendererConfig {
-                                            id: new_id,
-                                            name: "Text".to_string(),
-                                            text: "Sample Text".to_string(),
-                                            font_family: "Aleo".to_string(),
-                                            dimensions: (200.0, 50.0),
-                                            position: Point {
-                                                x: random_coords.0 as f32,
-                                                y: random_coords.1 as f32,
-                                            },
-                                            layer: 2,
-                                            color: [0, 0, 0, 255],
-                                            font_size: 24,
-                                            background_fill: [255, 255, 255, 255],
-                                        };
-
-                                        let window_size = WindowSize {
-                                            width: window_size.width,
-                                            height: window_size.height,
-                                        };
-
-                                        editor.add_text_item(
-                                            &window_size,
-                                            device,
-                                            queue,
-                                            text_config.clone(),
-                                            text_config.text.clone(),
-                                            new_id,
-                                            current_sequence_id.get().clone(),
-                                        );
-
-                                        editor_state.add_saved_text_item(
-                                            &mut editor.saved_state.as_mut().expect("Couldn't get saved state"),
-                                            current_sequence_id.get().clone(),
-                                            SavedTextRendererConfig {
-                                                id: text_config.id.to_string(),
-                                                name: text_config.name.clone(),
-                                                text: text_config.text.clone(),
-                                                font_family: text_config.font_family.clone(),
-                                                dimensions: (text_config.dimensions.0 as i32, text_config.dimensions.1 as i32),
-                                                position: SavedPoint {
-                                                    x: text_config.position.x as i32,
-                                                    y: text_config.position.y as i32,
-                                                },
-                                                layer: text_config.layer,
-                                                color: text_config.color,
-                                                font_size: text_config.font_size,
-                                                background_fill: Some(text_config.background_fill),
-                                            },
-                                        );
-
-                                        let saved_state = editor
-                                            .saved_state
-                                            .as_ref()
-                                            .expect("Couldn't get saved state");
-                                        let updated_sequence = saved_state
-                                            .sequences
-                                            .iter()
-                                            .find(|s| s.id == current_sequence_id.get().clone())
-                                            .expect("Couldn't get updated sequence");
-                                        
-                                        let sequence_cloned = updated_sequence.clone();
-                                        
-                                        editor.current_sequence_data = Some(sequence_cloned.clone());
-                                        editor.update_motion_paths(&sequence_cloned);
-
-                                        println!("Text item added to editor successfully: {}", text_config.id);
-                                    }
-                                    Command::AddImage { file_path } => {
-                                        println!("Processing add image command from channel with file: {}", file_path);
-                                        let random_coords = get_random_coords(window_size);
-                                        let new_id = Uuid::new_v4();
-                                        
-                                        // Extract filename for a better name
-                                        let filename = std::path::Path::new(&file_path)
-                                            .file_stem()
-                                            .and_then(|s| s.to_str())
-                                            .unwrap_or("Image");
-
-                                        let image_config = StImageConfig {
-                                            id: new_id.to_string(),
-                                            name: filename.to_string(),
-                                            path: file_path.clone(),
-                                            dimensions: (150, 150),
-                                            position: Point {
-                                                x: random_coords.0 as f32,
-                                                y: random_coords.1 as f32,
-                                            },
-                                            layer: 2,
-                                        };
-
-                                        let window_size = WindowSize {
-                                            width: window_size.width,
-                                            height: window_size.height,
-                                        };
-
-                                        editor.add_image_item(
-                                            &window_size,
-                                            device,
-                                            queue,
-                                            image_config.clone(),
-                                            &Path::new(&file_path.clone()),
-                                            new_id,
-                                            current_sequence_id.get().clone(),
-                                        );
-
-                                        editor_state.add_saved_image_item(
-                                            &mut editor.saved_state.as_mut().expect("Couldn't get saved state"),
-                                            current_sequence_id.get().clone(),
-                                            SavedStImageConfig {
-                                                id: image_config.id.clone(),
-                                                name: image_config.name.clone(),
-                                                path: file_path,
-                                                dimensions: image_config.dimensions,
-                                                position: SavedPoint {
-                                                    x: image_config.position.x as i32,
-                                                    y: image_config.position.y as i32,
-                                                },
-                                                layer: image_config.layer,
-                                            },
-                                        );
-
-                                        let saved_state = editor
-                                            .saved_state
-                                            .as_ref()
-                                            .expect("Couldn't get saved state");
-                                        let updated_sequence = saved_state
-                                            .sequences
-                                            .iter()
-                                            .find(|s| s.id == current_sequence_id.get().clone())
-                                            .expect("Couldn't get updated sequence");
-                                        
-                                        let sequence_cloned = updated_sequence.clone();
-                                        
-                                        editor.current_sequence_data = Some(sequence_cloned.clone());
-                                        editor.update_motion_paths(&sequence_cloned);
-
-                                        println!("Image item added to editor successfully: {}", image_config.id);
-                                    }
-                                    Command::AddVideo { file_path } => {
-                                        println!("Processing add video command from channel with file: {}", file_path);
-                                        let random_coords = get_random_coords(window_size);
-                                        let new_id = Uuid::new_v4();
-                                        
-                                        let path = std::path::Path::new(&file_path);
-                                        // Extract filename for a better name
-                                        let filename = path
-                                            .file_stem()
-                                            .and_then(|s| s.to_str())
-                                            .unwrap_or("Video");
-
-                                        let parent = path.parent().unwrap_or(Path::new(""));
-    
-                                        let mouse_positions_path = parent.join("mousePositions.json");
-                                        let source_data_path = parent.join("sourceData.json");
-
-                                        let video_config = StVideoConfig {
-                                            id: new_id.to_string(),
-                                            name: filename.to_string(),
-                                            path: file_path.clone(),
-                                            dimensions: (300, 200),
-                                            position: Point {
-                                                x: random_coords.0 as f32,
-                                                y: random_coords.1 as f32,
-                                            },
-                                            layer: 2,
-                                            mouse_path: Some(mouse_positions_path.clone().display().to_string()),
-                                        };
-
-                                        let window_size = WindowSize {
-                                            width: window_size.width,
-                                            height: window_size.height,
-                                        };
-
-                                        let mut saved_mouse_path = None;
-                                        let mut stored_mouse_positions = None;
-                                        // if let Some(mouse_path) = &mouse_positions_path {
-                                            if let Ok(positions) = fs::read_to_string(mouse_positions_path.clone()) {
-                                                if let Ok(mouse_positions) = serde_json::from_str::<Vec<MousePosition>>(&positions) {
-                                                    let the_path = mouse_positions_path.to_str().expect("Couldn't make string from path");
-                                                    saved_mouse_path = Some(the_path.to_string());
-                                                    stored_mouse_positions = Some(mouse_positions);
-                                                }
-                                            }
-                                        // }
-
-                                        let mut stored_source_data = None;
-                                        // if let Some(source_path) = &source_data_path {
-                                            if let Ok(source_data) = fs::read_to_string(source_data_path) {
-                                                if let Ok(data) = serde_json::from_str::<SourceData>(&source_data) {
-                                                    stored_source_data = Some(data);
-                                                }
-                                            }
-                                        // }
-
-                                        editor.add_video_item(
-                                            &window_size,
-                                            device,
-                                            queue,
-                                            video_config.clone(),
-                                            &Path::new(&file_path.clone()),
-                                            new_id,
-                                            current_sequence_id.get().clone(),
-                                            stored_mouse_positions, // stored_mouse_positions
-                                            stored_source_data, // stored_source_data
-                                        );
-
-                                        let source_duration_ms = editor
-                                            .video_items
-                                            .last()
-                                            .expect("Couldn't get latest video")
-                                            .source_duration_ms
-                                            .clone();
-
-                                        editor_state.add_saved_video_item(
-                                            &mut editor.saved_state.as_mut().expect("Couldn't get saved state"),
-                                            current_sequence_id.get().clone(),
-                                            SavedStVideoConfig {
-                                                id: video_config.id.clone(),
-                                                name: video_config.name.clone(),
-                                                path: file_path,
-                                                dimensions: video_config.dimensions,
-                                                position: SavedPoint {
-                                                    x: video_config.position.x as i32,
-                                                    y: video_config.position.y as i32,
-                                                },
-                                                layer: video_config.layer,
-                                                mouse_path: Some(mouse_positions_path.clone().display().to_string()),
-                                            },
-                                            source_duration_ms
-                                        );
-
-                                        let saved_state = editor
-                                            .saved_state
-                                            .as_ref()
-                                            .expect("Couldn't get saved state");
-                                        let updated_sequence = saved_state
-                                            .sequences
-                                            .iter()
-                                            .find(|s| s.id == current_sequence_id.get().clone())
-                                            .expect("Couldn't get updated sequence");
-                                        
-                                        let sequence_cloned = updated_sequence.clone();
-                                        
-                                        editor.current_sequence_data = Some(sequence_cloned.clone());
-                                        editor.update_motion_paths(&sequence_cloned);
-
-                                        println!("Video item added to editor successfully: {}", video_config.id);
-                                    }
-                                    Command::SubmitMotionForm { description, position, scale, opacity, rotation, delay, duration } => {
-                                        println!("Processing motion form submission from channel");
-
-                                        // Reset canvas hidden state
-                                        // let mut editor_lock = editor_for_render.lock().unwrap();
-                                        // editor.canvas_hidden = false;
-                                        let object_dimensions = editor.last_motion_arrow_object_dimensions.clone();
-                                        let arrow_positions = editor.last_motion_arrow_end_positions.clone();
-                                        // drop(editor_lock);
-                                        
-                                        // println!("Motion form submitted - Description: {}, Position: {}, Scale: {}, Opacity: {}, Dimensions: {:?}", 
-                                        // description.get(), position.get(), scale.get(), opacity.get(), object_dimensions);
-                                
-                                        display_motion_loading.set(true);
-                                        
-                                        // Prepare API data
-                                        let api_data = serde_json::json!({
-                                            "description": description,
-                                            "position": position,
-                                            "scale": scale,
-                                            "opacity": opacity,
-                                            "rotation": rotation,
-                                            "delay": delay,
-                                            "duration": duration,
-                                            "arrow_positions": arrow_positions.map(|(p1, p2)| serde_json::json!({"startX": p1.x, "startY": p1.y, "endX": p2.x, "endY": p2.y})),
-                                            "object_dimensions": object_dimensions.map(|(w, h)| serde_json::json!({"width": w, "height": h}))
-                                        });
-                                        
-                                        // Clone the response sender for the async block
-                                        let response_sender = api_response_tx_for_render.clone();
-                                        
-                                        // Get the polygon_id before the async block to avoid Send issues
-                                        let polygon_id = editor.last_motion_arrow_object_id.to_string();
-                                        let object_type = editor.last_motion_arrow_object_type.clone();
-
-                                        let display_motion_form = display_motion_form.clone();
-                                        let display_motion_loading = display_motion_loading.clone();
-
-                                        println!("api_data {:?} {:?} {:?}", api_data, polygon_id, object_dimensions);
-                                        let tx = tx.clone();
-                                        
-                                        // Spawn the async task - no editor locking here!
-                                        tokio::spawn(async move {
-                                            let client = reqwest::Client::new();
-                                            match client
-                                                .post("http://localhost:3000/api/projects/generate-motion")
-                                                .json(&api_data)
-                                                .send()
-                                                .await
-                                            {
-                                                Ok(response) => {
-                                                    if response.status().is_success() {
-                                                        // Get the raw response text first for logging
-                                                        match response.text().await {
-                                                            Ok(raw_response) => {
-                                                                println!("Raw API response received:");
-                                                                println!("{}", raw_response);
-
-                                                                display_motion_form.set(false);
-                                                                display_motion_loading.set(false);
-                                                                
-                                                                // First try to parse as API format, then convert
-                                                                match serde_json::from_str::<ApiAnimationData>(&raw_response) {
-                                                                    Ok(api_data) => {
-                                                                        println!("Successfully parsed API response format: {:?}", api_data);
-                                                                        
-                                                                        // Convert to the expected format
-                                                                        let animation_data = api_data.to_animation_data(polygon_id, object_type);
-                                                                        println!("Converted to AnimationData format: {:?}", animation_data);
-                                                                        
-                                                                        // Send the response back through the channel
-                                                                        if let Err(e) = response_sender.send(animation_data) {
-                                                                            println!("Failed to send API response through channel: {}", e);
-                                                                        }
-                                                                    }
-                                                                    Err(parse_error) => {
-                                                                        println!("Failed to parse API response as ApiAnimationData:");
-                                                                        println!("Parse error: {}", parse_error);
-                                                                        println!("Raw response that failed to parse: {}", raw_response);
-                                                                        
-                                                                        // Try direct parsing as fallback and show expected formats
-                                                                        println!("\nTrying direct AnimationData parsing for comparison...");
-                                                                        match serde_json::from_str::<stunts_engine::animations::AnimationData>(&raw_response) {
-                                                                            Ok(_) => println!("Direct AnimationData parsing succeeded (unexpected!)"),
-                                                                            Err(direct_error) => {
-                                                                                println!("Direct AnimationData parsing also failed: {}", direct_error);
-                                                                                
-                                                                                // Log expected structures for comparison
-                                                                                println!("\n=== Expected API Format Example ===");
-                                                                                let example_api = ApiAnimationData {
-                                                                                    id: "example_id".to_string(),
-                                                                                    duration: 3000,
-                                                                                    properties: vec![
-                                                                                        ApiAnimationProperty {
-                                                                                            name: "Position".to_string(),
-                                                                                            keyframes: vec![
-                                                                                                ApiKeyframe {
-                                                                                                    time: 0,
-                                                                                                    value: ApiKeyframeValue::Position { Position: ApiPosition { x: 100, y: 100 } },
-                                                                                                    easing: "Linear".to_string(),
-                                                                                                }
-                                                                                            ],
-                                                                                        }
-                                                                                    ],
-                                                                                };
-                                                                                if let Ok(example_json) = serde_json::to_string_pretty(&example_api) {
-                                                                                    println!("{}", example_json);
-                                                                                }
-                                                                                
-                                                                                println!("\n=== Expected AnimationData Format Example ===");
-                                                                                let example_animation_data = stunts_engine::animations::AnimationData::default();
-                                                                                if let Ok(example_json) = serde_json::to_string_pretty(&example_animation_data) {
-                                                                                    println!("{}", example_json);
-                                                                                }
-                                                                            }
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                            Err(text_error) => {
-                                                                println!("Failed to get response text: {}", text_error);
-                                                            }
-                                                        }
-                                                    } else {
-                                                        println!("API call failed with status: {}", response.status());
-                                                        display_motion_form.set(false);
-                                                        display_motion_loading.set(false);
-                                                        // let tx = command_tx.clone();
-                                                        if let Err(e) = tx.send(Command::RedisplayCanvas) {
-                                                            println!("Failed to send redisplay canvas command: {}", e);
-                                                        }
-                                                    }
-                                                }
-                                                Err(e) => println!("API call failed: {}", e),
-                                            }
-                                        });
-                                    }
-                                    Command::UpdateTextProperty { property_key, property_value } => {
-                                        println!("Processing text property update: {} = {}", property_key, property_value);
-                                        
-                                        // Convert property to ObjectProperty enum
-                                        let object_property = match property_key.as_str() {
-                                            "font_family" => ObjectProperty::FontFamily(property_value),
-                                            "font_size" => {
-                                                if let Ok(size) = property_value.parse::<f32>() {
-                                                    ObjectProperty::FontSize(size)
-                                                } else {
-                                                    println!("Invalid font size: {}", property_value);
-                                                    continue;
-                                                }
-                                            },
-                                            "text_content" => ObjectProperty::Text(property_value),
-                                            _ => {
-                                                println!("Unsupported property: {}", property_key);
-                                                continue;
-                                            }
-                                        };
-
-                                        // if let Some(last_text) = editor.text_items.last() {
-                                            let text_id = editor.selected_polygon_id; //  this will be currently selected text object id
-                                            let window_size = WindowSize {
-                                                width: window_size.width,
-                                                height: window_size.height,
-                                            };
-                                            
-                                            if let Err(e) = editor.update_text_property(
-                                                text_id,
-                                                object_property,
-                                            ) {
-                                                println!("Failed to update text property: {}", e);
-                                            } else {
-                                                println!("Text property updated successfully");
-                                            }
-                                        // } else {
-                                        //     println!("No text items to update");
-                                        // }
-                                    }
-                                    Command::TogglePlay => {
-                                        if editor.is_playing {
-                                            println!("Pause Sequence...");
-
-                                            editor.is_playing = false;
-                                            editor.start_playing_time = None;
-
-                                            // should return objects to the startup positions and state
-                                            editor.reset_sequence_objects();
-                                        } else {
-                                            println!("Play Sequence...");
-
-                                            let now = std::time::Instant::now();
-                                            editor.start_playing_time = Some(now);
-                                            editor.is_playing = true;
-                                        }
-                                    }
-                                    Command::ShowCaptureSources => {
-                                        println!("Processing show capture sources command");
-                                        // Get available capture sources (Windows enumeration)
-                                        // Note: This runs in render thread where editor is accessible
-                                        match get_sources() {
-                                            Ok(sources) => {
-                                                let filtered_sources: Vec<WindowInfo> = sources
-                                                    .into_iter()
-                                                    .filter(|s| s.title.len() > 1 && s.rect.width > 100 && s.rect.height > 100)
-                                                    .collect();
-
-                                                // Store sources for UI (can't directly update UI from here)
-                                                // The UI will need to be rebuilt with source buttons
-                                                println!("Found {} valid capture sources", filtered_sources.len());
-                                                for source in &filtered_sources {
-                                                    println!("Source: {} (HWND: {}) - {}x{}",
-                                                        source.title, source.hwnd, source.rect.width, source.rect.height);
-                                                }
-
-                                                // Show the dropdown
-                                                capture_sources_visible.set(true);
-
-                                                    
-
-                                                // Update the signal with available sources
-                                                available_capture_sources.set(filtered_sources.into_iter().map(|source| {
-                                                    DropdownOption {
-                                                        label: format!("{} ({}x{})", source.title, source.rect.width, source.rect.height),
-                                                        // value: source.hwnd.to_string(),
-                                                        value: format!("{}-{}x{}", source.hwnd.to_string(), source.rect.width, source.rect.height)
-                                                    }
-                                                }).collect());
-                                            }
-                                            Err(e) => {
-                                                println!("Failed to get capture sources: {}", e);
-                                            }
-                                        }
-                                    }
-                                    Command::StartScreenCapture { hwnd, width, height } => {
-                                        println!("Processing start screen capture command for HWND: {}", hwnd);
-
-                                        editor.st_capture
-                                            .start_mouse_tracking()
-                                            .expect(
-                                                "Couldn't start mouse tracking",
-                                            );
-
-                                        match editor.st_capture.start_video_capture(hwnd, width as u32, height as u32, project_id.to_string()) {
-                                            Ok(_) => {
-                                                println!("Screen capture started successfully");
-                                                is_recording.set(true);
-                                                capture_button_text.set("Stop Capture".to_string());
-                                                capture_sources_visible.set(false);
-                                            }
-                                            Err(e) => {
-                                                println!("Failed to start screen capture: {}", e);
-                                            }
-                                        }
-                                    }
-                                    Command::StopScreenCapture => {
-                                        println!("Processing stop screen capture command");
-
-                                        let mouse_positions_path = editor.st_capture
-                                                    .stop_mouse_tracking(
-                                                        project_id.to_string(),
-                                                    )
-                                                    .expect(
-                                                        "Couldn't stop mouse tracking",
-                                                    );
-
-                                        match editor.st_capture.stop_video_capture(project_id.to_string()) {
-                                            Ok((video_path, mouse_data_path)) => {
-                                                println!("Screen capture stopped successfully");
-                                                println!("Video saved to: {:?}", video_path);
-                                                println!("Mouse data saved to: {:?}", mouse_data_path);
-
-                                                is_recording.set(false);
-                                                capture_button_text.set("Screen Capture".to_string());
-
-                                                // Video will be automatically added via the completion callback
-                                            }
-                                            Err(e) => {
-                                                println!("Failed to stop screen capture: {}", e);
-                                                is_recording.set(false);
-                                                capture_button_text.set("Screen Capture".to_string());
-                                            }
-                                        }
-                                    }
-                                    Command::Export { progress_tx } => {
-                                        println!("Processing export command");
-                                        
-                                        if is_exporting.get() {
-                                            println!("Export already in progress");
-                                            continue;
-                                        }
-                                        
-                                        is_exporting.set(true);
-                                        export_status.set("Starting export...".to_string());
-                                        export_button_text.set("Exporting...".to_string());
-                                        
-                                        // Get all sequences from editor.saved_state
-                                        let sequences = if let Some(ref saved_state) = editor.saved_state {
-                                            saved_state.sequences.clone()
-                                        } else {
-                                            println!("No saved state found");
-                                            export_status.set("No sequences to export".to_string());
-                                            export_button_text.set("Export".to_string());
-                                            is_exporting.set(false);
-                                            continue;
-                                        };
-                                        
-                                        if sequences.is_empty() {
-                                            println!("No sequences to export");
-                                            export_status.set("No sequences to export".to_string());
-                                            export_button_text.set("Export".to_string());
-                                            is_exporting.set(false);
-                                            continue;
-                                        }
-                                        
-                                        // Create default timeline config with sequences in series
-                                        let mut timeline_sequences = Vec::new();
-                                        let mut current_start_time = 0;
-                                        
-                                        for sequence in &sequences {
-                                            timeline_sequences.push(TimelineSequence {
-                                                id: Uuid::new_v4().to_string(),
-                                                sequence_id: sequence.id.clone(),
-                                                start_time_ms: current_start_time,
-                                                // duration_ms: sequence.duration_ms,
-                                                track_type: stunts_engine::timelines::TrackType::Video,
-                                            });
-                                            current_start_time += sequence.duration_ms;
-                                        }
-                                        
-                                        let timeline_config = SavedTimelineStateConfig {
-                                            timeline_sequences,
-                                        };
-                                        
-                                        // Calculate total duration
-                                        let total_duration_s = sequences.iter()
-                                            .map(|s| s.duration_ms as f64 / 1000.0)
-                                            .sum::<f64>();
-                                        
-                                        if total_duration_s <= 0.0 {
-                                            println!("Invalid sequence duration");
-                                            export_status.set("Invalid sequence duration".to_string());
-                                            export_button_text.set("Export".to_string());
-                                            is_exporting.set(false);
-                                            continue;
-                                        }
-                                        
-                                        // Create exports directory and filename
-                                        let exports_dir = std::env::current_dir()
-                                            .unwrap_or_else(|_| std::path::PathBuf::from("."))
-                                            .join("exports");
-
-                                        if let Err(e) = std::fs::create_dir_all(&exports_dir) {
-                                            println!("Failed to create exports directory: {}", e);
-                                            export_status.set("Failed to create exports directory".to_string());
-                                            export_button_text.set("Export".to_string());
-                                            is_exporting.set(false);
-                                            continue;
-                                        }
-
-                                        let filename = format!("export_{}.mp4", 
-                                            std::time::SystemTime::now()
-                                                .duration_since(std::time::UNIX_EPOCH)
-                                                .unwrap()
-                                                .as_secs());
-                                        
-                                        let output_path = exports_dir.join(filename);
-                                        let output_path_str = output_path.to_str().expect("Invalid export path").to_string();
-                                        
-                                        // Get window size for export
-                                        let export_window_size = WindowSize {
-                                            // width: window_size.width,
-                                            // height: window_size.height,
-                                            width: 1920,
-                                            height: 1080
-                                        };
-                                        
-                                        let project_id_for_export = project_id.to_string();
-                                        
-                                        println!("Starting export thread - sequences: {}, duration: {}s", 
-                                                sequences.len(), total_duration_s);
-                                        
-                                        // Spawn export thread
-                                        std::thread::spawn(move || {
-                                            // Create tokio runtime for this thread
-                                            let rt = tokio::runtime::Runtime::new().unwrap();
-                                            
-                                            rt.block_on(async {
-                                                // Create exporter in the export thread
-                                                let mut exporter = Exporter::new(&output_path_str);
-                                                
-                                                match exporter.run(
-                                                    export_window_size,
-                                                    sequences,
-                                                    timeline_config,
-                                                    export_window_size.width,
-                                                    export_window_size.height,
-                                                    total_duration_s,
-                                                    progress_tx.clone(),
-                                                    project_id_for_export,
-                                                ).await {
-                                                    Ok(_) => {
-                                                        println!("Export completed successfully");
-                                                        let _ = progress_tx.send(ExportProgress::Complete(output_path_str));
-                                                    }
-                                                    Err(e) => {
-                                                        println!("Export failed: {}", e);
-                                                        let _ = progress_tx.send(ExportProgress::Error(e));
-                                                    }
-                                                }
-                                            });
-                                        });
-                                    }
-                                    // Authentication commands
-                                    Command::SubmitSignIn { email, password } => {
-                                        println!("Processing sign in command");
-                                        auth_loading.set(true);
-                                        
-                                        let auth_state = auth_state.clone();
-                                        let local_projects_signal = local_projects_signal.clone();
-                                        let show_auth_form = show_auth_form.clone();
-                                        let show_project_list = show_project_list.clone();
-                                        let auth_loading = auth_loading.clone();
-                                        let editor_for_auth = editor_for_render.clone();
-                                        
-                                        tokio::spawn(async move {
-                                            match authenticate_user(&email, &password).await {
-                                                Ok(auth_response) => {
-                                                    // Create AuthToken with expiry
-                                                    let auth_token = AuthToken {
-                                                        token: auth_response.jwt_data.token.clone(),
-                                                        expiry: Some(chrono::Utc::now() + chrono::Duration::seconds(auth_response.jwt_data.expiry)),
-                                                    };
-                                                    
-                                                    // Store token securely in keyring
-                                                    if let Err(e) = store_auth_token(&auth_token) {
-                                                        println!("Failed to store auth token: {}", e);
-                                                    }
-                                                    
-                                                    // Fetch subscription details
-                                                    match helpers::utilities::fetch_subscription_details(&auth_response.jwt_data.token).await {
-                                                        Ok(subscription) => {
-                                                            let new_auth_state = AuthState {
-                                                                token: Some(auth_token),
-                                                                is_authenticated: true,
-                                                                subscription: Some(subscription),
-                                                            };
-                                                            
-                                                            // Load local projects
-                                                            match load_local_projects() {
-                                                                Ok(projects) => {
-                                                                    auth_state.set(new_auth_state);
-                                                                    local_projects_signal.set(projects);
-                                                                    auth_loading.set(false);
-                                                                    show_auth_form.set(false);
-                                                                    show_project_list.set(true);
-                                                                    
-                                                                    println!("Authentication successful, loaded local projects");
-                                                                }
-                                                                Err(e) => {
-                                                                    println!("Failed to load local projects: {}", e);
-                                                                    auth_loading.set(false);
-                                                                }
-                                                            }
-                                                        }
-                                                        Err(e) => {
-                                                            println!("Failed to fetch subscription details: {}", e);
-                                                            auth_loading.set(false);
-                                                        }
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    println!("Authentication failed: {}", e);
-                                                    auth_loading.set(false);
-                                                }
-                                            }
-                                        });
-                                    }
-                                    Command::SignOut => {
-                                        println!("Processing sign out command");
-                                        
-                                        // Clear stored token
-                                        let _ = clear_stored_auth_token();
-                                        
-                                        // Reset authentication state
-                                        let new_auth_state = AuthState {
-                                            token: None,
-                                            is_authenticated: false,
-                                            subscription: None,
-                                        };
-                                        auth_state.set(new_auth_state);
-                                        local_projects_signal.set(Vec::new());
-                                        selected_project_signal.set(None);
-                                        
-                                        // Update UI visibility
-                                        show_project_list.set(false);
-                                        show_project_creation.set(false);
-                                        show_auth_form.set(true);
-                                        show_editor.set(false);
-                                        
-                                        // Hide canvas
-                                        editor.canvas_hidden = true;
-                                        
-                                        println!("User signed out successfully");
-                                    }
-                                    Command::LoadProjects => {
-                                        println!("Processing load projects command");
-                                        
-                                        match load_local_projects() {
-                                            Ok(projects) => {
-                                                local_projects_signal.set(projects);
-                                                println!("Local projects reloaded successfully");
-                                            }
-                                            Err(e) => {
-                                                println!("Failed to reload local projects: {}", e);
-                                            }
-                                        }
-                                    }
-                                    Command::SelectProject { project_id } => {
-                                        println!("Processing select project command: {}", project_id);
-                                        
-                                        let current_projects = local_projects_signal.get();
-                                        if let Some(project) = current_projects.iter().find(|p| p.project_id == project_id) {
-                                            selected_project_signal.set(Some(project.clone()));
-                                            
-                                            // Load the project state
-                                            match stunts_engine::saved_state::load_project_state(project_id.clone()) {
-                                                Ok(saved_state) => {
-                                                    editor.saved_state = Some(saved_state.clone());
-                                                    editor.project_selected = Some(uuid::Uuid::parse_str(&project_id).unwrap());
-                                                    editor.current_view = "scene".to_string();
-
-                                                    saved_state.sequences.iter().enumerate().for_each(|(i, s)| {
-                                                        editor.restore_sequence_objects(
-                                                            &s,
-                                                            true,
-                                                        );
-                                                    });
-                                                    
-                                                    // Set the current sequence data
-                                                    if let Some(sequence) = saved_state.sequences.first() {
-                                                        current_sequence_id.set(sequence.id.clone());
-                                                        editor.current_sequence_data = Some(sequence.clone());
-
-                                                        editor.polygons.iter_mut().for_each(|p| {
-                                                            p.hidden = true;
-                                                        });
-                                                        editor.image_items.iter_mut().for_each(|i| {
-                                                            i.hidden = true;
-                                                        });
-                                                        editor.text_items.iter_mut().for_each(|t| {
-                                                            t.hidden = true;
-                                                        });
-                                                        editor.video_items.iter_mut().for_each(|t| {
-                                                            t.hidden = true;
-                                                        });
-
-                                                        sequence.active_polygons.iter().for_each(|ap| {
-                                                            let polygon = editor
-                                                                .polygons
-                                                                .iter_mut()
-                                                                .find(|p| p.id.to_string() == ap.id)
-                                                                .expect("Couldn't find polygon");
-                                                            polygon.hidden = false;
-                                                        });
-                                                        sequence.active_image_items.iter().for_each(|si| {
-                                                            let image = editor
-                                                                .image_items
-                                                                .iter_mut()
-                                                                .find(|i| i.id.to_string() == si.id)
-                                                                .expect("Couldn't find image");
-                                                            image.hidden = false;
-                                                        });
-                                                        sequence.active_text_items.iter().for_each(|tr| {
-                                                            let text = editor
-                                                                .text_items
-                                                                .iter_mut()
-                                                                .find(|t| t.id.to_string() == tr.id)
-                                                                .expect("Couldn't find image");
-                                                            text.hidden = false;
-                                                        });
-                                                        sequence.active_video_items.iter().for_each(|tr| {
-                                                            let video = editor
-                                                                .video_items
-                                                                .iter_mut()
-                                                                .find(|t| t.id.to_string() == tr.id)
-                                                                .expect("Couldn't find image");
-                                                            video.hidden = false;
-                                                        });
-
-                                                        let mut background_fill = Some(BackgroundFill::Color([
-                                                            wgpu_to_human(0.8) as i32,
-                                                            wgpu_to_human(0.8) as i32,
-                                                            wgpu_to_human(0.8) as i32,
-                                                            255,
-                                                        ]));
-
-                                                        if sequence.background_fill.is_some() {
-                                                            background_fill = sequence.background_fill.clone();
-                                                        }
-
-                                                        match background_fill.expect("Couldn't get default background fill")
-                                                        {
-                                                            BackgroundFill::Color(fill) => {
-                                                                editor.replace_background(
-                                                                    uuid::Uuid::parse_str(&sequence.id).unwrap(),
-                                                                    rgb_to_wgpu(
-                                                                        fill[0] as u8,
-                                                                        fill[1] as u8,
-                                                                        fill[2] as u8,
-                                                                        fill[3] as f32,
-                                                                    ),
-                                                                );
-                                                            }
-                                                            _ => {
-                                                                println!("Not supported yet...");
-                                                            }
-                                                        }
-
-                                                        editor.update_motion_paths(sequence);
-                                                    }
-                                                    
-                                                    // Hide project selection UI and show main canvas
-                                                    show_project_list.set(false);
-                                                    show_project_creation.set(false);
-                                                    show_editor.set(true);
-                                                    editor.canvas_hidden = false;
-                                                    
-                                                    // Load sequences into dropdown
-                                                    let sequence_options: Vec<DropdownOption> = saved_state.sequences.iter().map(|seq| {
-                                                        DropdownOption {
-                                                            label: seq.name.clone(),
-                                                            value: seq.id.clone(),
-                                                        }
-                                                    }).collect();
-                                                    available_sequences.set(sequence_options);
-                                                    
-                                                    println!("Project selected and loaded: {}", project.project_name);
-                                                }
-                                                Err(e) => {
-                                                    println!("Failed to load project state: {}", e);
-                                                }
-                                            }
-                                        }
-                                    }
-                                    Command::CreateProject { name } => {
-                                        println!("Processing create project command: {}", name);
-                                        
-                                        // Check if user can create projects based on subscription
-                                        let auth_state = auth_state.get();
-                                        if auth_state.can_create_projects() {
-                                            match create_local_project(&name) {
-                                                Ok(new_project) => {
-                                                    // Update local projects list
-                                                    let mut current_projects = local_projects_signal.get();
-                                                    current_projects.push(new_project.clone());
-                                                    local_projects_signal.set(current_projects);
-                                                    
-                                                    // Set as selected project
-                                                    selected_project_signal.set(Some(new_project.clone()));
-                                                    
-                                                    // Load the newly created project state
-                                                    match stunts_engine::saved_state::load_project_state(new_project.project_id.clone()) {
-                                                        Ok(mut saved_state) => {
-                                                            // Create first sequence automatically
-                                                            let sequence_id = Uuid::new_v4();
-                                                            let first_sequence = Sequence {
-                                                                id: sequence_id.to_string(),
-                                                                name: "Sequence 1".to_string(),
-                                                                background_fill: Some(BackgroundFill::Color([
-                                                                    wgpu_to_human(0.8) as i32,
-                                                                    wgpu_to_human(0.8) as i32,
-                                                                    wgpu_to_human(0.8) as i32,
-                                                                    255,
-                                                                ])),
-                                                                duration_ms: 20000,
-                                                                active_polygons: Vec::new(),
-                                                                polygon_motion_paths: Vec::new(),
-                                                                active_text_items: Vec::new(),
-                                                                active_image_items: Vec::new(),
-                                                                active_video_items: Vec::new(),
-                                                            };
-
-                                                            current_sequence_id.set(sequence_id.clone().to_string());
-                                                            
-                                                            saved_state.sequences = vec![first_sequence];
-                                                            saved_state.timeline_state = arrange_sequences_in_series(&mut saved_state.sequences);
-                                                            
-                                                            // Save the updated state
-                                                            let _ = stunts_engine::saved_state::save_saved_state_raw(saved_state.clone());
-                                                            
-                                                            // Update editor with new project and sequence
-                                                            editor.saved_state = Some(saved_state.clone());
-                                                            editor.project_selected = Some(uuid::Uuid::parse_str(&new_project.project_id).unwrap());
-                                                            editor.current_view = "scene".to_string();
-
-                                                            saved_state.sequences.iter().enumerate().for_each(|(i, s)| {
-                                                                editor.restore_sequence_objects(
-                                                                    &s,
-                                                                    true,
-                                                                );
-                                                            });
-                                                            
-                                                            // Set the current sequence data
-                                                            if let Some(sequence) = saved_state.sequences.first() {
-                                                                editor.current_sequence_data = Some(sequence.clone());
-
-                                                                editor.polygons.iter_mut().for_each(|p| {
-                                                                    p.hidden = true;
-                                                                });
-                                                                editor.image_items.iter_mut().for_each(|i| {
-                                                                    i.hidden = true;
-                                                                });
-                                                                editor.text_items.iter_mut().for_each(|t| {
-                                                                    t.hidden = true;
-                                                                });
-                                                                editor.video_items.iter_mut().for_each(|t| {
-                                                                    t.hidden = true;
-                                                                });
-
-                                                                sequence.active_polygons.iter().for_each(|ap| {
-                                                                    let polygon = editor
-                                                                        .polygons
-                                                                        .iter_mut()
-                                                                        .find(|p| p.id.to_string() == ap.id)
-                                                                        .expect("Couldn't find polygon");
-                                                                    polygon.hidden = false;
-                                                                });
-                                                                sequence.active_image_items.iter().for_each(|si| {
-                                                                    let image = editor
-                                                                        .image_items
-                                                                        .iter_mut()
-                                                                        .find(|i| i.id.to_string() == si.id)
-                                                                        .expect("Couldn't find image");
-                                                                    image.hidden = false;
-                                                                });
-                                                                sequence.active_text_items.iter().for_each(|tr| {
-                                                                    let text = editor
-                                                                        .text_items
-                                                                        .iter_mut()
-                                                                        .find(|t| t.id.to_string() == tr.id)
-                                                                        .expect("Couldn't find image");
-                                                                    text.hidden = false;
-                                                                });
-                                                                sequence.active_video_items.iter().for_each(|tr| {
-                                                                    let video = editor
-                                                                        .video_items
-                                                                        .iter_mut()
-                                                                        .find(|t| t.id.to_string() == tr.id)
-                                                                        .expect("Couldn't find image");
-                                                                    video.hidden = false;
-                                                                });
-
-                                                                let mut background_fill = Some(BackgroundFill::Color([
-                                                                    wgpu_to_human(0.8) as i32,
-                                                                    wgpu_to_human(0.8) as i32,
-                                                                    wgpu_to_human(0.8) as i32,
-                                                                    255,
-                                                                ]));
-
-                                                                if sequence.background_fill.is_some() {
-                                                                    background_fill = sequence.background_fill.clone();
-                                                                }
-
-                                                                match background_fill.expect("Couldn't get default background fill")
-                                                                {
-                                                                    BackgroundFill::Color(fill) => {
-                                                                        editor.replace_background(
-                                                                            uuid::Uuid::parse_str(&sequence.id).unwrap(),
-                                                                            rgb_to_wgpu(
-                                                                                fill[0] as u8,
-                                                                                fill[1] as u8,
-                                                                                fill[2] as u8,
-                                                                                fill[3] as f32,
-                                                                            ),
-                                                                        );
-                                                                    }
-                                                                    _ => {
-                                                                        println!("Not supported yet...");
-                                                                    }
-                                                                }
-                                                                
-                                                                editor.update_motion_paths(sequence);
-                                                            }
-                                                            
-                                                            // Hide creation form and show main canvas
-                                                            show_project_creation.set(false);
-                                                            show_project_list.set(false);
-                                                            show_editor.set(true);
-                                                            project_name_text.set("".to_string());
-                                                            editor.canvas_hidden = false;
-                                                            
-                                                            println!("Project created successfully: {}", new_project.project_name);
-                                                        }
-                                                        Err(e) => {
-                                                            println!("Failed to load newly created project state: {}", e);
-                                                        }
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    println!("Failed to create local project: {}", e);
-                                                }
-                                            }
-                                        }
-                                    }
-                                    Command::CreateSequence { name, project_id } => {
-                                        println!("Processing create sequence command: {} for project {}", name, project_id);
-
-                                        editor.canvas_hidden = false;
-                                        
-                                        // editor is already a MutexGuard, no need to lock again
-                                        // Create new sequence with unique ID
-                                        let sequence_id = uuid::Uuid::new_v4().to_string();
-                                        let new_sequence = Sequence {
-                                            id: sequence_id.clone(),
-                                            name: name.clone(),
-                                            duration_ms: 5000, // Default 5 second duration
-                                            background_fill: None,
-                                            active_polygons: Vec::new(),
-                                            active_text_items: Vec::new(),
-                                            active_image_items: Vec::new(),
-                                            active_video_items: Vec::new(),
-                                            polygon_motion_paths: Vec::new(),
-                                        };
-                                        
-                                        // Set as current sequence first
-                                        current_sequence_id.set(sequence_id.clone());
-                                        editor.current_sequence_data = Some(new_sequence.clone());
-
-                                        editor.update_motion_paths(&new_sequence);
-                                        
-                                        // Add to saved state
-                                        if let Some(ref mut saved_state) = editor.saved_state {
-                                            saved_state.sequences.push(new_sequence.clone());
-                                            
-                                            // Save the updated state
-                                            save_saved_state_raw(saved_state.clone());
-                                            
-                                            // Update available sequences dropdown
-                                            let sequence_options: Vec<DropdownOption> = saved_state.sequences.iter().map(|seq| {
-                                                DropdownOption {
-                                                    label: seq.name.clone(),
-                                                    value: seq.id.clone(),
-                                                }
-                                            }).collect();
-                                            available_sequences.set(sequence_options);
-                                        }
-                                    }
-                                    Command::SelectSequence { sequence_id } => {
-                                        println!("Selecting sequence: {}", sequence_id);
-
-                                        editor.canvas_hidden = false;
-                                        
-                                        // editor is already a MutexGuard, no need to lock again
-                                        // Clone the sequence data first to avoid borrowing conflicts
-                                        let sequence_data = if let Some(ref saved_state) = editor.saved_state {
-                                            saved_state.sequences.iter().find(|s| s.id == sequence_id).cloned()
-                                        } else {
-                                            None
-                                        };
-                                        
-                                        if let Some(sequence) = sequence_data {
-                                            // Hide all objects first
-                                            editor.polygons.iter_mut().for_each(|p| p.hidden = true);
-                                            editor.image_items.iter_mut().for_each(|i| i.hidden = true);
-                                            editor.text_items.iter_mut().for_each(|t| t.hidden = true);
-                                            editor.video_items.iter_mut().for_each(|v| v.hidden = true);
-                                            
-                                            // Show objects for this sequence
-                                            sequence.active_polygons.iter().for_each(|ap| {
-                                                if let Some(polygon) = editor.polygons.iter_mut().find(|p| p.id.to_string() == ap.id) {
-                                                    polygon.hidden = false;
-                                                }
-                                            });
-                                            sequence.active_image_items.iter().for_each(|si| {
-                                                if let Some(image) = editor.image_items.iter_mut().find(|i| i.id.to_string() == si.id) {
-                                                    image.hidden = false;
-                                                }
-                                            });
-                                            sequence.active_text_items.iter().for_each(|tr| {
-                                                if let Some(text) = editor.text_items.iter_mut().find(|t| t.id.to_string() == tr.id) {
-                                                    text.hidden = false;
-                                                }
-                                            });
-                                            sequence.active_video_items.iter().for_each(|vi| {
-                                                if let Some(video) = editor.video_items.iter_mut().find(|v| v.id.to_string() == vi.id) {
-                                                    video.hidden = false;
-                                                }
-                                            });
-                                            
-                                            // Set as current sequence
-                                            editor.current_sequence_data = Some(sequence.clone());
-                                            current_sequence_id.set(sequence_id);
-
-                                            editor.update_motion_paths(&sequence);
-                                        }
-                                    }
-                                    Command::LoadSequences => {
-                                        println!("Loading sequences");
-
-                                        editor.canvas_hidden = true;
-                                        
-                                        // editor is already a MutexGuard, no need to lock again
-                                        if let Some(ref saved_state) = editor.saved_state {
-                                            // Update available sequences dropdown
-                                            let sequence_options: Vec<DropdownOption> = saved_state.sequences.iter().map(|seq| {
-                                                DropdownOption {
-                                                    label: seq.name.clone(),
-                                                    value: seq.id.clone(),
-                                                }
-                                            }).collect();
-                                            available_sequences.set(sequence_options);
-                                        }
-                                    }
-                                    Command::ApplyTheme { theme } => {
-                                        println!("Applying theme: {:?}", theme);
-
-                                        let background_color_row = theme[0].trunc() as usize;
-                                        let background_color_column = (theme[0].fract() * 10.0) as usize;
-                                        let background_color_hex = theme_sidebar::THEME_COLORS[background_color_row][background_color_column];
-
-                                        let text_color_row = theme[4].trunc() as usize;
-                                        let text_color_column = (theme[4].fract() * 10.0) as usize;
-                                        let text_color_hex = theme_sidebar::THEME_COLORS[text_color_row][text_color_column];
-
-                                        // Parse hex to RGB
-                                        let background_color = hex_to_rgb(background_color_hex);
-                                        let text_color = hex_to_rgb(text_color_hex);
-                                        let font_index = theme[2];
-
-                                        println!("Updating text color: {:?}", text_color);
-                                        println!("Updating background color: {:?}", background_color);
-
-                                        let text_color_wgpu = rgb_to_wgpu(
-                                            text_color[0] as u8,
-                                            text_color[1] as u8,
-                                            text_color[2] as u8,
-                                            255.0,
-                                        );
-
-                                        let background_color_wgpu = rgb_to_wgpu(
-                                            background_color[0] as u8,
-                                            background_color[1] as u8,
-                                            background_color[2] as u8,
-                                            255.0,
-                                        );
-
-                                        // Update text items for current sequence
-                                        let ids_to_update: Vec<_> = editor
-                                            .text_items
-                                            .iter()
-                                            .filter(|text| {
-                                                text.current_sequence_id.to_string() == current_sequence_id.get()
-                                            })
-                                            .map(|text| text.id)
-                                            .collect();
-
-                                        if let Some(font_data) = editor.font_manager.font_data.get(font_index as usize) {
-                                            let font_id = font_data.0.clone();
-
-                                            for id in ids_to_update.clone() {
-                                                editor.update_text_color(id, text_color);
-                                                editor.update_text_font_family(font_id.clone(), id);
-                                                
-                                                // Update text fill colors
-                                                editor.update_text(id, "red_fill", stunts_engine::editor::InputValue::Number(text_color_wgpu[0] as f32), false);
-                                                editor.update_text(id, "green_fill", stunts_engine::editor::InputValue::Number(text_color_wgpu[1] as f32), false);
-                                                editor.update_text(id, "blue_fill", stunts_engine::editor::InputValue::Number(text_color_wgpu[2] as f32), false);
-                                            }
-                                        }
-
-                                        // loop through polygons and apply text_color (so it contrasts with background)
-                                        let ids_to_update: Vec<_> = editor
-                                            .polygons
-                                            .iter()
-                                            .filter(|poly| {
-                                                poly.current_sequence_id.to_string() == current_sequence_id.get()
-                                            })
-                                            .map(|poly| poly.id)
-                                            .collect();
-
-                                        for id in ids_to_update.clone() {
-                                            editor.update_polygon(id, "red", stunts_engine::editor::InputValue::Number(text_color[0] as f32), false);
-                                            editor.update_polygon(id, "green", stunts_engine::editor::InputValue::Number(text_color[1] as f32), false);
-                                            editor.update_polygon(id, "blue", stunts_engine::editor::InputValue::Number(text_color[2] as f32), false);
-                                        }
-
-                                        // Update background for current sequence
-                                        if let Ok(background_uuid) = uuid::Uuid::parse_str(&current_sequence_id.get()) {
-                                            editor.update_background(
-                                                background_uuid,
-                                                "red",
-                                                stunts_engine::editor::InputValue::Number(background_color[0] as f32),
-                                            );
-                                            editor.update_background(
-                                                background_uuid,
-                                                "green",
-                                                stunts_engine::editor::InputValue::Number(background_color[1] as f32),
-                                            );
-                                            editor.update_background(
-                                                background_uuid,
-                                                "blue",
-                                                stunts_engine::editor::InputValue::Number(background_color[2] as f32),
-                                            );
-                                        }
-
-                                        // Update saved state
-                                        if let Some(saved_state) = editor.saved_state.as_mut() {
-                                            saved_state.sequences.iter_mut().for_each(|s| {
-                                                if s.id == current_sequence_id.get() {
-                                                    // Update text items
-                                                    s.active_text_items.iter_mut().for_each(|t| {
-                                                        t.color = text_color;
-                                                        if let Some(background_fill) = t.background_fill.as_mut() {
-                                                            *background_fill = text_color;
-                                                        }
-                                                    });
-
-                                                    // // Update sequence background
-                                                    // if s.background_fill.is_none() {
-                                                    //     s.background_fill = Some(BackgroundFill::Color([
-                                                    //         wgpu_to_human(0.8) as i32,
-                                                    //         wgpu_to_human(0.8) as i32, 
-                                                    //         wgpu_to_human(0.8) as i32,
-                                                    //         255,
-                                                    //     ]));
-                                                    // }
-
-                                                    // if let Some(BackgroundFill::Color(fill)) = s.background_fill.as_mut() {
-                                                    //     *fill = background_color;
-                                                    // }
-
-                                                    // Just set it directly - simpler and clearer
-                                                    s.background_fill = Some(BackgroundFill::Color(background_color));
-
-                                                }
-                                            });
-                                        }
-
-                                        save_saved_state_raw(editor.saved_state.clone().expect("Couldn't get saved state"));
+                                let mut preferences = preferences.borrow_mut();
+                                let mut ctx = CommandContext {
+                                    editor: &mut editor,
+                                    editor_state: &mut editor_state,
+                                    gpu: Some(GpuContext { device, queue }),
+                                    window_size,
+                                    editor_project_id: project_id,
+                                    selected_project,
+                                    signals: &app_signals,
+                                    command_tx: tx.clone(),
+                                    api_response_tx: api_response_tx_for_render.clone(),
+                                    export_queue: &export_queue,
+                                    preferences: &mut preferences,
+                                };
+
+                                let result = command_dispatcher.borrow_mut().dispatch(command, &mut ctx);
+                                if let Err(e) = &result {
+                                    println!("Command failed: {}", e);
+                                }
 
-                                        println!("Theme applied successfully!");
-                                    }
-                                    Command::RedisplayCanvas => {
-                                        println!("Redisplaying canvas");
-                                        editor.canvas_hidden = false;
-                                    }
-                                    
+                                // Automation clients wait for the result
+                                if let Some(reply) = reply {
+                                    let _ = reply.send(result);
                                 }
                             }
                         } else {
-                            println!("Could not acquire editor lock during render, command not processed");
+                            // Retried next frame
+                            pending_commands.defer();
                         }
                     }
                 }
@@ -3396,15 +2259,3 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     )
 }
-
-fn hex_to_rgb(hex: &str) -> [i32; 4] {
-    let hex = hex.trim_start_matches('#');
-    if hex.len() == 6 {
-        let r = i32::from_str_radix(&hex[0..2], 16).unwrap_or(0);
-        let g = i32::from_str_radix(&hex[2..4], 16).unwrap_or(0);
-        let b = i32::from_str_radix(&hex[4..6], 16).unwrap_or(0);
-        [r, g, b, 255]
-    } else {
-        [128, 128, 128, 255] // fallback gray
-    }
-}
\ No newline at end of file
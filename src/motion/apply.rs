@@ -0,0 +1,167 @@
+use std::time::Duration;
+use stunts_engine::animations::{ObjectType, Sequence};
+use stunts_engine::editor::Editor;
+use stunts_engine::saved_state::save_saved_state_raw;
+use uuid::Uuid;
+
+use super::{merge_animation, GeneratedMotion};
+use crate::editor_state::EditorState;
+
+/// Generated motion on its way from a background task to the render loop, with the
+/// sequence it was requested for. It's dropped if another sequence is open when it arrives.
+#[derive(Debug, Clone)]
+pub enum MotionUpdate {
+    Generated { sequence_id: String, motion: GeneratedMotion },
+    /// Results of Regenerate All, applied together or not at all
+    Batch { sequence_id: String, motions: Vec<GeneratedMotion> },
+}
+
+impl MotionUpdate {
+    pub fn sequence_id(&self) -> &str {
+        match self {
+            MotionUpdate::Generated { sequence_id, .. } | MotionUpdate::Batch { sequence_id, .. } => sequence_id,
+        }
+    }
+
+    /// Whether the sequence the motion was requested for is the one open in the editor
+    pub fn is_for_current_sequence(&self, editor: &Editor) -> bool {
+        editor
+            .current_sequence_data
+            .as_ref()
+            .is_some_and(|sequence| sequence.id == self.sequence_id())
+    }
+}
+
+/// Merge a generated animation into the current sequence and save the project
+pub fn apply_generated_motion(
+    editor: &mut Editor,
+    editor_state: &mut EditorState,
+    motion: GeneratedMotion,
+) -> Result<(), String> {
+    apply_generated_motions(editor, editor_state, vec![motion])
+}
+
+/// Merge several generated animations into the current sequence and save once.
+/// Nothing is changed if any of them can't be merged.
+pub fn apply_generated_motions(
+    editor: &mut Editor,
+    editor_state: &mut EditorState,
+    motions: Vec<GeneratedMotion>,
+) -> Result<(), String> {
+    let count = motions.len();
+    let mut sequence = current_saved_sequence(editor)?;
+    for motion in motions {
+        merge_into_sequence(editor, editor_state, &mut sequence, motion)?;
+    }
+
+    let saved_state = editor.saved_state.as_mut().ok_or("No project is open")?;
+    let saved_sequence = saved_state
+        .sequences
+        .iter_mut()
+        .find(|s| s.id == sequence.id)
+        .ok_or("Couldn't find the current sequence in the saved state")?;
+    *saved_sequence = sequence.clone();
+    let saved_state = saved_state.clone();
+
+    editor.current_sequence_data = Some(sequence.clone());
+
+    // Call update_motion_paths to refresh the editor
+    editor.update_motion_paths(&sequence);
+    editor.canvas_hidden = false;
+
+    save_saved_state_raw(saved_state);
+
+    println!("{} generated animation(s) successfully integrated into sequence", count);
+    Ok(())
+}
+
+/// Show a generated animation's motion path on the canvas without changing the project
+pub fn preview_generated_motion(
+    editor: &mut Editor,
+    editor_state: &mut EditorState,
+    motion: GeneratedMotion,
+) -> Result<(), String> {
+    let mut sequence = current_saved_sequence(editor)?;
+    merge_into_sequence(editor, editor_state, &mut sequence, motion)?;
+
+    editor.update_motion_paths(&sequence);
+    editor.canvas_hidden = false;
+    Ok(())
+}
+
+/// Put the saved motion paths back after previewing
+pub fn end_motion_preview(editor: &mut Editor) {
+    if let Some(sequence) = editor.current_sequence_data.clone() {
+        editor.update_motion_paths(&sequence);
+    }
+}
+
+/// The saved copy of the sequence open in the editor
+fn current_saved_sequence(editor: &Editor) -> Result<Sequence, String> {
+    let current_sequence_id = editor
+        .current_sequence_data
+        .as_ref()
+        .map(|sequence| sequence.id.clone())
+        .ok_or("No sequence is selected")?;
+
+    editor
+        .saved_state
+        .as_ref()
+        .and_then(|saved_state| saved_state.sequences.iter().find(|s| s.id == current_sequence_id))
+        .cloned()
+        .ok_or_else(|| "Couldn't find the current sequence in the saved state".to_string())
+}
+
+/// Merge `motion` into its object's motion path in `sequence`
+fn merge_into_sequence(
+    editor: &Editor,
+    editor_state: &EditorState,
+    sequence: &mut Sequence,
+    motion: GeneratedMotion,
+) -> Result<(), String> {
+    // Clean up data
+    let mut animation = motion.animation;
+    animation.id = Uuid::new_v4().to_string();
+    animation.position = [0, 0];
+
+    let existing = sequence
+        .polygon_motion_paths
+        .iter()
+        .find(|path| path.polygon_id == animation.polygon_id)
+        .cloned();
+    let mut merged_animation = merge_animation(existing.as_ref(), animation, motion.merge_mode);
+
+    let is_video = merged_animation.object_type == ObjectType::VideoItem;
+    if is_video && !merged_animation.properties.iter().any(|prop| prop.property_path == "zoom") {
+        merged_animation.properties.push(editor_state.save_default_zoom());
+    }
+
+    let polygon_id = merged_animation.polygon_id.clone();
+
+    // A video's motion runs for the length of the video; other objects keep their timing
+    if is_video {
+        let video_item_dur_ms = editor
+            .video_items
+            .iter()
+            .find(|v| v.id == polygon_id)
+            .ok_or("Couldn't find video item")?
+            .source_duration_ms;
+
+        println!("video_item_dur_ms {:?}", video_item_dur_ms);
+
+        merged_animation = editor_state.scale_animation(
+            merged_animation,
+            Duration::from_secs_f32((video_item_dur_ms / 1000) as f32),
+        );
+    }
+
+    // Remove any existing motion paths for this polygon_id
+    sequence
+        .polygon_motion_paths
+        .retain(|path| path.polygon_id != polygon_id);
+
+    // Add the merged motion path
+    sequence.polygon_motion_paths.push(merged_animation);
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use stunts_engine::animations::AnimationData;
+
+use super::{GeneratedMotion, MergeMode};
+use crate::helpers::projects::{load_project_file, save_project_file};
+
+const CANDIDATES_FILENAME: &str = "motion_candidates.json";
+
+/// Variants kept per object; the oldest are dropped first
+pub const MAX_CANDIDATES_PER_OBJECT: usize = 20;
+
+/// Most variants a single submit of the motion form may request
+pub const MAX_VARIANTS: usize = 4;
+
+/// One generated variant, kept so it can be previewed and applied later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MotionCandidate {
+    pub id: String,
+    pub created: DateTime<Utc>,
+    pub merge_mode: MergeMode,
+    pub animation: AnimationData,
+    /// Whether this variant was last applied to the object
+    #[serde(default)]
+    pub applied: bool,
+}
+
+impl MotionCandidate {
+    pub fn new(motion: GeneratedMotion) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            created: Utc::now(),
+            merge_mode: motion.merge_mode,
+            animation: motion.animation,
+            applied: false,
+        }
+    }
+
+    pub fn to_generated_motion(&self) -> GeneratedMotion {
+        GeneratedMotion::new(self.animation.clone(), self.merge_mode)
+    }
+}
+
+/// Candidate history of a project, stored next to its saved state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MotionCandidates {
+    /// Variants per object id, oldest first
+    pub objects: HashMap<String, Vec<MotionCandidate>>,
+}
+
+impl MotionCandidates {
+    pub fn load(project_id: &str) -> MotionCandidates {
+        load_project_file(project_id, CANDIDATES_FILENAME)
+    }
+
+    pub fn save(&self, project_id: &str) -> anyhow::Result<()> {
+        save_project_file(project_id, CANDIDATES_FILENAME, self)
+    }
+
+    pub fn for_object(&self, object_id: &str) -> &[MotionCandidate] {
+        self.objects.get(object_id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Where the variant with `candidate_id` is among the object's variants, which changes
+    /// as the oldest are dropped
+    pub fn position(&self, object_id: &str, candidate_id: &str) -> Option<usize> {
+        self.for_object(object_id)
+            .iter()
+            .position(|candidate| candidate.id == candidate_id)
+    }
+
+    pub fn get(&self, object_id: &str, candidate_id: &str) -> Option<&MotionCandidate> {
+        self.for_object(object_id)
+            .iter()
+            .find(|candidate| candidate.id == candidate_id)
+    }
+
+    /// Add a variant for the object its animation belongs to and return its id
+    pub fn add(&mut self, candidate: MotionCandidate) -> String {
+        let candidate_id = candidate.id.clone();
+        let candidates = self
+            .objects
+            .entry(candidate.animation.polygon_id.clone())
+            .or_default();
+
+        candidates.push(candidate);
+
+        if candidates.len() > MAX_CANDIDATES_PER_OBJECT {
+            let excess = candidates.len() - MAX_CANDIDATES_PER_OBJECT;
+            candidates.drain(..excess);
+        }
+
+        candidate_id
+    }
+
+    /// Mark the variant with `candidate_id` as the one applied to the object
+    pub fn mark_applied(&mut self, object_id: &str, candidate_id: &str) {
+        if let Some(candidates) = self.objects.get_mut(object_id) {
+            for candidate in candidates.iter_mut() {
+                candidate.applied = candidate.id == candidate_id;
+            }
+        }
+    }
+}
+
+/// "Variant 2 of 5 · 14:32 (applied)" for the variants panel
+pub fn candidate_label(candidates: &[MotionCandidate], candidate_id: &str) -> String {
+    match candidates.iter().position(|candidate| candidate.id == candidate_id) {
+        Some(index) => {
+            let candidate = &candidates[index];
+            format!(
+                "Variant {} of {} · {}{}",
+                index + 1,
+                candidates.len(),
+                candidate.created.with_timezone(&Local).format("%b %e %H:%M"),
+                if candidate.applied { " (applied)" } else { "" }
+            )
+        }
+        None if candidates.is_empty() => "No variants yet".to_string(),
+        None => format!("{} variants · the previewed one was dropped", candidates.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use stunts_engine::animations::ObjectType;
+
+    fn candidate() -> MotionCandidate {
+        MotionCandidate::new(GeneratedMotion::candidate(
+            AnimationData {
+                id: uuid::Uuid::new_v4().to_string(),
+                object_type: ObjectType::Polygon,
+                polygon_id: "object".to_string(),
+                duration: Duration::from_millis(3000),
+                start_time_ms: 0,
+                properties: Vec::new(),
+                position: [0, 0],
+            },
+            MergeMode::ReplaceAll,
+        ))
+    }
+
+    #[test]
+    fn ids_still_find_a_variant_after_the_oldest_are_dropped() {
+        let mut candidates = MotionCandidates::default();
+        let first_kept = (0..MAX_CANDIDATES_PER_OBJECT)
+            .map(|_| candidates.add(candidate()))
+            .nth(1)
+            .unwrap();
+        assert_eq!(candidates.position("object", &first_kept), Some(1));
+
+        let newest = candidates.add(candidate());
+
+        assert_eq!(candidates.for_object("object").len(), MAX_CANDIDATES_PER_OBJECT);
+        assert_eq!(candidates.position("object", &first_kept), Some(0));
+        assert_eq!(candidates.position("object", &newest), Some(MAX_CANDIDATES_PER_OBJECT - 1));
+
+        candidates.mark_applied("object", &first_kept);
+        assert!(candidates.get("object", &first_kept).unwrap().applied);
+        assert_eq!(candidates.for_object("object").iter().filter(|c| c.applied).count(), 1);
+    }
+}
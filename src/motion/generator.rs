@@ -0,0 +1,657 @@
+use stunts_engine::editor::Point;
+
+use super::MotionPrompt;
+use crate::api::{ApiAnimationData, ApiAnimationProperty, ApiKeyframe, ApiKeyframeValue, ApiPosition};
+
+/// Length of a generated animation when the prompt doesn't say
+const DEFAULT_DURATION_MS: i32 = 3000;
+
+/// Easings tried in turn by the extra variants of a prompt
+const VARIANT_EASINGS: [&str; 3] = ["EaseOut", "EaseIn", "Linear"];
+/// Length of each extra variant relative to the first
+const VARIANT_PACE: [f32; 3] = [0.8, 1.25, 1.0];
+
+/// Height of a bounce, in canvas pixels
+const BOUNCE_HEIGHT: f32 = 50.0;
+/// Offset of a gentle float or hover
+const FLOAT_HEIGHT: f32 = 15.0;
+/// Offset of a shake or wiggle
+const SHAKE_OFFSET: f32 = 10.0;
+
+/// Build an animation from the motion form without the cloud service.
+///
+/// Each property field is matched against common phrases ("fades in", "spins 360 degrees",
+/// "bounces up and down", "grows from small to normal"); an empty field falls back to the
+/// object description. The object travels along the arrows through each waypoint in order,
+/// at a steady speed; without waypoints it stays at `origin`, the object's current position.
+/// The ideas in `animation_ideas.rs` cover the phrasing this is meant to understand.
+pub fn generate_motion(prompt: &MotionPrompt, waypoints: &[Point], origin: Point) -> ApiAnimationData {
+    let duration = DEFAULT_DURATION_MS;
+
+    let path: Vec<(f32, f32)> = if waypoints.is_empty() {
+        vec![(origin.x, origin.y)]
+    } else {
+        waypoints.iter().map(|point| (point.x, point.y)).collect()
+    };
+
+    let phrase = |field: &str| {
+        if field.trim().is_empty() {
+            prompt.description.to_lowercase()
+        } else {
+            field.to_lowercase()
+        }
+    };
+
+    ApiAnimationData {
+        id: uuid::Uuid::new_v4().to_string(),
+        duration,
+        properties: vec![
+            position_property(&phrase(&prompt.position), &path, duration),
+            rotation_property(&phrase(&prompt.rotation), duration),
+            scale_property(&phrase(&prompt.scale), duration),
+            opacity_property(&phrase(&prompt.opacity), duration),
+        ],
+    }
+}
+
+/// Another take on the same prompt. Variant 0 is `generate_motion` itself; later
+/// variants change the pacing and easing.
+pub fn generate_motion_variant(
+    prompt: &MotionPrompt,
+    waypoints: &[Point],
+    origin: Point,
+    variant: usize,
+) -> ApiAnimationData {
+    let mut animation = generate_motion(prompt, waypoints, origin);
+    if variant == 0 {
+        return animation;
+    }
+
+    let slot = (variant - 1) % VARIANT_EASINGS.len();
+    let pace = VARIANT_PACE[slot];
+
+    animation.duration = (animation.duration as f32 * pace).round() as i32;
+    for property in &mut animation.properties {
+        for keyframe in &mut property.keyframes {
+            keyframe.time = (keyframe.time as f32 * pace).round() as i32;
+            keyframe.easing = VARIANT_EASINGS[slot].to_string();
+        }
+    }
+
+    animation
+}
+
+fn position_property(phrase: &str, path: &[(f32, f32)], duration: i32) -> ApiAnimationProperty {
+    let stays = mentions(phrase, &["stationary", "stays", "remains", "rests", "in place", "still", "no movement"]);
+    let path = if stays { &path[..1] } else { path };
+    let easing = easing_for(phrase);
+
+    // Offset applied on top of the path at each step, as (x, y)
+    let offsets: Vec<(f32, f32)> = if mentions(phrase, &["bounce", "bouncing", "hops", "jumps"]) {
+        vec![(0.0, 0.0), (0.0, -BOUNCE_HEIGHT), (0.0, 0.0), (0.0, -BOUNCE_HEIGHT), (0.0, 0.0)]
+    } else if mentions(phrase, &["float", "hover", "drift", "bob", "sway", "up and down"]) {
+        vec![(0.0, 0.0), (0.0, -FLOAT_HEIGHT), (0.0, 0.0), (0.0, FLOAT_HEIGHT), (0.0, 0.0)]
+    } else if mentions(phrase, &["shake", "shakes", "wiggle", "vibrate", "jitter", "side to side"]) {
+        vec![
+            (0.0, 0.0),
+            (-SHAKE_OFFSET, 0.0),
+            (SHAKE_OFFSET, 0.0),
+            (-SHAKE_OFFSET, 0.0),
+            (SHAKE_OFFSET, 0.0),
+            (0.0, 0.0),
+        ]
+    } else {
+        vec![(0.0, 0.0), (0.0, 0.0)]
+    };
+
+    // A keyframe at every step of the pattern and at every waypoint, so the object
+    // passes through each one
+    let steps = offsets.len() - 1;
+    let mut fractions: Vec<f32> = (0..=steps).map(|step| step as f32 / steps as f32).collect();
+    fractions.extend(waypoint_fractions(path));
+    fractions.sort_by(|a, b| a.total_cmp(b));
+    fractions.dedup_by(|a, b| (*a - *b).abs() < 0.001);
+
+    let keyframes = fractions
+        .into_iter()
+        .map(|fraction| {
+            let (x, y) = along(path, fraction);
+            let (offset_x, offset_y) = offset_at(&offsets, fraction);
+
+            keyframe(
+                at(duration, fraction),
+                ApiKeyframeValue::Position {
+                    Position: ApiPosition {
+                        x: (x + offset_x).round() as i32,
+                        y: (y + offset_y).round() as i32,
+                    },
+                },
+                &easing,
+            )
+        })
+        .collect();
+
+    ApiAnimationProperty {
+        name: "Position".to_string(),
+        keyframes,
+    }
+}
+
+/// How far along the path each inner waypoint is, as a fraction of its length
+fn waypoint_fractions(path: &[(f32, f32)]) -> Vec<f32> {
+    let total = path_length(path);
+    if total <= 0.0 || path.len() < 3 {
+        return Vec::new();
+    }
+
+    let mut travelled = 0.0;
+    path.windows(2)
+        .take(path.len() - 2)
+        .map(|segment| {
+            travelled += distance(segment[0], segment[1]);
+            travelled / total
+        })
+        .collect()
+}
+
+/// The point `fraction` of the way along the path, by distance
+fn along(path: &[(f32, f32)], fraction: f32) -> (f32, f32) {
+    let total = path_length(path);
+    if total <= 0.0 {
+        return path[0];
+    }
+
+    let mut remaining = total * fraction.clamp(0.0, 1.0);
+    for segment in path.windows(2) {
+        let length = distance(segment[0], segment[1]);
+        if remaining <= length && length > 0.0 {
+            let t = remaining / length;
+            return (
+                segment[0].0 + (segment[1].0 - segment[0].0) * t,
+                segment[0].1 + (segment[1].1 - segment[0].1) * t,
+            );
+        }
+        remaining -= length;
+    }
+
+    path[path.len() - 1]
+}
+
+/// The pattern offset at `fraction`, interpolated between its steps
+fn offset_at(offsets: &[(f32, f32)], fraction: f32) -> (f32, f32) {
+    let position = fraction.clamp(0.0, 1.0) * (offsets.len() - 1) as f32;
+    let index = (position.floor() as usize).min(offsets.len() - 2);
+    let t = position - index as f32;
+    let (from, to) = (offsets[index], offsets[index + 1]);
+
+    (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+}
+
+fn path_length(path: &[(f32, f32)]) -> f32 {
+    path.windows(2).map(|segment| distance(segment[0], segment[1])).sum()
+}
+
+fn distance(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+}
+
+fn rotation_property(phrase: &str, duration: i32) -> ApiAnimationProperty {
+    let rotation = |degrees: i32| ApiKeyframeValue::Rotation { Rotation: degrees };
+
+    let values: Vec<i32> = if mentions(phrase, &["no rotation", "upright", "doesn't rotate", "without rotating"]) {
+        vec![0, 0]
+    } else if mentions(phrase, &["wobble", "wiggle", "rock", "sway", "tilt back and forth", "side to side"]) {
+        vec![0, -15, 15, -15, 15, 0]
+    } else if mentions(phrase, &["tilt", "lean"]) {
+        vec![0, direction(phrase) * 15]
+    } else if mentions(phrase, &["spin", "rotat", "turn", "twirl", "roll", "flip", "degree"]) {
+        let mut degrees = first_number(phrase)
+            .map(|degrees| degrees.round() as i32)
+            .unwrap_or_else(|| {
+                if mentions(phrase, &["half"]) {
+                    180
+                } else if mentions(phrase, &["quarter"]) {
+                    90
+                } else if mentions(phrase, &["continuous", "constantly", "repeatedly"]) {
+                    720
+                } else {
+                    360
+                }
+            });
+
+        if mentions(phrase, &["twice"]) {
+            degrees *= 2;
+        }
+
+        vec![0, direction(phrase) * degrees]
+    } else {
+        vec![0, 0]
+    };
+
+    // Spins look best at a constant speed
+    let easing = if values.len() == 2 && values[1] != 0 && !mentions(phrase, &["smooth", "gentl", "eas"]) {
+        "Linear".to_string()
+    } else {
+        easing_for(phrase)
+    };
+
+    ApiAnimationProperty {
+        name: "Rotation".to_string(),
+        keyframes: spread(&values, duration, rotation, &easing),
+    }
+}
+
+fn scale_property(phrase: &str, duration: i32) -> ApiAnimationProperty {
+    let scale = |percent: i32| ApiKeyframeValue::Scale { Scale: percent };
+    let subtle = mentions(phrase, &["slight", "subtl", "gentl", "barely"]);
+
+    let values: Vec<i32> = if mentions(phrase, &["pulse", "pulsing", "breath", "beat", "throb", "larger and smaller", "bigger and smaller", "grow and shrink", "grows and shrinks"]) {
+        let peak = if subtle { 105 } else { 115 };
+        vec![100, peak, 100, peak, 100]
+    } else if mentions(phrase, &["pop"]) {
+        vec![0, 120, 100]
+    } else if mentions(phrase, &["from tiny", "from nothing", "from zero", "from a dot"]) {
+        if mentions(phrase, &["bounce"]) {
+            vec![0, 115, 100]
+        } else {
+            vec![0, 100]
+        }
+    } else if mentions(phrase, &["from small", "starts small", "from smaller"]) {
+        if mentions(phrase, &["bounce"]) {
+            vec![50, 115, 100]
+        } else {
+            vec![50, 100]
+        }
+    } else if mentions(phrase, &["shrink", "smaller", "scales down", "zooms out"]) {
+        let target = if mentions(phrase, &["nothing", "zero", "disappear", "vanish"]) {
+            0
+        } else {
+            first_number(phrase).map(|n| n.round() as i32).unwrap_or(50)
+        };
+        vec![100, target]
+    } else if mentions(phrase, &["fullscreen", "full screen", "fills the screen"]) {
+        vec![100, 250]
+    } else if mentions(phrase, &["grow", "larger", "bigger", "expand", "scales up", "zooms in", "enlarge"]) {
+        let target = first_number(phrase)
+            .map(|n| n.round() as i32)
+            .unwrap_or(if subtle { 110 } else { 150 });
+        vec![100, target]
+    } else {
+        vec![100, 100]
+    };
+
+    ApiAnimationProperty {
+        name: "Scale".to_string(),
+        keyframes: spread(&values, duration, scale, &easing_for(phrase)),
+    }
+}
+
+fn opacity_property(phrase: &str, duration: i32) -> ApiAnimationProperty {
+    let opacity = |percent: i32| ApiKeyframeValue::Opacity { Opacity: percent };
+
+    let values: Vec<i32> = if mentions(phrase, &["fades in and out", "fade in and out", "flicker", "blink", "twinkle", "in and out"]) {
+        vec![0, 100, 0]
+    } else if mentions(phrase, &["fades in", "fade in", "appears", "materializ", "reveals"]) {
+        vec![0, 100]
+    } else if mentions(phrase, &["fades out", "fade out", "fades away", "disappears", "vanish"]) {
+        vec![100, 0]
+    } else if mentions(phrase, &["semi-transparent", "semi transparent", "translucent", "half"]) {
+        vec![50, 50]
+    } else if let Some(percent) = phrase.contains('%').then(|| first_number(phrase)).flatten() {
+        let percent = percent.round().clamp(0.0, 100.0) as i32;
+        vec![percent, percent]
+    } else {
+        vec![100, 100]
+    };
+
+    ApiAnimationProperty {
+        name: "Opacity".to_string(),
+        keyframes: spread(&values, duration, opacity, &easing_for(phrase)),
+    }
+}
+
+/// Keyframes for `values`, evenly spaced over the animation
+fn spread(
+    values: &[i32],
+    duration: i32,
+    to_value: impl Fn(i32) -> ApiKeyframeValue,
+    easing: &str,
+) -> Vec<ApiKeyframe> {
+    let steps = (values.len() - 1).max(1);
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(step, value)| keyframe(at(duration, step as f32 / steps as f32), to_value(*value), easing))
+        .collect()
+}
+
+fn keyframe(time: i32, value: ApiKeyframeValue, easing: &str) -> ApiKeyframe {
+    ApiKeyframe {
+        time,
+        value,
+        easing: easing.to_string(),
+        ..ApiKeyframe::default()
+    }
+}
+
+fn at(duration: i32, fraction: f32) -> i32 {
+    (duration as f32 * fraction).round() as i32
+}
+
+fn easing_for(phrase: &str) -> String {
+    let easing = if mentions(phrase, &["steady", "steadily", "constant", "linear", "evenly"]) {
+        "Linear"
+    } else if mentions(phrase, &["accelerat", "speeds up", "drop", "falls"]) {
+        "EaseIn"
+    } else if mentions(phrase, &["decelerat", "slows", "settles", "lands", "elastic", "bounce"]) {
+        "EaseOut"
+    } else {
+        "EaseInOut"
+    };
+
+    easing.to_string()
+}
+
+/// -1 for counter-clockwise or leftward phrasing, otherwise 1
+fn direction(phrase: &str) -> i32 {
+    if mentions(phrase, &["counter", "counterclockwise", "anticlockwise", "anti-clockwise", "left", "leftward", "backward"]) {
+        -1
+    } else {
+        1
+    }
+}
+
+/// Whether the phrase has any of `words` as whole words, so "roll" matches "rolls" and
+/// "rolling" but not "scroll". Stems like "rotat" match "rotates" and "rotation", and a
+/// key of several words matches them in a row.
+fn mentions(phrase: &str, words: &[&str]) -> bool {
+    let tokens = tokenize(phrase);
+
+    words.iter().any(|key| {
+        let key = tokenize(key);
+        !key.is_empty()
+            && tokens
+                .windows(key.len())
+                .any(|window| window.iter().zip(&key).all(|(token, word)| inflects(token, word)))
+    })
+}
+
+/// The words of `text`, split on anything that isn't a letter or digit
+fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Endings a word may take and still count as a mention of it
+const INFLECTIONS: [&str; 12] = ["", "s", "es", "e", "d", "ed", "ing", "ly", "y", "ion", "ions", "th"];
+
+/// Whether `token` is `word` with one of the `INFLECTIONS`, allowing a doubled last
+/// letter as in "spinning" or "popped"
+fn inflects(token: &str, word: &str) -> bool {
+    let Some(ending) = token.strip_prefix(word) else {
+        return false;
+    };
+
+    let ending = match (word.chars().last(), ending.chars().next()) {
+        (Some(last), Some(first)) if last == first && ending.len() > first.len_utf8() => &ending[first.len_utf8()..],
+        _ => ending,
+    };
+
+    INFLECTIONS.contains(&ending)
+}
+
+/// The first number in the phrase, e.g. 360 in "spins 360 degrees"
+fn first_number(phrase: &str) -> Option<f32> {
+    let start = phrase.find(|c: char| c.is_ascii_digit())?;
+    let number: String = phrase[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+
+    number.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation_ideas::get_animation_ideas;
+
+    fn prompt(field: &str, phrase: &str) -> MotionPrompt {
+        let mut prompt = MotionPrompt::default();
+        match field {
+            "position" => prompt.position = phrase.to_string(),
+            "rotation" => prompt.rotation = phrase.to_string(),
+            "scale" => prompt.scale = phrase.to_string(),
+            "opacity" => prompt.opacity = phrase.to_string(),
+            _ => prompt.description = phrase.to_string(),
+        }
+        prompt
+    }
+
+    fn origin() -> Point {
+        Point { x: 0.0, y: 0.0 }
+    }
+
+    fn property<'a>(animation: &'a ApiAnimationData, name: &str) -> &'a ApiAnimationProperty {
+        animation
+            .properties
+            .iter()
+            .find(|property| property.name == name)
+            .unwrap_or_else(|| panic!("no {} property", name))
+    }
+
+    /// Keyframe values of a rotation, scale or opacity property
+    fn values(property: &ApiAnimationProperty) -> Vec<i32> {
+        property
+            .keyframes
+            .iter()
+            .map(|keyframe| match &keyframe.value {
+                ApiKeyframeValue::Rotation { Rotation: value }
+                | ApiKeyframeValue::Scale { Scale: value }
+                | ApiKeyframeValue::Opacity { Opacity: value } => *value,
+                other => panic!("unexpected value {:?}", other),
+            })
+            .collect()
+    }
+
+    fn positions(property: &ApiAnimationProperty) -> Vec<(i32, i32)> {
+        property
+            .keyframes
+            .iter()
+            .map(|keyframe| match &keyframe.value {
+                ApiKeyframeValue::Position { Position: position } => (position.x, position.y),
+                other => panic!("unexpected value {:?}", other),
+            })
+            .collect()
+    }
+
+    fn times(property: &ApiAnimationProperty) -> Vec<i32> {
+        property.keyframes.iter().map(|keyframe| keyframe.time).collect()
+    }
+
+    fn easing(property: &ApiAnimationProperty) -> &str {
+        &property.keyframes[0].easing
+    }
+
+    #[test]
+    fn rotation_phrases() {
+        let cases: &[(&str, &[i32])] = &[
+            ("No rotation", &[0, 0]),
+            ("Spins smoothly and continuously", &[0, 720]),
+            ("Wiggles slightly back and forth", &[0, -15, 15, -15, 15, 0]),
+            ("Slowly tilts to show depth", &[0, 15]),
+            ("Rotates very slowly on lazy susan", &[0, 360]),
+            ("spins 90 degrees counter-clockwise", &[0, -90]),
+            ("flips twice", &[0, 720]),
+            ("turns half way to the left", &[0, -180]),
+            ("rocks back and forth", &[0, -15, 15, -15, 15, 0]),
+            ("spinning clockwise", &[0, 360]),
+            ("", &[0, 0]),
+            // Words that merely contain a motion word
+            ("a rocket", &[0, 0]),
+            ("scrolls into view", &[0, 0]),
+            ("returns to the start", &[0, 0]),
+        ];
+
+        for (phrase, expected) in cases {
+            let animation = generate_motion(&prompt("rotation", phrase), &[], origin());
+            assert_eq!(values(property(&animation, "Rotation")), *expected, "{:?}", phrase);
+        }
+    }
+
+    #[test]
+    fn scale_phrases() {
+        let cases: &[(&str, &[i32])] = &[
+            ("Pulses just a bit larger occasionally", &[100, 115, 100, 115, 100]),
+            ("Pulses subtly", &[100, 105, 100, 105, 100]),
+            ("Grows from small to normal with gentle bounce", &[50, 115, 100]),
+            ("Appears with soft growth", &[100, 150]),
+            ("Maintains steady size", &[100, 100]),
+            ("pops in", &[0, 120, 100]),
+            ("grows from nothing", &[0, 100]),
+            ("shrinks to nothing", &[100, 0]),
+            ("grows to 200%", &[100, 200]),
+            ("zooms to fill the full screen", &[100, 250]),
+        ];
+
+        for (phrase, expected) in cases {
+            let animation = generate_motion(&prompt("scale", phrase), &[], origin());
+            assert_eq!(values(property(&animation, "Scale")), *expected, "{:?}", phrase);
+        }
+    }
+
+    #[test]
+    fn opacity_phrases() {
+        let cases: &[(&str, &[i32])] = &[
+            ("Fades in smoothly", &[0, 100]),
+            ("Stays fully visible", &[100, 100]),
+            ("Fades out", &[100, 0]),
+            ("Blinks", &[0, 100, 0]),
+            ("Semi-transparent", &[50, 50]),
+            ("30% visible", &[30, 30]),
+        ];
+
+        for (phrase, expected) in cases {
+            let animation = generate_motion(&prompt("opacity", phrase), &[], origin());
+            assert_eq!(values(property(&animation, "Opacity")), *expected, "{:?}", phrase);
+        }
+    }
+
+    #[test]
+    fn position_phrases_without_waypoints() {
+        let cases: &[(&str, &[(i32, i32)])] = &[
+            ("Gently bobs up and down", &[(0, 0), (0, -15), (0, 0), (0, 15), (0, 0)]),
+            ("bounces", &[(0, 0), (0, -50), (0, 0), (0, -50), (0, 0)]),
+            ("shakes", &[(0, 0), (-10, 0), (10, 0), (-10, 0), (10, 0), (0, 0)]),
+            ("Remains perfectly centered", &[(0, 0), (0, 0)]),
+        ];
+
+        for (phrase, expected) in cases {
+            let animation = generate_motion(&prompt("position", phrase), &[], origin());
+            assert_eq!(positions(property(&animation, "Position")), *expected, "{:?}", phrase);
+        }
+    }
+
+    #[test]
+    fn position_without_waypoints_stays_at_the_origin() {
+        let origin = Point { x: 300.0, y: 200.0 };
+
+        let animation = generate_motion(&prompt("position", "bounces"), &[], origin);
+        assert_eq!(
+            positions(property(&animation, "Position")),
+            vec![(300, 200), (300, 150), (300, 200), (300, 150), (300, 200)]
+        );
+
+        let animation = generate_motion(&prompt("position", "Remains perfectly centered"), &[], origin);
+        assert_eq!(positions(property(&animation, "Position")), vec![(300, 200), (300, 200)]);
+    }
+
+    #[test]
+    fn position_follows_waypoints() {
+        let waypoints = [
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 100.0, y: 0.0 },
+            Point { x: 100.0, y: 100.0 },
+        ];
+
+        let animation = generate_motion(&prompt("position", "moves steadily"), &waypoints, origin());
+        let position = property(&animation, "Position");
+        assert_eq!(positions(position), vec![(0, 0), (100, 0), (100, 100)]);
+        assert_eq!(times(position), vec![0, 1500, 3000]);
+        assert_eq!(easing(position), "Linear");
+
+        let animation = generate_motion(&prompt("position", "stays still"), &waypoints, origin());
+        assert_eq!(positions(property(&animation, "Position")), vec![(0, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn easing_phrases() {
+        let cases = [
+            ("rotation", "flips twice", "Rotation", "Linear"),
+            ("rotation", "Spins smoothly and continuously", "Rotation", "EaseInOut"),
+            ("position", "falls", "Position", "EaseIn"),
+            ("position", "Settles into place", "Position", "EaseOut"),
+            ("opacity", "Fades in smoothly", "Opacity", "EaseInOut"),
+        ];
+
+        for (field, phrase, name, expected) in cases {
+            let animation = generate_motion(&prompt(field, phrase), &[], origin());
+            assert_eq!(easing(property(&animation, name)), expected, "{:?}", phrase);
+        }
+    }
+
+    #[test]
+    fn empty_fields_fall_back_to_the_description() {
+        let animation = generate_motion(&prompt("description", "a ball that bounces and fades out"), &[], origin());
+
+        assert_eq!(positions(property(&animation, "Position")), vec![(0, 0), (0, -50), (0, 0), (0, -50), (0, 0)]);
+        assert_eq!(values(property(&animation, "Opacity")), vec![100, 0]);
+        assert_eq!(values(property(&animation, "Rotation")), vec![0, 0]);
+    }
+
+    #[test]
+    fn variants_change_pace_and_easing() {
+        let prompt = prompt("opacity", "fades in");
+
+        let first = generate_motion_variant(&prompt, &[], origin(), 0);
+        assert_eq!(first.duration, DEFAULT_DURATION_MS);
+        assert_eq!(times(property(&first, "Opacity")), vec![0, 3000]);
+
+        let second = generate_motion_variant(&prompt, &[], origin(), 1);
+        assert_eq!(second.duration, 2400);
+        assert_eq!(times(property(&second, "Opacity")), vec![0, 2400]);
+        assert_eq!(easing(property(&second, "Opacity")), "EaseOut");
+        assert_eq!(values(property(&second, "Opacity")), vec![0, 100]);
+
+        let fourth = generate_motion_variant(&prompt, &[], origin(), 4);
+        assert_eq!(fourth.duration, second.duration);
+    }
+
+    #[test]
+    fn every_animation_idea_generates_motion() {
+        for idea in get_animation_ideas() {
+            let prompt = MotionPrompt {
+                description: idea.object_description.clone(),
+                position: idea.position_description.clone(),
+                rotation: idea.rotation_description.clone(),
+                scale: idea.scale_description.clone(),
+                opacity: idea.opacity_description.clone(),
+                ..MotionPrompt::default()
+            };
+
+            let animation = generate_motion(&prompt, &[], origin());
+            assert_eq!(animation.properties.len(), 4, "{}", idea.object_description);
+            for property in &animation.properties {
+                let times = times(property);
+                assert!(times.len() >= 2, "{}: {}", idea.object_description, property.name);
+                assert_eq!(times[0], 0, "{}: {}", idea.object_description, property.name);
+                assert_eq!(*times.last().unwrap(), animation.duration, "{}: {}", idea.object_description, property.name);
+                assert!(times.windows(2).all(|pair| pair[0] <= pair[1]), "{}: {}", idea.object_description, property.name);
+            }
+        }
+    }
+}
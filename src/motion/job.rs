@@ -0,0 +1,347 @@
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+use stunts_engine::saved_state::get_ground_truth_dir;
+
+use crate::api::Unauthorized;
+use crate::export::CancelToken;
+
+/// Folder next to the app preferences where unreadable responses are kept for bug reports
+const FAILED_RESPONSES_DIRNAME: &str = "failed_motion_responses";
+
+/// Why a motion generation request didn't produce an animation
+#[derive(Debug, Clone)]
+pub enum GenerationError {
+    /// The backend refused the request (401 or 403)
+    Auth { status: u16 },
+    /// The backend answered with an error status; 5xx are retried
+    Server { status: u16, message: String },
+    /// The response couldn't be turned into an animation
+    Parse {
+        message: String,
+        /// Where the raw response was saved, if saving worked
+        raw_response: Option<PathBuf>,
+    },
+    /// The backend couldn't be reached
+    Network(String),
+    /// No answer within the job's timeout
+    TimedOut(Duration),
+    Cancelled,
+}
+
+impl GenerationError {
+    /// Classify an unsuccessful response
+    pub fn from_status(status: reqwest::StatusCode, body: String) -> Self {
+        match status.as_u16() {
+            401 | 403 => GenerationError::Auth { status: status.as_u16() },
+            status => GenerationError::Server { status, message: body },
+        }
+    }
+
+    /// A response that couldn't be used, saving it so it can be attached to a bug report
+    pub fn parse(message: String, raw_response: &str) -> Self {
+        GenerationError::Parse {
+            message,
+            raw_response: save_failed_response(raw_response),
+        }
+    }
+
+    /// The session is gone, so falling back to the built-in generator would only hide that
+    /// the user has to sign in again
+    pub fn needs_sign_in(&self) -> bool {
+        matches!(self, GenerationError::Auth { .. })
+    }
+
+    /// Worth sending the same request again after a pause
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, GenerationError::Server { status, .. } if *status >= 500)
+    }
+}
+
+impl Unauthorized for GenerationError {
+    fn is_unauthorized(&self) -> bool {
+        matches!(self, GenerationError::Auth { status: 401 })
+    }
+
+    fn session_expired() -> Self {
+        GenerationError::Auth { status: 401 }
+    }
+
+    fn network(message: String) -> Self {
+        GenerationError::Network(message)
+    }
+}
+
+impl From<reqwest::Error> for GenerationError {
+    fn from(e: reqwest::Error) -> Self {
+        match e.status() {
+            Some(status) => GenerationError::from_status(status, e.to_string()),
+            None => GenerationError::Network(e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerationError::Auth { status } => {
+                write!(f, "Not signed in or the session expired ({}), sign in again", status)
+            }
+            GenerationError::Server { status, message } if message.trim().is_empty() => {
+                write!(f, "The motion server returned an error ({})", status)
+            }
+            GenerationError::Server { status, message } => {
+                write!(f, "The motion server returned an error ({}): {}", status, message.trim())
+            }
+            GenerationError::Parse { message, raw_response: Some(path) } => {
+                write!(f, "Couldn't read the motion server's response: {} (saved to {})", message, path.display())
+            }
+            GenerationError::Parse { message, raw_response: None } => {
+                write!(f, "Couldn't read the motion server's response: {}", message)
+            }
+            GenerationError::Network(message) => write!(f, "Couldn't reach the motion server: {}", message),
+            GenerationError::TimedOut(timeout) => {
+                write!(f, "The motion server didn't answer within {}s", timeout.as_secs())
+            }
+            GenerationError::Cancelled => write!(f, "Motion generation was cancelled"),
+        }
+    }
+}
+
+/// How long a request may take and how often it's retried
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Limit for each attempt
+    pub timeout: Duration,
+    pub max_attempts: u32,
+    /// Pause before the second attempt; doubled after every retry
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(60),
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Pause after the given failed attempt, counting from 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// One motion generation in flight, which may be retried and cancelled.
+/// Cloning shares the cancel token.
+#[derive(Debug, Clone, Default)]
+pub struct GenerationJob {
+    pub policy: RetryPolicy,
+    cancel: CancelToken,
+}
+
+impl GenerationJob {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            cancel: CancelToken::new(),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Run `attempt` until it succeeds, fails for good, runs out of attempts or the job is cancelled
+    pub async fn run<T, F, Fut>(&self, mut attempt: F) -> Result<T, GenerationError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, GenerationError>>,
+    {
+        let mut attempt_number = 1;
+        loop {
+            let result = tokio::select! {
+                _ = self.cancel.cancelled() => Err(GenerationError::Cancelled),
+                result = tokio::time::timeout(self.policy.timeout, attempt()) => {
+                    result.unwrap_or(Err(GenerationError::TimedOut(self.policy.timeout)))
+                }
+            };
+
+            match result {
+                Err(e) if e.is_retryable() && attempt_number < self.policy.max_attempts => {
+                    let backoff = self.policy.backoff(attempt_number);
+                    println!("Motion generation attempt {} failed, retrying in {:?}: {}", attempt_number, backoff, e);
+
+                    tokio::select! {
+                        _ = self.cancel.cancelled() => return Err(GenerationError::Cancelled),
+                        _ = tokio::time::sleep(backoff) => {}
+                    }
+                    attempt_number += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Keep a response that couldn't be parsed, returning where it went
+fn save_failed_response(raw_response: &str) -> Option<PathBuf> {
+    let dir = get_ground_truth_dir()
+        .expect("Couldn't get CommonOS directory")
+        .join(FAILED_RESPONSES_DIRNAME);
+    let path = dir.join(format!("{}.json", chrono::Utc::now().format("%Y%m%d-%H%M%S%.3f")));
+
+    let saved = fs::create_dir_all(&dir).and_then(|_| fs::write(&path, raw_response));
+    match saved {
+        Ok(()) => {
+            println!("Saved the unreadable motion response to {}", path.display());
+            Some(path)
+        }
+        Err(e) => {
+            println!("Failed to save the unreadable motion response: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Instant;
+
+    fn quick_policy() -> RetryPolicy {
+        RetryPolicy {
+            timeout: Duration::from_secs(5),
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+        }
+    }
+
+    fn server_error(status: u16) -> GenerationError {
+        GenerationError::Server {
+            status,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy::default();
+
+        let backoffs: Vec<u64> = (1..=6).map(|attempt| policy.backoff(attempt).as_secs()).collect();
+        assert_eq!(backoffs, vec![1, 2, 4, 8, 8, 8]);
+        assert_eq!(policy.backoff(u32::MAX), policy.max_backoff);
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried() {
+        let job = GenerationJob::new(quick_policy());
+        let attempts = AtomicU32::new(0);
+
+        let result = job
+            .run(|| {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                async move {
+                    if attempt < 3 {
+                        Err(server_error(503))
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            })
+            .await;
+
+        assert!(matches!(result, Ok(3)), "{:?}", result);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn retries_stop_after_the_last_attempt() {
+        let job = GenerationJob::new(quick_policy());
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<(), _> = job
+            .run(|| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { Err(server_error(500)) }
+            })
+            .await;
+
+        assert!(matches!(result, Err(GenerationError::Server { status: 500, .. })), "{:?}", result);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn final_errors_are_not_retried() {
+        let cases = [
+            ("auth", GenerationError::Auth { status: 401 }),
+            ("forbidden", GenerationError::Auth { status: 403 }),
+            ("client error", server_error(400)),
+            (
+                "parse",
+                GenerationError::Parse {
+                    message: "missing keyframes".to_string(),
+                    raw_response: None,
+                },
+            ),
+        ];
+
+        for (name, error) in cases {
+            let job = GenerationJob::new(quick_policy());
+            let attempts = AtomicU32::new(0);
+
+            let result: Result<(), _> = job
+                .run(|| {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    let error = error.clone();
+                    async move { Err(error) }
+                })
+                .await;
+
+            assert!(result.is_err(), "{}", name);
+            assert_eq!(attempts.load(Ordering::SeqCst), 1, "{}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn cancelling_during_the_backoff_stops_the_retries() {
+        let job = GenerationJob::new(RetryPolicy {
+            initial_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(30),
+            ..quick_policy()
+        });
+        let attempts = AtomicU32::new(0);
+        let started = Instant::now();
+
+        let canceller = job.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            canceller.cancel();
+        });
+
+        let result: Result<(), _> = job
+            .run(|| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { Err(server_error(502)) }
+            })
+            .await;
+
+        assert!(matches!(result, Err(GenerationError::Cancelled)), "{:?}", result);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
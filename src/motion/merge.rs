@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use stunts_engine::animations::{AnimationData, AnimationProperty, KeyType};
+
+/// How a generated animation is combined with the object's existing motion
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeMode {
+    /// Discard the existing motion
+    #[default]
+    ReplaceAll,
+    /// Replace only the properties the new animation contains, keeping the rest
+    ReplacePresent,
+    /// Play the new animation after the existing one ends
+    Append,
+}
+
+impl MergeMode {
+    pub const ALL: [MergeMode; 3] = [MergeMode::ReplaceAll, MergeMode::ReplacePresent, MergeMode::Append];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MergeMode::ReplaceAll => "Replace all motion",
+            MergeMode::ReplacePresent => "Replace described properties",
+            MergeMode::Append => "Append after current end",
+        }
+    }
+
+    /// Value used by the motion form's dropdown
+    pub fn key(&self) -> &'static str {
+        match self {
+            MergeMode::ReplaceAll => "replace_all",
+            MergeMode::ReplacePresent => "replace_present",
+            MergeMode::Append => "append",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<MergeMode> {
+        MergeMode::ALL.into_iter().find(|mode| mode.key() == key)
+    }
+}
+
+/// An animation ready to be applied to the object the motion arrow was drawn on
+#[derive(Debug, Clone)]
+pub struct GeneratedMotion {
+    pub animation: AnimationData,
+    pub merge_mode: MergeMode,
+    /// One of several requested variants, kept as a candidate to preview instead of applied
+    pub candidate: bool,
+}
+
+impl GeneratedMotion {
+    pub fn new(animation: AnimationData, merge_mode: MergeMode) -> Self {
+        Self {
+            animation,
+            merge_mode,
+            candidate: false,
+        }
+    }
+
+    pub fn candidate(animation: AnimationData, merge_mode: MergeMode) -> Self {
+        Self {
+            candidate: true,
+            ..Self::new(animation, merge_mode)
+        }
+    }
+}
+
+/// Combine `incoming` with the object's current animation according to `mode`.
+/// The result keeps the incoming animation's id and polygon. The incoming start time is the
+/// requested delay: replacing takes it as the new start, appending waits that long after
+/// the existing motion ends.
+pub fn merge_animation(existing: Option<&AnimationData>, incoming: AnimationData, mode: MergeMode) -> AnimationData {
+    let existing = match (existing, mode) {
+        (Some(existing), MergeMode::ReplacePresent | MergeMode::Append) => existing,
+        _ => return incoming,
+    };
+
+    let mut merged = existing.clone();
+    merged.id = incoming.id.clone();
+    merged.polygon_id = incoming.polygon_id.clone();
+
+    if mode == MergeMode::Append {
+        let gap = Duration::from_millis(incoming.start_time_ms.max(0) as u64);
+        let offset = existing.duration + gap;
+
+        for mut property in incoming.properties {
+            shift_keyframes(&mut property, offset);
+
+            match find_property(&mut merged.properties, &property) {
+                Some(current) => current.keyframes.extend(property.keyframes),
+                None => merged.properties.push(property),
+            }
+        }
+
+        merged.duration = offset + incoming.duration;
+    } else {
+        merged.start_time_ms = incoming.start_time_ms;
+        for property in incoming.properties {
+            match find_property(&mut merged.properties, &property) {
+                Some(current) => *current = property,
+                None => merged.properties.push(property),
+            }
+        }
+
+        merged.duration = merged.duration.max(incoming.duration);
+    }
+
+    merged
+}
+
+/// The property animating the same thing, matched by path and then by name
+fn find_property<'a>(properties: &'a mut [AnimationProperty], property: &AnimationProperty) -> Option<&'a mut AnimationProperty> {
+    let index = properties
+        .iter()
+        .position(|current| !property.property_path.is_empty() && current.property_path == property.property_path)
+        .or_else(|| properties.iter().position(|current| current.name == property.name))?;
+
+    properties.get_mut(index)
+}
+
+fn shift_keyframes(property: &mut AnimationProperty, offset: Duration) {
+    for keyframe in &mut property.keyframes {
+        keyframe.time += offset;
+
+        if let KeyType::Range(range) = &mut keyframe.key_type {
+            range.end_time += offset;
+        }
+    }
+
+    for child in &mut property.children {
+        shift_keyframes(child, offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stunts_engine::animations::{EasingType, KeyframeValue, ObjectType, UIKeyframe};
+    use stunts_engine::editor::PathType;
+
+    fn property(name: &str, times_ms: &[u64]) -> AnimationProperty {
+        AnimationProperty {
+            name: name.to_string(),
+            property_path: name.to_lowercase(),
+            children: Vec::new(),
+            keyframes: times_ms
+                .iter()
+                .map(|ms| UIKeyframe {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    time: Duration::from_millis(*ms),
+                    value: KeyframeValue::Opacity(100),
+                    easing: EasingType::Linear,
+                    path_type: PathType::Linear,
+                    key_type: KeyType::Frame,
+                })
+                .collect(),
+            depth: 0,
+        }
+    }
+
+    fn animation(start_time_ms: i32, duration_ms: u64, properties: Vec<AnimationProperty>) -> AnimationData {
+        AnimationData {
+            id: uuid::Uuid::new_v4().to_string(),
+            object_type: ObjectType::Polygon,
+            polygon_id: "object".to_string(),
+            duration: Duration::from_millis(duration_ms),
+            start_time_ms,
+            properties,
+            position: [0, 0],
+        }
+    }
+
+    fn times(property: &AnimationProperty) -> Vec<u64> {
+        property.keyframes.iter().map(|keyframe| keyframe.time.as_millis() as u64).collect()
+    }
+
+    #[test]
+    fn replace_all_takes_incoming() {
+        let existing = animation(500, 3000, vec![property("Position", &[0, 3000])]);
+        let incoming = animation(1000, 2000, vec![property("Opacity", &[0, 2000])]);
+
+        let merged = merge_animation(Some(&existing), incoming.clone(), MergeMode::ReplaceAll);
+        assert_eq!(merged.id, incoming.id);
+        assert_eq!(merged.start_time_ms, 1000);
+        assert_eq!(merged.properties.len(), 1);
+    }
+
+    #[test]
+    fn replace_present_keeps_other_properties_and_takes_the_delay() {
+        let existing = animation(500, 3000, vec![property("Position", &[0, 3000]), property("Opacity", &[0, 3000])]);
+        let incoming = animation(1000, 2000, vec![property("Opacity", &[0, 1000, 2000])]);
+
+        let merged = merge_animation(Some(&existing), incoming, MergeMode::ReplacePresent);
+        assert_eq!(merged.start_time_ms, 1000);
+        assert_eq!(merged.duration, Duration::from_millis(3000));
+        assert_eq!(times(&merged.properties[0]), vec![0, 3000]);
+        assert_eq!(times(&merged.properties[1]), vec![0, 1000, 2000]);
+    }
+
+    #[test]
+    fn append_waits_for_the_delay_after_the_existing_motion() {
+        let existing = animation(500, 3000, vec![property("Position", &[0, 3000])]);
+        let incoming = animation(1000, 2000, vec![property("Position", &[0, 2000]), property("Opacity", &[0, 2000])]);
+
+        let merged = merge_animation(Some(&existing), incoming, MergeMode::Append);
+        assert_eq!(merged.start_time_ms, 500);
+        assert_eq!(merged.duration, Duration::from_millis(6000));
+        assert_eq!(times(&merged.properties[0]), vec![0, 3000, 4000, 6000]);
+        assert_eq!(times(&merged.properties[1]), vec![4000, 6000]);
+    }
+
+    #[test]
+    fn without_existing_motion_takes_incoming() {
+        let incoming = animation(1000, 2000, vec![property("Opacity", &[0, 2000])]);
+
+        let merged = merge_animation(None, incoming, MergeMode::Append);
+        assert_eq!(merged.start_time_ms, 1000);
+        assert_eq!(merged.duration, Duration::from_millis(2000));
+    }
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+pub mod apply;
+pub mod candidates;
+pub mod generator;
+pub mod job;
+pub mod merge;
+pub mod prompts;
+pub mod timing;
+
+pub use apply::{apply_generated_motion, apply_generated_motions, end_motion_preview, preview_generated_motion, MotionUpdate};
+pub use candidates::{candidate_label, MotionCandidate, MotionCandidates, MAX_VARIANTS};
+pub use generator::{generate_motion, generate_motion_variant};
+pub use job::{GenerationError, GenerationJob, RetryPolicy};
+pub use merge::{merge_animation, GeneratedMotion, MergeMode};
+pub use prompts::{MotionArrow, MotionPrompts, SavedMotionPrompt};
+pub use timing::{parse_delay, parse_duration, MotionTiming};
+
+/// What the user typed into the motion form, one phrase per property
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MotionPrompt {
+    pub description: String,
+    pub position: String,
+    pub scale: String,
+    pub opacity: String,
+    pub rotation: String,
+    pub delay: String,
+    pub duration: String,
+}
+
+impl MotionPrompt {
+    /// `property_path`s of the properties the user described, e.g. "opacity" when only
+    /// the opacity field was filled in
+    pub fn described_properties(&self) -> Vec<&'static str> {
+        [
+            ("position", &self.position),
+            ("scale", &self.scale),
+            ("opacity", &self.opacity),
+            ("rotation", &self.rotation),
+        ]
+        .into_iter()
+        .filter(|(_, phrase)| !phrase.trim().is_empty())
+        .map(|(property_path, _)| property_path)
+        .collect()
+    }
+}
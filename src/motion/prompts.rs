@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use stunts_engine::animations::ObjectType;
+use stunts_engine::editor::Point;
+
+use super::{MergeMode, MotionPrompt};
+use crate::helpers::projects::{load_project_file, save_project_file};
+
+const PROMPTS_FILENAME: &str = "motion_prompts.json";
+
+/// A motion arrow, or a chain of them, in canvas coordinates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MotionArrow {
+    pub start: [f32; 2],
+    pub end: [f32; 2],
+    /// Where chained arrows meet, in order from the start
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub via: Vec<[f32; 2]>,
+}
+
+impl MotionArrow {
+    /// `None` for fewer than two points
+    pub fn from_points(waypoints: &[Point]) -> Option<Self> {
+        let (start, end) = (waypoints.first()?, waypoints.last()?);
+        if waypoints.len() < 2 {
+            return None;
+        }
+
+        Some(Self {
+            start: [start.x, start.y],
+            end: [end.x, end.y],
+            via: waypoints[1..waypoints.len() - 1]
+                .iter()
+                .map(|point| [point.x, point.y])
+                .collect(),
+        })
+    }
+
+    /// Start, waypoints and end, in order
+    pub fn to_points(&self) -> Vec<Point> {
+        std::iter::once(&self.start)
+            .chain(&self.via)
+            .chain(std::iter::once(&self.end))
+            .map(|[x, y]| Point { x: *x, y: *y })
+            .collect()
+    }
+}
+
+/// The last motion form submitted for an object, kept so it can be run again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedMotionPrompt {
+    pub prompt: MotionPrompt,
+    pub arrow: Option<MotionArrow>,
+    pub object_type: ObjectType,
+    #[serde(default)]
+    pub merge_mode: MergeMode,
+    pub updated: DateTime<Utc>,
+}
+
+/// The last motion prompt of each of a project's objects, in `motion_prompts.json` in the project folder
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MotionPrompts {
+    /// Prompt per object id
+    pub objects: HashMap<String, SavedMotionPrompt>,
+}
+
+impl MotionPrompts {
+    pub fn load(project_id: &str) -> MotionPrompts {
+        load_project_file(project_id, PROMPTS_FILENAME)
+    }
+
+    pub fn save(&self, project_id: &str) -> anyhow::Result<()> {
+        save_project_file(project_id, PROMPTS_FILENAME, self)
+    }
+
+    pub fn get(&self, object_id: &str) -> Option<&SavedMotionPrompt> {
+        self.objects.get(object_id)
+    }
+
+    /// Remember the prompt for an object, replacing the previous one
+    pub fn remember(&mut self, object_id: String, prompt: SavedMotionPrompt) {
+        self.objects.insert(object_id, prompt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::projects::{load_json_file, save_json_file};
+
+    fn points(coords: &[(f32, f32)]) -> Vec<Point> {
+        coords.iter().map(|&(x, y)| Point { x, y }).collect()
+    }
+
+    fn coords(points: &[Point]) -> Vec<(f32, f32)> {
+        points.iter().map(|point| (point.x, point.y)).collect()
+    }
+
+    #[test]
+    fn arrows_keep_their_waypoints_in_order() {
+        let cases: &[&[(f32, f32)]] = &[
+            &[(0.0, 0.0), (100.0, 50.0)],
+            &[(0.0, 0.0), (40.0, 80.0), (120.0, 60.0), (200.0, 10.0)],
+        ];
+
+        for &case in cases {
+            let arrow = MotionArrow::from_points(&points(case)).unwrap();
+            assert_eq!(arrow.start, [case[0].0, case[0].1]);
+            assert_eq!(arrow.end, [case[case.len() - 1].0, case[case.len() - 1].1]);
+            assert_eq!(arrow.via.len(), case.len() - 2);
+            assert_eq!(coords(&arrow.to_points()), case);
+        }
+    }
+
+    #[test]
+    fn an_arrow_needs_two_points() {
+        assert_eq!(MotionArrow::from_points(&[]), None);
+        assert_eq!(MotionArrow::from_points(&points(&[(10.0, 10.0)])), None);
+    }
+
+    #[test]
+    fn prompts_survive_a_save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("stunts-prompts-{}", uuid::Uuid::new_v4()))
+            .join(PROMPTS_FILENAME);
+
+        let mut prompts = MotionPrompts::default();
+        prompts.remember(
+            "object".to_string(),
+            SavedMotionPrompt {
+                prompt: MotionPrompt {
+                    description: "drift in from the left".to_string(),
+                    duration: "2s".to_string(),
+                    ..MotionPrompt::default()
+                },
+                arrow: MotionArrow::from_points(&points(&[(0.0, 0.0), (50.0, 20.0), (100.0, 0.0)])),
+                object_type: ObjectType::Polygon,
+                merge_mode: MergeMode::ReplaceAll,
+                updated: Utc::now(),
+            },
+        );
+
+        save_json_file(&path, &prompts).unwrap();
+        let loaded: MotionPrompts = load_json_file(&path);
+
+        let saved = prompts.get("object").unwrap();
+        let restored = loaded.get("object").unwrap();
+        assert_eq!(restored.prompt, saved.prompt);
+        assert_eq!(restored.arrow, saved.arrow);
+        assert_eq!(restored.merge_mode, saved.merge_mode);
+        assert!(matches!(restored.object_type, ObjectType::Polygon));
+        assert_eq!(restored.updated, saved.updated);
+
+        let missing: MotionPrompts = load_json_file(&path.with_file_name("missing.json"));
+        assert!(missing.objects.is_empty());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,284 @@
+use std::time::Duration;
+use stunts_engine::animations::{AnimationData, AnimationProperty};
+
+/// Longest duration or delay the form accepts
+const MAX_TIMING: Duration = Duration::from_secs(10 * 60);
+
+/// Speed words for the Duration field, matched by prefix ("slowly" is "slow")
+const DURATION_WORDS: &[(&str, u64)] = &[
+    ("momentar", 500),
+    ("brief", 1000),
+    ("quick", 1000),
+    ("fast", 1500),
+    ("normal", 3000),
+    ("medium", 3000),
+    ("moderat", 3000),
+    ("slow", 5000),
+    ("leisure", 6000),
+    ("long", 6000),
+];
+
+/// Words for the Delay field
+const DELAY_WORDS: &[(&str, u64)] = &[
+    ("none", 0),
+    ("no", 0),
+    ("immediate", 0),
+    ("instant", 0),
+    ("momentar", 500),
+    ("brief", 500),
+    ("quick", 500),
+    ("short", 1000),
+    ("long", 3000),
+];
+
+/// Words that carry no meaning, e.g. "after about 2 seconds"
+const FILLER_WORDS: &[&str] = &[
+    "and", "about", "around", "roughly", "approximately", "for", "after", "over", "of", "delay",
+    "delayed", "wait", "lasting", "in", "then", "total", "the",
+];
+
+/// Duration and delay from the motion form, `None` where the field was left empty
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MotionTiming {
+    pub duration: Option<Duration>,
+    pub delay: Option<Duration>,
+}
+
+impl MotionTiming {
+    /// Stretch the keyframes to the requested duration and start the animation after the delay
+    pub fn apply(&self, animation: &mut AnimationData) {
+        if let Some(duration) = self.duration {
+            if !animation.duration.is_zero() {
+                let factor = duration.as_secs_f64() / animation.duration.as_secs_f64();
+                retime(&mut animation.properties, factor);
+            }
+            animation.duration = duration;
+        }
+
+        if let Some(delay) = self.delay {
+            animation.start_time_ms = delay.as_millis() as i32;
+        }
+    }
+}
+
+/// Parse the Duration field, e.g. "slow", "5 seconds", "1500ms", "1.5s", "1 min 30 s"
+pub fn parse_duration(text: &str) -> Result<Option<Duration>, String> {
+    let duration = parse_timing(text, DURATION_WORDS)?;
+
+    if duration == Some(Duration::ZERO) {
+        return Err("Duration must be longer than zero".to_string());
+    }
+
+    Ok(duration)
+}
+
+/// Parse the Delay field, e.g. "momentary", "2 seconds", "none"
+pub fn parse_delay(text: &str) -> Result<Option<Duration>, String> {
+    parse_timing(text, DELAY_WORDS)
+}
+
+enum Token {
+    Number(f64),
+    Word(String),
+}
+
+fn parse_timing(text: &str, words: &[(&str, u64)]) -> Result<Option<Duration>, String> {
+    let text = text.trim().to_lowercase();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    let mut total_ms = 0.0;
+    let mut has_amount = false;
+    let mut keyword_ms: Option<f64> = None;
+    let mut pending: Option<f64> = None;
+    // Whether `pending` was written out ("2", "one") rather than implied by "a"
+    let mut pending_explicit = false;
+    let mut very = false;
+
+    for token in tokenize(&text)? {
+        match token {
+            Token::Number(number) => {
+                if let Some(previous) = pending {
+                    return Err(format!("Add a unit after {}, e.g. {}s or {}ms", previous, previous, previous));
+                }
+                pending = Some(number);
+                pending_explicit = true;
+            }
+            Token::Word(word) => {
+                if let Some(unit_ms) = unit_ms(&word) {
+                    // "second" on its own means one second
+                    total_ms += pending.take().unwrap_or(1.0) * unit_ms;
+                    has_amount = true;
+                } else if word == "one" {
+                    pending = Some(1.0);
+                    pending_explicit = true;
+                } else if matches!(word.as_str(), "a" | "an") {
+                    if pending.is_none() {
+                        pending = Some(1.0);
+                        pending_explicit = false;
+                    }
+                } else if word == "half" {
+                    // "half a second", "one and a half seconds"
+                    pending = Some(match pending {
+                        Some(whole) if pending_explicit => whole + 0.5,
+                        _ => 0.5,
+                    });
+                } else if word == "very" || word == "really" || word == "extra" {
+                    very = true;
+                } else if let Some(ms) = keyword(&word, words) {
+                    keyword_ms = Some(ms as f64);
+                } else if !FILLER_WORDS.contains(&word.as_str()) {
+                    return Err(format!("Didn't understand \"{}\", try e.g. \"2 seconds\" or \"1500ms\"", word));
+                }
+            }
+        }
+    }
+
+    // A bare number is seconds
+    if let Some(seconds) = pending {
+        total_ms += seconds * 1000.0;
+        has_amount = true;
+    }
+
+    let ms = if has_amount {
+        total_ms
+    } else if let Some(ms) = keyword_ms {
+        match (very, ms) {
+            (false, ms) => ms,
+            // Very slow is slower, very fast is faster
+            (true, ms) if ms >= 3000.0 => ms * 1.6,
+            (true, ms) => ms * 0.5,
+        }
+    } else {
+        return Err("Enter a time, e.g. \"2 seconds\", \"1500ms\" or \"slow\"".to_string());
+    };
+
+    // Check before converting, as Duration panics on values it can't hold, e.g. a long run of digits
+    if !(ms <= MAX_TIMING.as_millis() as f64) {
+        return Err(format!(
+            "{:.1} seconds is too long, keep it under {} minutes",
+            ms / 1000.0,
+            MAX_TIMING.as_secs() / 60
+        ));
+    }
+
+    Ok(Some(Duration::from_secs_f64(ms / 1000.0)))
+}
+
+/// Split "1.5s" into a number and a word; commas, hyphens and spaces separate tokens
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                number.push(c);
+                chars.next();
+            }
+
+            let value = number
+                .parse::<f64>()
+                .map_err(|_| format!("\"{}\" is not a number", number))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphabetic() || c == '\'' {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_alphabetic() || **c == '\'') {
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        } else {
+            chars.next();
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn unit_ms(word: &str) -> Option<f64> {
+    match word {
+        "ms" | "msec" | "msecs" | "millisecond" | "milliseconds" => Some(1.0),
+        "s" | "sec" | "secs" | "second" | "seconds" => Some(1000.0),
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(60_000.0),
+        _ => None,
+    }
+}
+
+fn keyword(word: &str, words: &[(&str, u64)]) -> Option<u64> {
+    words
+        .iter()
+        .find(|(key, _)| word == *key || (key.len() >= 4 && word.starts_with(key)))
+        .map(|(_, ms)| *ms)
+}
+
+fn retime(properties: &mut [AnimationProperty], factor: f64) {
+    for property in properties {
+        for keyframe in &mut property.keyframes {
+            keyframe.time = Duration::from_secs_f64(keyframe.time.as_secs_f64() * factor);
+        }
+        retime(&mut property.children, factor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Option<Duration> {
+        Some(Duration::from_millis(ms))
+    }
+
+    #[test]
+    fn parses_units() {
+        assert_eq!(parse_duration("1500ms"), Ok(ms(1500)));
+        assert_eq!(parse_duration("1.5s"), Ok(ms(1500)));
+        assert_eq!(parse_duration("2 seconds"), Ok(ms(2000)));
+        assert_eq!(parse_duration("1 min 30 s"), Ok(ms(90_000)));
+        assert_eq!(parse_duration("3"), Ok(ms(3000)));
+        assert_eq!(parse_duration("second"), Ok(ms(1000)));
+    }
+
+    #[test]
+    fn parses_words() {
+        assert_eq!(parse_duration("slow"), Ok(ms(5000)));
+        assert_eq!(parse_duration("Slowly"), Ok(ms(5000)));
+        assert_eq!(parse_duration("very slow"), Ok(ms(8000)));
+        assert_eq!(parse_duration("very fast"), Ok(ms(750)));
+        assert_eq!(parse_duration("half a second"), Ok(ms(500)));
+        assert_eq!(parse_duration("one and a half seconds"), Ok(ms(1500)));
+        assert_eq!(parse_delay("after about 2 seconds"), Ok(ms(2000)));
+        assert_eq!(parse_delay("none"), Ok(ms(0)));
+        assert_eq!(parse_delay("momentary"), Ok(ms(500)));
+    }
+
+    #[test]
+    fn empty_is_unset() {
+        assert_eq!(parse_duration(""), Ok(None));
+        assert_eq!(parse_delay("   "), Ok(None));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("2 3").is_err());
+        assert!(parse_duration("soon-ish").is_err());
+        assert!(parse_duration("1.2.3s").is_err());
+    }
+
+    #[test]
+    fn enforces_bounds() {
+        assert_eq!(parse_duration("10 minutes"), Ok(Some(MAX_TIMING)));
+        assert!(parse_duration("10 minutes 1ms").is_err());
+        assert!(parse_delay("11 min").is_err());
+    }
+
+    #[test]
+    fn huge_numbers_are_errors_not_panics() {
+        assert!(parse_duration("99999999999999999999").is_err());
+        assert!(parse_delay("99999999999999999999 minutes").is_err());
+        assert!(parse_duration(&"9".repeat(400)).is_err());
+    }
+}
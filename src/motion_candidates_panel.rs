@@ -0,0 +1,104 @@
+use gui_core::{Element, widgets::*};
+use gui_core::widgets::container::{Background, Padding};
+use gui_core::widgets::text::text_signal;
+use gui_reactive::Signal;
+use std::sync::mpsc;
+use vello::peniko::{Color, Gradient};
+
+use crate::commands::Command;
+
+/// State of the motion variants panel, updated by the object command handler
+#[derive(Clone)]
+pub struct MotionCandidateSignals {
+    pub visible: Signal<bool>,
+    /// Id of the object whose variants are browsed
+    pub object_id: Signal<String>,
+    /// Id of the variant previewed on the canvas; indexes shift as old variants are dropped
+    pub candidate_id: Signal<String>,
+    pub label: Signal<String>,
+}
+
+impl MotionCandidateSignals {
+    pub fn new() -> Self {
+        Self {
+            visible: Signal::new(false),
+            object_id: Signal::new(String::new()),
+            candidate_id: Signal::new(String::new()),
+            label: Signal::new(String::new()),
+        }
+    }
+}
+
+pub fn create_motion_candidates_panel(
+    command_tx: mpsc::Sender<Command>,
+    signals: MotionCandidateSignals,
+    button_normal: Gradient,
+    button_hover: Gradient,
+    button_pressed: Gradient,
+) -> Element {
+    let panel_button = |label: &str, width: f32, command: Command| {
+        Element::new_widget(Box::new(
+            button(label)
+                .with_font_size(12.0)
+                .with_width(width)
+                .with_height(25.0)
+                .with_backgrounds(
+                    Background::Gradient(button_normal.clone()),
+                    Background::Gradient(button_hover.clone()),
+                    Background::Gradient(button_pressed.clone())
+                )
+                .on_click({
+                    let tx = command_tx.clone();
+                    move || {
+                        let _ = tx.send(command.clone());
+                    }
+                })
+        ))
+    };
+
+    container()
+        .absolute()
+        .with_position(850.0, 280.0)
+        .with_size(300.0, 150.0)
+        .with_background_color(Color::rgba8(50, 50, 60, 240))
+        .with_border_radius(8.0)
+        .with_padding(Padding::all(15.0))
+        .with_shadow(4.0, 4.0, 8.0, Color::rgba8(0, 0, 0, 150))
+        .with_display_signal(signals.visible.clone())
+        .with_child(
+            column()
+                .with_size(270.0, 120.0)
+                .with_main_axis_alignment(MainAxisAlignment::Start)
+                .with_cross_axis_alignment(CrossAxisAlignment::Start)
+                .with_child(Element::new_widget(Box::new(
+                    text("Motion Variants")
+                        .with_font_size(14.0)
+                        .with_color(Color::rgba8(255, 255, 255, 255))
+                )))
+                .with_child(Element::new_widget(Box::new(
+                    text_signal(signals.label.clone())
+                        .with_font_size(12.0)
+                        .with_color(Color::rgba8(200, 200, 200, 255))
+                )))
+                .with_child(
+                    row()
+                        .with_size(270.0, 35.0)
+                        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+                        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+                        .with_child(panel_button("Previous", 80.0, Command::ShowMotionCandidate { step: -1 }))
+                        .with_child(panel_button("Next", 80.0, Command::ShowMotionCandidate { step: 1 }))
+                        .into_container_element()
+                )
+                .with_child(
+                    row()
+                        .with_size(270.0, 35.0)
+                        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+                        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+                        .with_child(panel_button("Use This Variant", 120.0, Command::ApplyMotionCandidate))
+                        .with_child(panel_button("Close", 80.0, Command::DiscardMotionCandidates))
+                        .into_container_element()
+                )
+                .into_container_element()
+        )
+        .into_container_element()
+}
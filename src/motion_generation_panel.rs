@@ -0,0 +1,122 @@
+use gui_core::{Element, widgets::*};
+use gui_core::widgets::container::{Background, Padding};
+use gui_core::widgets::text::text_signal;
+use gui_reactive::Signal;
+use std::sync::mpsc;
+use vello::peniko::{Color, Gradient};
+
+use crate::commands::Command;
+
+/// State of the motion generation panel, updated from the generation tasks
+#[derive(Clone)]
+pub struct MotionGenerationSignals {
+    pub visible: Signal<bool>,
+    pub status: Signal<String>,
+    /// Why the last generation failed, empty when it didn't
+    pub error: Signal<String>,
+}
+
+impl MotionGenerationSignals {
+    pub fn new() -> Self {
+        Self {
+            visible: Signal::new(false),
+            status: Signal::new(String::new()),
+            error: Signal::new(String::new()),
+        }
+    }
+
+    pub fn start(&self, status: &str) {
+        self.status.set(status.to_string());
+        self.error.set(String::new());
+        self.visible.set(true);
+    }
+
+    /// Keep the panel open with the error until it's closed
+    pub fn fail(&self, status: &str, error: &str) {
+        self.status.set(status.to_string());
+        self.error.set(error.to_string());
+        self.visible.set(true);
+    }
+
+    /// Hide the panel unless it's showing an error
+    pub fn finish(&self) {
+        if self.error.get().is_empty() {
+            self.visible.set(false);
+        }
+    }
+
+    pub fn close(&self) {
+        self.error.set(String::new());
+        self.visible.set(false);
+    }
+}
+
+pub fn create_motion_generation_panel(
+    command_tx: mpsc::Sender<Command>,
+    signals: MotionGenerationSignals,
+    button_normal: Gradient,
+    button_hover: Gradient,
+    button_pressed: Gradient,
+) -> Element {
+    let panel_button = |label: &str, command: Command| {
+        Element::new_widget(Box::new(
+            button(label)
+                .with_font_size(12.0)
+                .with_width(110.0)
+                .with_height(25.0)
+                .with_backgrounds(
+                    Background::Gradient(button_normal.clone()),
+                    Background::Gradient(button_hover.clone()),
+                    Background::Gradient(button_pressed.clone())
+                )
+                .on_click({
+                    let tx = command_tx.clone();
+                    move || {
+                        let _ = tx.send(command.clone());
+                    }
+                })
+        ))
+    };
+
+    container()
+        .absolute()
+        .with_position(850.0, 440.0)
+        .with_size(300.0, 150.0)
+        .with_background_color(Color::rgba8(50, 50, 60, 240))
+        .with_border_radius(8.0)
+        .with_padding(Padding::all(15.0))
+        .with_shadow(4.0, 4.0, 8.0, Color::rgba8(0, 0, 0, 150))
+        .with_display_signal(signals.visible.clone())
+        .with_child(
+            column()
+                .with_size(270.0, 120.0)
+                .with_main_axis_alignment(MainAxisAlignment::Start)
+                .with_cross_axis_alignment(CrossAxisAlignment::Start)
+                .with_child(Element::new_widget(Box::new(
+                    text("Motion Generation")
+                        .with_font_size(14.0)
+                        .with_color(Color::rgba8(255, 255, 255, 255))
+                )))
+                .with_child(Element::new_widget(Box::new(
+                    text_signal(signals.status.clone())
+                        .with_font_size(12.0)
+                        .with_color(Color::rgba8(200, 200, 200, 255))
+                )))
+                .with_child(Element::new_widget(Box::new(
+                    text_signal(signals.error.clone())
+                        .with_font_size(11.0)
+                        .with_color(Color::rgba8(255, 120, 120, 255))
+                )))
+                .with_child(
+                    row()
+                        .with_size(270.0, 35.0)
+                        .with_main_axis_alignment(MainAxisAlignment::SpaceBetween)
+                        .with_cross_axis_alignment(CrossAxisAlignment::Center)
+                        .with_child(panel_button("Cancel", Command::CancelMotionGeneration))
+                        .with_child(panel_button("Close", Command::CloseMotionGenerationPanel))
+                        .into_container_element()
+                )
+                .into_container_element()
+        )
+        .into_container_element()
+}
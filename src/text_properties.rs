@@ -3,7 +3,7 @@ use gui_core::widgets::dropdown::{DropdownOption, dropdown};
 use gui_reactive::Signal;
 use vello::peniko::{Color, Gradient};
 use std::sync::mpsc;
-use crate::Command;
+use crate::commands::Command;
 
 pub fn create_text_properties_panel(
     command_tx: mpsc::Sender<Command>,
@@ -2,7 +2,7 @@ use gui_core::{Element, widgets::*};
 use gui_core::widgets::container::Background;
 use vello::peniko::Color;
 use std::sync::mpsc;
-use crate::Command;
+use crate::commands::Command;
 
 pub const THEME_COLORS: [[&str; 5]; 10] = [
     ["#FFE4E1", "#FF6B6B", "#FF0000", "#B22222", "#8B0000"], // red